@@ -31,10 +31,18 @@ export default defineConfig({
         items: [
           { text: 'Writing Tests', link: '/guides/writing-tests' },
           { text: 'Variables & Chaining', link: '/guides/variables-chaining' },
+          { text: 'Scenarios', link: '/guides/scenarios' },
           { text: 'Authentication', link: '/guides/authentication' },
           { text: 'Request Bodies', link: '/guides/request-bodies' },
+          { text: 'GraphQL', link: '/guides/graphql' },
+          { text: 'WebSockets', link: '/guides/websocket' },
+          { text: 'gRPC', link: '/guides/grpc' },
+          { text: 'Streaming Responses', link: '/guides/streaming' },
           { text: 'Assertions', link: '/guides/assertions' },
           { text: 'Hooks', link: '/guides/hooks' },
+          { text: 'Data-Driven Tests', link: '/guides/data-driven-tests' },
+          { text: 'Mock Server', link: '/guides/mock-server' },
+          { text: 'Recording Traffic', link: '/guides/recording' },
           { text: 'AI Context Specs', link: '/guides/ai-context-specs' },
           { text: 'Run, Filter, and Debug', link: '/guides/run-debug' },
           { text: 'CI/CD Integration', link: '/guides/ci-cd' },
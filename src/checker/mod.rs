@@ -1,5 +1,7 @@
 pub mod parser;
+pub mod references;
+pub mod templates;
 pub mod validator;
 
-pub use parser::{list_tests, parse_tests};
-pub use validator::validate;
+pub use parser::{list_tests, list_variables, load_document, parse_tests};
+pub use validator::{validate, validate_with_variables};
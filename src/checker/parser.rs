@@ -1,44 +1,196 @@
+use crate::checker::templates::apply_templates;
+use crate::engine::dataset::expand_tests;
+use crate::engine::variables::resolve_variables;
 use crate::models::suite::TestSuite;
+use crate::models::test::Test;
+use crate::utils::secrets::{MASK, declare_secret_names, is_secret_variable};
+use crate::utils::string::value_to_string;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::path::Path;
 use toml;
 
-/// Parse tests from a TOML file
+/// Parse tests from a TOML file, merging `[templates.*]` into the tests that extend them
 /// 
 /// # Errors
-/// Returns an error if the file cannot be read or parsed
-pub fn parse_tests(file_path: Option<&str>) -> Result<TestSuite, &'static str> {
-    let path = file_path.unwrap_or(".catalyst/tests.toml");
-    let content = fs::read_to_string(path).map_err(|_| "Failed to read tests file")?;
-    toml::from_str(&content).map_err(|_| "Invalid TOML format")
+/// Returns an error if the file cannot be read or parsed, or a template is unknown or cyclic
+pub fn parse_tests(file_path: Option<&str>) -> Result<TestSuite, String> {
+    let document = load_document(file_path.unwrap_or(".catalyst/tests.toml"))?;
+    for key in ["tests", "setup_tests", "teardown_tests"] {
+        if let Some(toml::Value::Array(tests)) = document.get(key) {
+            require_expected_status(tests)?;
+        }
+    }
+    let mut suite: TestSuite = toml::Value::Table(document)
+        .try_into()
+        .map_err(|_| "Invalid TOML format".to_string())?;
+    let suite_tests = suite.setup_tests.iter_mut().chain(suite.teardown_tests.iter_mut()).flatten();
+    for test in suite.tests.iter_mut().chain(suite_tests) {
+        default_method(test);
+    }
+    Ok(suite)
 }
 
-pub fn list_tests(verbose: bool, file_path: Option<&str>) {
+/// Every request needs `expected_status`; a scenario has none of its own
+fn require_expected_status(tests: &[toml::Value]) -> Result<(), String> {
+    for test in tests.iter().filter_map(toml::Value::as_table) {
+        match test.get("steps") {
+            Some(toml::Value::Array(steps)) => require_expected_status(steps)?,
+            Some(_) => {}
+            None if test.contains_key("expected_status") => {}
+            None => {
+                let name = test.get("name").and_then(toml::Value::as_str).unwrap_or("<unnamed>");
+                return Err(format!("Test `{name}` is missing `expected_status`"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn default_method(test: &mut Test) {
+    if test.method.is_empty() && test.graphql.is_some() {
+        test.method = "POST".to_string();
+    } else if test.method.is_empty() && test.is_websocket() {
+        test.method = "GET".to_string();
+    }
+    for step in test.steps.iter_mut().flatten() {
+        default_method(step);
+    }
+}
+
+/// The raw TOML of a tests file, with templates already merged into the tests
+///
+/// # Errors
+/// Returns an error if the file cannot be read or parsed, or a template is unknown or cyclic
+pub fn load_document(path: &str) -> Result<toml::Table, String> {
+    let content = fs::read_to_string(path).map_err(|_| "Failed to read tests file".to_string())?;
+    let mut document: toml::Table = toml::from_str(&content).map_err(|_| "Invalid TOML format".to_string())?;
+    apply_templates(&mut document).map_err(|errors| errors.join("; "))?;
+    Ok(document)
+}
+
+pub fn list_tests(verbose: bool, file_path: Option<&str>, by_tag: bool) {
+    let test_file_dir = Path::new(file_path.unwrap_or(".catalyst/tests.toml"))
+        .parent()
+        .unwrap_or(Path::new("."));
+
     match parse_tests(file_path) {
-        Ok(test_suite) => {
+        Ok(mut test_suite) => {
+            match expand_tests(&test_suite.tests, test_file_dir) {
+                Ok(tests) => test_suite.tests = tests,
+                Err(err) => {
+                    println!("Failed to list tests: {err}");
+                    return;
+                }
+            }
             if test_suite.tests.is_empty() {
                 println!("No tests found in `tests.toml`.");
                 return;
             }
 
+            if by_tag {
+                list_tests_by_tag(&test_suite.tests, verbose);
+                return;
+            }
+
             println!("Available tests:");
             for test in &test_suite.tests {
-                println!("- {}", test.name);
-                if verbose {
-                    println!("  Method: {}", test.method);
-                    println!("  Endpoint: {}", test.endpoint);
-                    if let Some(query_params) = &test.query_params {
-                        println!("  Query Params: {query_params:?}");
-                    }
-                    if let Some(headers) = &test.headers {
-                        println!("  Headers: {headers:?}");
-                    }
-                    if let Some(body) = &test.body {
-                        println!("  Body: {body}");
-                    }
-                    println!("  Expected Status: {}", test.expected_status);
-                }
+                print_test(test, verbose, "");
             }
         }
         Err(err) => println!("Failed to list tests: {err}"),
     }
 }
+
+/// One group per tag in alphabetical order, then the untagged tests; a test appears under each of its tags
+fn list_tests_by_tag(tests: &[Test], verbose: bool) {
+    let mut groups: BTreeMap<&str, Vec<&Test>> = BTreeMap::new();
+    let mut untagged = Vec::new();
+    for test in tests {
+        match test.tags.as_deref() {
+            Some(tags) if !tags.is_empty() => {
+                for tag in tags {
+                    groups.entry(tag).or_default().push(test);
+                }
+            }
+            _ => untagged.push(test),
+        }
+    }
+
+    println!("Available tests by tag:");
+    let untagged_group = (!untagged.is_empty()).then_some(("(untagged)", untagged));
+    for (tag, tests) in groups.into_iter().chain(untagged_group) {
+        println!("{tag} ({}):", tests.len());
+        for test in tests {
+            print_test(test, verbose, "  ");
+        }
+    }
+}
+
+fn print_test(test: &Test, verbose: bool, indent: &str) {
+    println!("{indent}- {}", test.name);
+    if !verbose {
+        return;
+    }
+    if let Some(template) = &test.extends {
+        println!("{indent}  Extends: {template}");
+    }
+    if let Some(tags) = &test.tags {
+        println!("{indent}  Tags: {}", tags.join(", "));
+    }
+    println!("{indent}  Method: {}", test.method);
+    println!("{indent}  Endpoint: {}", test.endpoint);
+    if let Some(query_params) = &test.query_params {
+        println!("{indent}  Query Params: {query_params:?}");
+    }
+    if let Some(headers) = &test.headers {
+        println!("{indent}  Headers: {headers:?}");
+    }
+    if let Some(body) = &test.body {
+        println!("{indent}  Body: {body}");
+    }
+    println!("{indent}  Expected Status: {}", test.expected_status);
+    if !test.parameters.is_empty() {
+        let parameters: Vec<String> = test
+            .parameters
+            .iter()
+            .map(|(key, value)| format!("{key}={}", value_to_string(value)))
+            .collect();
+        println!("{indent}  Parameters: {}", parameters.join(", "));
+    }
+}
+
+/// Print the variables available before the first test and where each one came from
+pub fn list_variables(
+    file_path: Option<&str>,
+    cli_variables: &HashMap<String, String>,
+    profile: Option<&str>,
+) {
+    let test_suite = match parse_tests(file_path) {
+        Ok(suite) => suite,
+        Err(err) => {
+            println!("Failed to list variables: {err}");
+            return;
+        }
+    };
+
+    if let Some(names) = &test_suite.config.secrets {
+        declare_secret_names(names);
+    }
+
+    match resolve_variables(&test_suite, profile, cli_variables) {
+        Ok(variables) if variables.is_empty() => println!("No variables defined."),
+        Ok(variables) => {
+            println!("Resolved variables:");
+            for variable in variables {
+                let value = if is_secret_variable(&variable.name) {
+                    MASK.to_string()
+                } else {
+                    value_to_string(&variable.value)
+                };
+                println!("- {} = {} ({})", variable.name, value, variable.source);
+            }
+        }
+        Err(err) => println!("Failed to list variables: {err}"),
+    }
+}
@@ -0,0 +1,261 @@
+use crate::engine::variables::path_root_key;
+use crate::models::command::CommandStep;
+use crate::models::suite::TestSuite;
+use crate::models::test::{JsonAssertion, Test};
+use crate::utils::string::find_variable_references;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Variables the runner sets on its own after every test
+const BUILTIN_VARIABLES: &[&str] = &["response_time_ms"];
+const BUILTIN_PREFIXES: &[&str] = &["header_"];
+/// Variables bound for each run of a `for_each` test
+const FOR_EACH_VARIABLES: &[&str] = &["item", "index"];
+
+/// A `{{name}}` placeholder found in the suite, with a human readable location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariableReference {
+    pub name: String,
+    pub location: String,
+}
+
+/// Collect every `{{name}}` placeholder used by the suite
+#[must_use]
+pub fn collect_references(suite: &TestSuite) -> Vec<VariableReference> {
+    let mut refs = Vec::new();
+
+    if let Some(store) = suite.config.env.as_ref().and_then(|e| e.store.as_ref()) {
+        for (key, value) in store {
+            push_refs(&mut refs, value, format!("env.store `{key}`"));
+        }
+    }
+    if let Some(token) = &suite.config.auth_token {
+        push_refs(&mut refs, token, "config auth_token".to_string());
+    }
+    if let Some(steps) = &suite.setup {
+        collect_step_references(&mut refs, steps, "setup");
+    }
+    if let Some(steps) = &suite.teardown {
+        collect_step_references(&mut refs, steps, "teardown");
+    }
+
+    for test in suite.setup_tests.iter().flatten() {
+        collect_test_references(&mut refs, test, &format!("setup test `{}`", test.name));
+    }
+    for test in &suite.tests {
+        collect_test_references(&mut refs, test, &format!("test `{}`", test.name));
+    }
+    for test in suite.teardown_tests.iter().flatten() {
+        collect_test_references(&mut refs, test, &format!("teardown test `{}`", test.name));
+    }
+
+    refs
+}
+
+/// The placeholders of one test, and of the steps of a scenario; `ctx` names the test in locations
+fn collect_test_references(refs: &mut Vec<VariableReference>, test: &Test, ctx: &str) {
+    push_refs(refs, &test.endpoint, format!("{ctx} endpoint"));
+    if let Some(headers) = &test.headers {
+        for (name, value) in headers {
+            push_refs(refs, value, format!("{ctx} header '{name}'"));
+        }
+    }
+    if let Some(token) = &test.auth_token {
+        push_refs(refs, token, format!("{ctx} auth_token"));
+    }
+    if let Some(condition) = &test.skip_if {
+        push_refs(refs, condition, format!("{ctx} skip_if"));
+    }
+    if let Some(name) = test.for_each.as_deref().and_then(path_root_key) {
+        refs.push(VariableReference {
+            name: name.to_string(),
+            location: format!("{ctx} for_each"),
+        });
+    }
+    if let Some(params) = &test.query_params {
+        for (name, value) in params {
+            push_refs(refs, value, format!("{ctx} query param '{name}'"));
+        }
+    }
+    if let Some(body) = &test.body {
+        push_json_refs(refs, body, &format!("{ctx} body"));
+    }
+    if let Some(graphql) = &test.graphql {
+        if graphql.query_file().is_none() {
+            push_refs(refs, &graphql.query, format!("{ctx} graphql query"));
+        }
+        if let Some(variables) = &graphql.variables {
+            push_json_refs(refs, variables, &format!("{ctx} graphql variables"));
+        }
+    }
+    if let Some(expected) = &test.expected_body {
+        push_json_refs(refs, expected, &format!("{ctx} expected_body"));
+    }
+    if let Some(assertions) = &test.assertions {
+        for (i, assertion) in assertions.iter().enumerate() {
+            push_assertion_refs(refs, assertion, format!("{ctx} assertion {}", i + 1));
+        }
+    }
+    if let Some(stream) = &test.stream {
+        if let Some(until) = &stream.until {
+            push_refs(refs, until, format!("{ctx} stream until"));
+        }
+        for (i, event) in stream.events.iter().flatten().enumerate() {
+            push_json_refs(refs, event, &format!("{ctx} stream event {}", i + 1));
+        }
+    }
+    for (i, step) in test.script.iter().flatten().enumerate() {
+        let step_ctx = format!("{ctx} script step {}", i + 1);
+        if let Some(send) = &step.send {
+            push_json_refs(refs, send, &format!("{step_ctx} send"));
+        }
+        if let Some(expect) = &step.expect {
+            push_json_refs(refs, expect, &format!("{step_ctx} expect"));
+        }
+        for (j, assertion) in step.assertions.iter().flatten().enumerate() {
+            push_assertion_refs(refs, assertion, format!("{step_ctx} assertion {}", j + 1));
+        }
+    }
+    if let Some(steps) = &test.before {
+        collect_step_references(refs, steps, &format!("{ctx} before"));
+    }
+    if let Some(steps) = &test.after {
+        collect_step_references(refs, steps, &format!("{ctx} after"));
+    }
+    for step in test.steps.iter().flatten() {
+        collect_test_references(refs, step, &format!("{ctx} step `{}`", step.name));
+    }
+}
+
+/// Every one of `tests`, each followed by its steps when it is a scenario
+fn tests_and_steps<'a>(tests: impl IntoIterator<Item = &'a Test>) -> Vec<&'a Test> {
+    tests
+        .into_iter()
+        .flat_map(|test| std::iter::once(test).chain(tests_and_steps(test.steps.iter().flatten())))
+        .collect()
+}
+
+/// Collect every variable name the suite can define: `store` (also on `script`, scenario steps and
+/// setup or teardown tests), `get_cookie`,
+/// command `capture`/`export`, `env.store` and the `item`/`index` of `for_each`
+#[must_use]
+pub fn collect_definitions(suite: &TestSuite) -> HashSet<String> {
+    let mut defined = HashSet::new();
+
+    if let Some(store) = suite.config.env.as_ref().and_then(|e| e.store.as_ref()) {
+        defined.extend(store.keys().cloned());
+    }
+
+    let suite_steps = suite.setup.iter().chain(suite.teardown.iter()).flatten();
+    let tests = tests_and_steps(suite.all_tests());
+    let test_steps = tests
+        .iter()
+        .flat_map(|t| t.before.iter().chain(t.after.iter()).flatten());
+    for step in suite_steps.chain(test_steps) {
+        if let Some(capture) = &step.capture {
+            defined.insert(capture.var.clone());
+            defined.insert(format!("{}_stderr", capture.var));
+        }
+        if let Some(exports) = &step.export {
+            defined.extend(exports.keys().cloned());
+        }
+    }
+
+    for test in tests {
+        if let Some(store) = &test.store {
+            defined.extend(store.keys().cloned());
+        }
+        for step in test.script.iter().flatten() {
+            if let Some(store) = &step.store {
+                defined.extend(store.keys().cloned());
+            }
+        }
+        if let Some(cookies) = &test.get_cookie {
+            defined.extend(cookies.values().cloned());
+        }
+        defined.extend(test.parameters.iter().map(|(key, _)| key.clone()));
+        if test.for_each.is_some() {
+            defined.extend(FOR_EACH_VARIABLES.iter().map(|name| (*name).to_string()));
+        }
+    }
+
+    defined
+}
+
+/// Report the references that neither the suite nor `provided` (variables
+/// resolved from the command line, profile, dotenv or process env) defines
+#[must_use]
+pub fn find_undefined_variables(
+    suite: &TestSuite,
+    provided: &HashSet<String>,
+) -> Vec<VariableReference> {
+    let defined = collect_definitions(suite);
+    collect_references(suite)
+        .into_iter()
+        .filter(|r| {
+            !defined.contains(&r.name)
+                && !provided.contains(&r.name)
+                && !is_builtin_variable(&r.name)
+        })
+        .collect()
+}
+
+/// Whether `name` is produced at runtime without an explicit definition
+#[must_use]
+pub fn is_builtin_variable(name: &str) -> bool {
+    BUILTIN_VARIABLES.contains(&name) || BUILTIN_PREFIXES.iter().any(|p| name.starts_with(p))
+}
+
+fn collect_step_references(refs: &mut Vec<VariableReference>, steps: &[CommandStep], context: &str) {
+    for (i, step) in steps.iter().enumerate() {
+        let location = format!("{context} step {}", i + 1);
+        push_refs(refs, &step.run, location.clone());
+        for arg in step.args.iter().flatten() {
+            push_refs(refs, arg, location.clone());
+        }
+        if let Some(dir) = &step.dir {
+            push_refs(refs, dir, location.clone());
+        }
+        if let Some(env) = &step.env {
+            for value in env.values() {
+                push_refs(refs, value, location.clone());
+            }
+        }
+        if let Some(when) = &step.when {
+            push_refs(refs, when, location.clone());
+        }
+    }
+}
+
+fn push_assertion_refs(refs: &mut Vec<VariableReference>, assertion: &JsonAssertion, location: String) {
+    match assertion {
+        JsonAssertion::Exact(v) | JsonAssertion::Contains(v) => push_json_refs(refs, v, &location),
+        JsonAssertion::Regex(p) => push_refs(refs, p, location),
+        JsonAssertion::PathRegex(path, pattern) => {
+            push_refs(refs, path, location.clone());
+            push_refs(refs, pattern, location);
+        }
+        JsonAssertion::PathContains(path, v) => {
+            push_refs(refs, path, location.clone());
+            push_json_refs(refs, v, &location);
+        }
+    }
+}
+
+fn push_refs(refs: &mut Vec<VariableReference>, input: &str, location: String) {
+    for name in find_variable_references(input) {
+        refs.push(VariableReference {
+            name: name.to_string(),
+            location: location.clone(),
+        });
+    }
+}
+
+fn push_json_refs(refs: &mut Vec<VariableReference>, json: &Value, location: &str) {
+    match json {
+        Value::String(s) => push_refs(refs, s, location.to_string()),
+        Value::Object(map) => map.values().for_each(|v| push_json_refs(refs, v, location)),
+        Value::Array(arr) => arr.iter().for_each(|v| push_json_refs(refs, v, location)),
+        _ => {}
+    }
+}
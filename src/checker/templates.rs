@@ -0,0 +1,118 @@
+use std::collections::{HashMap, HashSet};
+use toml::{Table, Value};
+
+/// Map fields merged key by key, the test's entries winning
+const MERGED_TABLES: &[&str] = &["headers", "query_params", "store", "get_cookie", "expected_cookies"];
+/// List fields where the test's entries are appended to the template's
+const APPENDED_ARRAYS: &[&str] = &["tags", "assertions", "expected_headers", "before", "after"];
+
+/// Merge `[templates.<name>]` into every test that `extends` it, then drop the templates.
+///
+/// Templates may extend other templates. Other fields of the extending side replace
+/// the inherited ones.
+///
+/// # Errors
+/// Returns one message per unknown template or inheritance cycle
+pub fn apply_templates(document: &mut Table) -> Result<(), Vec<String>> {
+    let templates = match document.remove("templates") {
+        None => Table::new(),
+        Some(Value::Table(templates)) => templates,
+        Some(_) => return Err(vec!["`templates` must be a table of templates".to_string()]),
+    };
+
+    let mut errors = Vec::new();
+    let mut resolved: HashMap<String, Table> = HashMap::new();
+    for name in templates.keys() {
+        if let Err(e) = resolve(name, &templates, &mut resolved, &mut Vec::new()) {
+            errors.push(e);
+        }
+    }
+
+    for key in ["tests", "setup_tests", "teardown_tests"] {
+        if let Some(Value::Array(tests)) = document.get_mut(key) {
+            extend_tests(tests, &templates, &resolved, &mut errors);
+        }
+    }
+
+    // A cycle is found once from each of its members
+    let mut seen = HashSet::new();
+    errors.retain(|e| seen.insert(e.clone()));
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// Merge each test's template into it, then do the same for the `steps` of scenarios
+fn extend_tests(tests: &mut [Value], templates: &Table, resolved: &HashMap<String, Table>, errors: &mut Vec<String>) {
+    for test in tests.iter_mut() {
+        let Value::Table(table) = test else { continue };
+        if let Some(parent) = table.get("extends").and_then(Value::as_str) {
+            if let Some(base) = resolved.get(parent) {
+                *table = merge(base, table);
+            } else if !templates.contains_key(parent) {
+                let name = table.get("name").and_then(Value::as_str).unwrap_or("<unnamed>");
+                errors.push(format!("Test `{name}` extends unknown template `{parent}`"));
+            }
+        }
+        if let Some(Value::Array(steps)) = table.get_mut("steps") {
+            extend_tests(steps, templates, resolved, errors);
+        }
+    }
+}
+
+/// The fields of template `name` with its own parents merged in
+fn resolve(
+    name: &str,
+    templates: &Table,
+    resolved: &mut HashMap<String, Table>,
+    chain: &mut Vec<String>,
+) -> Result<Table, String> {
+    if let Some(table) = resolved.get(name) {
+        return Ok(table.clone());
+    }
+    if let Some(start) = chain.iter().position(|n| n == name) {
+        // Start from the smallest name so every member reports the same cycle
+        let mut cycle = chain[start..].to_vec();
+        let first = cycle.iter().enumerate().min_by_key(|(_, n)| *n).map_or(0, |(i, _)| i);
+        cycle.rotate_left(first);
+        cycle.push(cycle[0].clone());
+        return Err(format!("Template inheritance cycle: {}", cycle.join(" -> ")));
+    }
+    let Some(Value::Table(table)) = templates.get(name) else {
+        return Err(format!("Template `{name}` must be a table"));
+    };
+
+    let merged = match table.get("extends").and_then(Value::as_str) {
+        None => table.clone(),
+        Some(parent) if !templates.contains_key(parent) => {
+            return Err(format!("Template `{name}` extends unknown template `{parent}`"));
+        }
+        Some(parent) => {
+            chain.push(name.to_string());
+            let base = resolve(parent, templates, resolved, chain);
+            chain.pop();
+            merge(&base?, table)
+        }
+    };
+    resolved.insert(name.to_string(), merged.clone());
+    Ok(merged)
+}
+
+/// Field-by-field merge of `overlay` over `base`
+#[must_use]
+pub fn merge(base: &Table, overlay: &Table) -> Table {
+    let mut merged = base.clone();
+    for (key, value) in overlay {
+        let combined = match (merged.get(key), value) {
+            (Some(Value::Table(inherited)), Value::Table(own)) if MERGED_TABLES.contains(&key.as_str()) => {
+                let mut table = inherited.clone();
+                table.extend(own.clone());
+                Value::Table(table)
+            }
+            (Some(Value::Array(inherited)), Value::Array(own)) if APPENDED_ARRAYS.contains(&key.as_str()) => {
+                Value::Array(inherited.iter().chain(own).cloned().collect())
+            }
+            _ => value.clone(),
+        };
+        merged.insert(key.clone(), combined);
+    }
+    merged
+}
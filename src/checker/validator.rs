@@ -1,21 +1,53 @@
 use crate::checker::parse_tests;
+use crate::checker::references::find_undefined_variables;
+use crate::engine::dataset::load_rows;
+use crate::engine::expression::Condition;
+use crate::engine::stream::Format;
+use crate::engine::variables::resolve_variables;
+use crate::http::tls::{parse_tls_version, resolve_path};
+use crate::models::config::{HttpSettings, TlsSettings};
+use crate::models::test::{StreamExpectation, Test};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
+const HTTP_METHODS: &[&str] = &["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"];
+
 pub fn validate(file_path: Option<&str>) {
+    validate_with_variables(file_path, &HashMap::new(), None);
+}
+
+/// Validate a test file, treating `cli_variables` and the selected profile as already defined
+pub fn validate_with_variables(
+    file_path: Option<&str>,
+    cli_variables: &HashMap<String, String>,
+    profile: Option<&str>,
+) {
     let test_file_path = file_path.unwrap_or(".catalyst/tests.toml");
     let test_file_dir = Path::new(test_file_path).parent().unwrap_or(Path::new("."));
 
     match parse_tests(file_path) {
         Ok(test_suite) => {
+            let rows: Vec<_> = test_suite
+                .tests
+                .iter()
+                .map(|test| load_rows(test, test_file_dir))
+                .collect();
+            let instances: usize = rows
+                .iter()
+                .map(|r| match r {
+                    Ok(Some(rows)) => rows.len(),
+                    _ => 1,
+                })
+                .sum();
             if test_suite.tests.is_empty() {
                 println!("Validation failed: No tests found in `tests.toml`.");
             } else {
-                println!(
-                    "Validation successful: Found {} tests.",
-                    test_suite.tests.len()
-                );
+                println!("Validation successful: Found {instances} tests.");
             }
 
+            validate_http_settings(&test_suite.config.http, "config");
+            validate_tls_settings(&test_suite.config.tls, test_file_dir);
+
             // Validate suite-level command hooks
             if let Some(setup_steps) = &test_suite.setup {
                 validate_command_steps(setup_steps, "setup", test_suite.config.allowed_commands.as_deref());
@@ -24,76 +56,363 @@ pub fn validate(file_path: Option<&str>) {
                 validate_command_steps(teardown_steps, "teardown", test_suite.config.allowed_commands.as_deref());
             }
 
-            for test in &test_suite.tests {
-                if test.name.is_empty() {
-                    println!("Error: A test is missing a name.");
+            // Row values are defined for the instances of their test
+            let mut parameter_names = HashSet::new();
+            for (test, rows) in test_suite.tests.iter().zip(&rows) {
+                match rows {
+                    Err(e) => println!("Error: Test `{}` data: {e}", test.name),
+                    Ok(Some(rows)) if rows.is_empty() => {
+                        println!("Warning: Test `{}` expands to no instances.", test.name);
+                    }
+                    Ok(Some(rows)) => {
+                        parameter_names.extend(rows.iter().flatten().map(|(key, _)| key.clone()));
+                    }
+                    Ok(None) => {}
                 }
-                if test.method.is_empty() {
-                    println!("Error: Test `{}` is missing an HTTP method.", test.name);
+            }
+
+            for test in &test_suite.tests {
+                validate_test(test, test_file_dir, test_suite.config.allowed_commands.as_deref());
+            }
+            for (kind, tests) in [("setup", &test_suite.setup_tests), ("teardown", &test_suite.teardown_tests)] {
+                for test in tests.iter().flatten() {
+                    validate_suite_test(test, kind, test_file_dir, test_suite.config.allowed_commands.as_deref());
                 }
-                if test.endpoint.is_empty() {
-                    println!("Error: Test `{}` is missing an endpoint.", test.name);
+            }
+
+            for (index, mock) in test_suite.mocks.iter().flatten().enumerate() {
+                let name = mock.name.clone().unwrap_or_else(|| format!("#{}", index + 1));
+                if !mock.path.starts_with('/') {
+                    println!("Error: Mock `{name}` path `{}` must start with `/`.", mock.path);
                 }
-                if !["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"]
-                    .contains(&test.method.to_uppercase().as_str())
+                if let Some(method) = &mock.method
+                    && !HTTP_METHODS.contains(&method.to_uppercase().as_str())
                 {
-                    println!(
-                        "Error: Test `{}` has an invalid HTTP method `{}`.",
-                        test.name, test.method
-                    );
+                    println!("Error: Mock `{name}` has an invalid HTTP method `{method}`.");
                 }
-
-                if test.body.is_some() && test.body_file.is_some() {
-                    println!(
-                        "Error: Test `{}` cannot have both `body` and `body_file` specified.",
-                        test.name
-                    );
-                }
-
-                if let Some(body_file) = &test.body_file {
-                    validate_body_file(&test.name, body_file, test_file_dir);
+                if !(100..=999).contains(&mock.status) {
+                    println!("Error: Mock `{name}` has an invalid status {}.", mock.status);
                 }
+            }
 
-                // Validate test-level command hooks
-                if let Some(before_steps) = &test.before {
-                    validate_command_steps(before_steps, &format!("test '{}' before", test.name), test_suite.config.allowed_commands.as_deref());
-                }
-                if let Some(after_steps) = &test.after {
-                    validate_command_steps(after_steps, &format!("test '{}' after", test.name), test_suite.config.allowed_commands.as_deref());
-                    
-                    // Validate 'on' field for after steps
-                    for step in after_steps {
-                        if let Some(on_condition) = &step.on
-                            && !["success", "failure", "always"].contains(&on_condition.as_str()) {
-                            println!(
-                                "Error: Test `{}` after hook has invalid 'on' condition '{}'. Must be one of: success, failure, always",
-                                test.name, on_condition
-                            );
-                        }
+            let mut provided: HashSet<String> =
+                match resolve_variables(&test_suite, profile, cli_variables) {
+                    Ok(resolved) => resolved.into_iter().map(|v| v.name).collect(),
+                    Err(e) => {
+                        println!("Error: {e}");
+                        HashSet::new()
                     }
-                }
+                };
+            provided.extend(parameter_names);
+            for reference in find_undefined_variables(&test_suite, &provided) {
+                println!(
+                    "Error: Undefined variable `{{{{{}}}}}` in {}. No store, capture, export, env.store or --var defines it.",
+                    reference.name, reference.location
+                );
             }
         }
         Err(err) => println!("Validation failed: {err}"),
     }
 }
 
-fn validate_body_file(test_name: &str, body_file: &str, test_file_dir: &Path) {
+fn validate_test(test: &Test, test_file_dir: &Path, allowed_commands: Option<&[String]>) {
+    if test.name.is_empty() {
+        println!("Error: A test is missing a name.");
+    }
+    if let Some(steps) = &test.steps {
+        validate_scenario(test, steps, test_file_dir, allowed_commands);
+    } else if test.is_grpc() {
+        validate_grpc(test, test_file_dir);
+    } else {
+        if test.method.is_empty() {
+            println!("Error: Test `{}` is missing an HTTP method.", test.name);
+        }
+        if test.endpoint.is_empty() {
+            println!("Error: Test `{}` is missing an endpoint.", test.name);
+        }
+        if !HTTP_METHODS.contains(&test.method.to_uppercase().as_str())
+        {
+            println!(
+                "Error: Test `{}` has an invalid HTTP method `{}`.",
+                test.name, test.method
+            );
+        }
+    }
+
+    if test.only == Some(true) {
+        println!(
+            "Warning: Test `{}` has `only = true`; tests without it will not run.",
+            test.name
+        );
+    }
+    if test.skip.is_some() && test.skip_if.is_some() {
+        println!(
+            "Warning: Test `{}` has both `skip` and `skip_if`; `skip_if` is ignored.",
+            test.name
+        );
+    }
+    if let Some(condition) = &test.skip_if
+        && let Err(e) = Condition::parse(condition)
+    {
+        println!("Error: Test `{}` has an invalid `skip_if` condition '{condition}': {e}", test.name);
+    }
+
+    if test.body.is_some() && test.body_file.is_some() {
+        println!(
+            "Error: Test `{}` cannot have both `body` and `body_file` specified.",
+            test.name
+        );
+    }
+
+    if let Some(graphql) = &test.graphql {
+        if test.body.is_some() || test.body_file.is_some() {
+            println!(
+                "Error: Test `{}` cannot have both `graphql` and `body` or `body_file` specified.",
+                test.name
+            );
+        }
+        if let Some(query_file) = graphql.query_file() {
+            validate_body_file(&test.name, "graphql.query", query_file, test_file_dir);
+        }
+    }
+
+    if let Some(body_file) = &test.body_file {
+        validate_body_file(&test.name, "body_file", body_file, test_file_dir);
+    }
+
+    if let Some(data_file) = &test.data_file {
+        validate_body_file(&test.name, "data_file", data_file, test_file_dir);
+    }
+
+    if let Some(path) = &test.for_each
+        && !path.starts_with('$')
+    {
+        println!(
+            "Error: Test `{}` has an invalid `for_each` `{path}`; use a path over the variables such as `$.items[*]`.",
+            test.name
+        );
+    }
+
+    validate_protocol(test);
+    if let Some(stream) = &test.stream {
+        validate_stream(test, stream);
+    }
+
+    validate_http_settings(&test.http, &format!("test `{}`", test.name));
+
+    // Validate test-level command hooks
+    if let Some(before_steps) = &test.before {
+        validate_command_steps(before_steps, &format!("test '{}' before", test.name), allowed_commands);
+    }
+    if let Some(after_steps) = &test.after {
+        validate_command_steps(after_steps, &format!("test '{}' after", test.name), allowed_commands);
+
+        // Validate 'on' field for after steps
+        for step in after_steps {
+            if let Some(on_condition) = &step.on
+                && !["success", "failure", "always"].contains(&on_condition.as_str())
+                && let Err(e) = Condition::parse(on_condition) {
+                println!(
+                    "Error: Test `{}` after hook has invalid 'on' condition '{}'. Must be one of: success, failure, always, or a condition ({e})",
+                    test.name, on_condition
+                );
+            }
+        }
+    }
+}
+
+/// A setup or teardown test always runs; selecting it by tag or `only` has no effect
+fn validate_suite_test(test: &Test, kind: &str, test_file_dir: &Path, allowed_commands: Option<&[String]>) {
+    if test.tags.is_some() || test.only.is_some() {
+        println!(
+            "Warning: Test `{}` of `{kind}_tests` always runs; its `tags` and `only` are ignored.",
+            test.name
+        );
+    }
+    let mut test = test.clone();
+    test.only = None;
+    test.name = format!("{kind} / {}", test.name);
+    validate_test(&test, test_file_dir, allowed_commands);
+}
+
+fn validate_scenario(test: &Test, steps: &[Test], test_file_dir: &Path, allowed_commands: Option<&[String]>) {
+    if steps.is_empty() {
+        println!("Error: Scenario `{}` has no steps.", test.name);
+    }
+    let has_request = !test.method.is_empty()
+        || !test.endpoint.is_empty()
+        || test.body.is_some()
+        || test.body_file.is_some()
+        || test.graphql.is_some()
+        || test.protocol.is_some()
+        || test.expected_body.is_some()
+        || test.assertions.is_some()
+        || test.store.is_some();
+    if has_request {
+        println!(
+            "Warning: Scenario `{}` ignores its own request and checks; put them in its steps.",
+            test.name
+        );
+    }
+    for step in steps {
+        if step.matrix.is_some() || step.data_file.is_some() {
+            println!(
+                "Error: Step `{}` of scenario `{}` cannot use `matrix` or `data_file`; set them on the scenario.",
+                step.name, test.name
+            );
+        }
+        let mut step = step.clone();
+        step.name = format!("{} / {}", test.name, step.name);
+        validate_test(&step, test_file_dir, allowed_commands);
+    }
+}
+
+fn validate_protocol(test: &Test) {
+    if let Some(protocol) = &test.protocol
+        && !["http", "ws", "grpc"].contains(&protocol.to_lowercase().as_str())
+    {
+        println!("Error: Test `{}` has an unknown protocol `{protocol}`; use `http`, `ws` or `grpc`.", test.name);
+    }
+    if !test.is_websocket() {
+        if test.script.is_some() {
+            println!("Warning: Test `{}` has a `script` but is not a `ws` test; it is ignored.", test.name);
+        }
+        return;
+    }
+    if test.script.as_ref().is_none_or(Vec::is_empty) {
+        println!("Warning: WebSocket test `{}` has no `script`; it only checks the handshake.", test.name);
+    }
+    for (index, step) in test.script.iter().flatten().enumerate() {
+        if step.send.is_none() && !step.receives() {
+            println!(
+                "Error: Step {} of test `{}` neither sends nor expects a message.",
+                index + 1,
+                test.name
+            );
+        }
+    }
+    if test.body.is_some() || test.body_file.is_some() || test.graphql.is_some() {
+        println!("Warning: WebSocket test `{}` ignores `body`, `body_file` and `graphql`; use `send` steps.", test.name);
+    }
+    if test.expected_body.is_some() || test.assertions.is_some() || test.store.is_some() {
+        println!(
+            "Warning: WebSocket test `{}` ignores `expected_body`, `assertions` and `store`; set them on `script` steps.",
+            test.name
+        );
+    }
+}
+
+fn validate_grpc(test: &Test, test_file_dir: &Path) {
+    if test.method.is_empty() {
+        println!("Error: gRPC test `{}` is missing the RPC `method`.", test.name);
+    }
+    if test.service.as_ref().is_none_or(String::is_empty) {
+        println!("Error: gRPC test `{}` is missing a `service`.", test.name);
+    }
+    for file in test.proto.iter().flatten() {
+        validate_body_file(&test.name, "proto", file, test_file_dir);
+    }
+    for path in test.proto_path.iter().flatten() {
+        if path.contains("..") || Path::new(path).is_absolute() || !test_file_dir.join(path).is_dir() {
+            println!(
+                "Error: Test `{}` has an invalid `proto_path` `{path}`: it must be a relative directory without `..`.",
+                test.name
+            );
+        }
+    }
+    if test.graphql.is_some() || test.stream.is_some() {
+        println!(
+            "Warning: gRPC test `{}` ignores `graphql` and `stream`; the request message is `body` or `body_file`.",
+            test.name
+        );
+    }
+}
+
+fn validate_stream(test: &Test, stream: &StreamExpectation) {
+    if let Some(format) = &stream.format
+        && Format::parse(format).is_none()
+    {
+        println!(
+            "Error: Test `{}` has an unknown stream format `{format}`; use `sse`, `ndjson` or `chunks`.",
+            test.name
+        );
+    }
+    if let Some(until) = &stream.until
+        && let Err(e) = Condition::parse(until)
+    {
+        println!("Error: Test `{}` has an invalid stream `until` condition '{until}': {e}", test.name);
+    }
+    if let (Some(min), Some(max)) = (stream.min_events, stream.max_events)
+        && min > max
+    {
+        println!("Error: Test `{}` has stream `min_events` ({min}) above `max_events` ({max}).", test.name);
+    }
+    if stream.until.is_none() && stream.window_ms.is_none() && test.http.timeout_ms.is_none() {
+        println!(
+            "Warning: Test `{}` reads a stream with no `until`, `window_ms` or `timeout_ms`; it waits for the server to end it.",
+            test.name
+        );
+    }
+    if test.is_websocket() {
+        println!("Warning: WebSocket test `{}` ignores `stream`.", test.name);
+    }
+}
+
+fn validate_http_settings(settings: &HttpSettings, context: &str) {
+    if settings.timeout_ms == Some(0) {
+        println!("Error: {context} has `timeout_ms = 0`; omit it to disable the timeout.");
+    }
+    if settings.connect_timeout_ms == Some(0) {
+        println!("Error: {context} has `connect_timeout_ms = 0`; omit it to disable the timeout.");
+    }
+    if settings.max_redirects.is_some() && settings.follow_redirects == Some(false) {
+        println!("Warning: {context} sets `max_redirects` but `follow_redirects = false`.");
+    }
+    if let Some(proxy) = &settings.proxy
+        && let Err(e) = reqwest::Proxy::all(proxy) {
+        println!("Error: {context} has an invalid proxy `{proxy}`: {e}");
+    }
+}
+
+fn validate_tls_settings(settings: &TlsSettings, test_file_dir: &Path) {
+    let files = [
+        ("ca_cert", &settings.ca_cert),
+        ("client_cert", &settings.client_cert),
+        ("client_key", &settings.client_key),
+    ];
+    for (key, path) in files {
+        if let Some(path) = path
+            && !resolve_path(path, test_file_dir).is_file()
+        {
+            println!("Error: config `{key}` references a non-existent file: `{path}`");
+        }
+    }
+    if settings.client_key.is_some() && settings.client_cert.is_none() {
+        println!("Error: config sets `client_key` without `client_cert`.");
+    }
+    if let Some(version) = &settings.min_tls_version
+        && let Err(e) = parse_tls_version(version)
+    {
+        println!("Error: {e}");
+    }
+}
+
+fn validate_body_file(test_name: &str, key: &str, body_file: &str, test_file_dir: &Path) {
     if body_file.is_empty() {
-        println!("Error: Test `{test_name}` has an empty `body_file` path.");
+        println!("Error: Test `{test_name}` has an empty `{key}` path.");
         return;
     }
 
     if body_file.contains("..") {
         println!(
-            "Error: Test `{test_name}` has an invalid `body_file` path `{body_file}` - path traversal is not allowed."
+            "Error: Test `{test_name}` has an invalid `{key}` path `{body_file}` - path traversal is not allowed."
         );
         return;
     }
 
     if Path::new(body_file).is_absolute() {
         println!(
-            "Error: Test `{test_name}` has an invalid `body_file` path `{body_file}` - absolute paths are not allowed."
+            "Error: Test `{test_name}` has an invalid `{key}` path `{body_file}` - absolute paths are not allowed."
         );
         return;
     }
@@ -102,14 +421,14 @@ fn validate_body_file(test_name: &str, body_file: &str, test_file_dir: &Path) {
 
     if !full_path.exists() {
         println!(
-            "Error: Test `{test_name}` references a non-existent `body_file`: `{body_file}`"
+            "Error: Test `{test_name}` references a non-existent `{key}`: `{body_file}`"
         );
         return;
     }
 
     if !full_path.is_file() {
         println!(
-            "Error: Test `{test_name}` references a `body_file` that is not a file: `{body_file}`"
+            "Error: Test `{test_name}` references a `{key}` that is not a file: `{body_file}`"
         );
         return;
     }
@@ -202,6 +521,12 @@ fn validate_command_steps(
             );
         }
 
+        if let Some(when) = &step.when
+            && let Err(e) = Condition::parse(when)
+        {
+            println!("Error: {step_context} has an invalid 'when' condition '{when}': {e}");
+        }
+
         // Validate 'on' field only valid for after hooks
         if step.on.is_some() && !context.contains("after") {
             println!(
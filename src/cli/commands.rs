@@ -13,9 +13,25 @@ pub struct Opts {
 #[derive(Subcommand)]
 pub enum Commands {
     Run {
-        #[arg(short = 'f', long, help = "Filter by test name")]
+        #[arg(short = 'f', long, help = "Filter by test name (substring or regex)")]
         filter: Option<String>,
 
+        #[arg(long = "tag", help = "Run only tests with this tag; repeatable")]
+        tags: Vec<String>,
+
+        #[arg(long = "exclude-tag", help = "Skip tests with this tag; repeatable")]
+        exclude_tags: Vec<String>,
+
+        #[arg(long = "test", help = "Run only the test with exactly this name; repeatable")]
+        names: Vec<String>,
+
+        #[arg(
+            long,
+            default_value = "false",
+            help = "Run only the tests that failed in the previous run"
+        )]
+        only_failed: bool,
+
         #[arg(long, default_value = "false", help = "Disable colored output")]
         disable_color: bool,
 
@@ -36,6 +52,31 @@ pub enum Commands {
 
         #[arg(long, default_value = "false", help = "Disable failure summary display")]
         no_fail_summary: bool,
+
+        #[arg(
+            long,
+            default_value = "false",
+            help = "Send requests even when {{variables}} cannot be resolved"
+        )]
+        allow_undefined: bool,
+
+        #[arg(long, help = "Use the variables of a [config.profiles.<name>] profile")]
+        profile: Option<String>,
+
+        #[arg(
+            long,
+            default_value = "false",
+            help = "Rerun the affected tests whenever the tests file or a file it reads changes"
+        )]
+        watch: bool,
+
+        #[arg(
+            long,
+            default_value = "false",
+            requires = "watch",
+            help = "With --watch, run setup once and keep variables between reruns"
+        )]
+        keep_variables: bool,
     },
 
     Validate {
@@ -47,6 +88,112 @@ pub enum Commands {
             help = "Set variables in key=value format (comma-separated: key1=val1,key2=val2)"
         )]
         var: Option<String>,
+
+        #[arg(long, help = "Use the variables of a [config.profiles.<name>] profile")]
+        profile: Option<String>,
+    },
+
+    Vars {
+        #[arg(long, help = "Specify a custom test file path")]
+        file: Option<String>,
+
+        #[arg(
+            long,
+            help = "Set variables in key=value format (comma-separated: key1=val1,key2=val2)"
+        )]
+        var: Option<String>,
+
+        #[arg(long, help = "Use the variables of a [config.profiles.<name>] profile")]
+        profile: Option<String>,
+    },
+
+    Bench {
+        #[arg(short = 'f', long, help = "Filter by test name (substring or regex)")]
+        filter: Option<String>,
+
+        #[arg(long, help = "Specify a custom test file path")]
+        file: Option<String>,
+
+        #[arg(
+            short = 'c',
+            long,
+            default_value = "10",
+            help = "Number of concurrent workers"
+        )]
+        concurrency: usize,
+
+        #[arg(long, default_value = "10s", help = "How long to run (e.g. 30s, 2m)")]
+        duration: String,
+
+        #[arg(
+            short = 't',
+            long = "threshold",
+            help = "Fail unless the condition holds (e.g. \"p95 < 300ms\", \"error_rate < 1%\"); repeatable"
+        )]
+        thresholds: Vec<String>,
+
+        #[arg(
+            long,
+            help = "Set variables in key=value format (comma-separated: key1=val1,key2=val2)"
+        )]
+        var: Option<String>,
+
+        #[arg(long, help = "Use the variables of a [config.profiles.<name>] profile")]
+        profile: Option<String>,
+
+        #[arg(long, default_value = "false", help = "Disable colored output")]
+        disable_color: bool,
+    },
+
+    Tui {
+        #[arg(long, help = "Specify a custom test file path")]
+        file: Option<String>,
+
+        #[arg(
+            long,
+            help = "Set variables in key=value format (comma-separated: key1=val1,key2=val2)"
+        )]
+        var: Option<String>,
+
+        #[arg(long, help = "Use the variables of a [config.profiles.<name>] profile")]
+        profile: Option<String>,
+
+        #[arg(
+            long,
+            default_value = "false",
+            help = "Send requests even when {{variables}} cannot be resolved"
+        )]
+        allow_undefined: bool,
+    },
+
+    Mock {
+        #[arg(long, help = "Specify a custom test file path")]
+        file: Option<String>,
+
+        #[arg(short = 'p', long, default_value = "8080", help = "Port to listen on")]
+        port: u16,
+
+        #[arg(long, default_value = "127.0.0.1", help = "Address to listen on")]
+        host: String,
+    },
+
+    Record {
+        #[arg(long, help = "URL of the server to forward requests to")]
+        target: String,
+
+        #[arg(long, default_value = "8081", help = "Port to listen on")]
+        listen: u16,
+
+        #[arg(long, default_value = "127.0.0.1", help = "Address to listen on")]
+        host: String,
+
+        #[arg(
+            short = 'o',
+            long,
+            default_value = ".catalyst/recorded.toml",
+            help = "Tests file to write; must not exist yet"
+        )]
+        output: String,
     },
 
     List {
@@ -55,6 +202,9 @@ pub enum Commands {
 
         #[arg(long, help = "Specify a custom test file path")]
         file: Option<String>,
+
+        #[arg(long, default_value = "false", help = "Group tests by tag")]
+        by_tag: bool,
     },
 }
 
@@ -1,6 +1,14 @@
 use super::commands::{Commands, Opts};
-use crate::checker::{list_tests, validate};
+use crate::checker::{list_tests, list_variables, validate_with_variables};
+use crate::core::bench::{BenchOptions, Threshold, parse_bench_duration, run_bench};
+use crate::core::mock::run_mock;
+use crate::core::record::run_record;
 use crate::core::runner::TestRunner;
+use crate::core::selection::TestSelection;
+use crate::core::watch::{WatchOptions, watch as watch_tests};
+use crate::engine::variables::load_env_files;
+use crate::output::BenchReportFormatter;
+use crate::tui::run_tui;
 use crate::utils::debug;
 
 /// Run the CLI application
@@ -11,53 +19,179 @@ pub fn run(opts: Opts) {
     match opts.command {
         Commands::Run {
             filter,
+            tags,
+            exclude_tags,
+            names,
+            only_failed,
             disable_color,
             verbose,
             file,
             debug: debug_enabled,
             var,
             no_fail_summary,
+            allow_undefined,
+            profile,
+            watch,
+            keep_variables,
         } => {
             if debug_enabled {
                 debug::enable_debug();
             }
             println!("Running API tests...");
+            let mut runner = TestRunner::new(disable_color);
+            runner.allow_undefined = allow_undefined;
+            runner.profile = profile;
+            runner.selection = TestSelection {
+                filter: None,
+                tags,
+                exclude_tags,
+                names,
+                only_failed,
+            };
+            if watch {
+                let options = WatchOptions {
+                    file: file.unwrap_or_else(|| ".catalyst/tests.toml".to_string()),
+                    filter,
+                    verbose,
+                    var,
+                    no_fail_summary,
+                    keep_variables,
+                };
+                tokio::runtime::Runtime::new().unwrap().block_on(watch_tests(runner, options));
+                return;
+            }
             tokio::runtime::Runtime::new().unwrap().block_on(run_tests(
+                runner,
                 filter,
                 verbose,
-                disable_color,
                 file,
                 var,
                 no_fail_summary,
             ));
         }
-        Commands::Validate { file, var } => {
+        Commands::Validate { file, var, profile } => {
             println!("Validating tests configuration...");
-            run_validate(file, var);
+            run_validate(file, var, profile);
+        }
+        Commands::Bench {
+            filter,
+            file,
+            concurrency,
+            duration,
+            thresholds,
+            var,
+            profile,
+            disable_color,
+        } => {
+            let passed = run_bench_command(
+                BenchOptions {
+                    file,
+                    filter,
+                    concurrency,
+                    duration: parse_bench_duration(&duration).unwrap_or_else(|e| exit_with(&e)),
+                    var,
+                    profile,
+                },
+                &thresholds,
+                disable_color,
+            );
+            if !passed {
+                std::process::exit(1);
+            }
+        }
+        Commands::Tui {
+            file,
+            var,
+            profile,
+            allow_undefined,
+        } => {
+            let mut runner = TestRunner::new(false);
+            runner.allow_undefined = allow_undefined;
+            runner.profile = profile;
+            if let Err(e) = tokio::runtime::Runtime::new().unwrap().block_on(run_tui(runner, file, var)) {
+                exit_with(&e);
+            }
         }
-        Commands::List { verbose, file } => {
+        Commands::Mock { file, port, host } => {
+            if let Err(e) = tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(run_mock(file.as_deref(), &host, port))
+            {
+                exit_with(&e);
+            }
+        }
+        Commands::Record {
+            target,
+            listen,
+            host,
+            output,
+        } => {
+            if let Err(e) = tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(run_record(&target, &host, listen, &output))
+            {
+                exit_with(&e);
+            }
+        }
+        Commands::List { verbose, file, by_tag } => {
             println!("Listing available tests...");
-            list_tests(verbose, file.as_deref());
+            list_tests(verbose, file.as_deref(), by_tag);
+        }
+        Commands::Vars { file, var, profile } => {
+            load_env_files();
+            let cli_variables = Commands::parse_variables(var);
+            list_variables(file.as_deref(), &cli_variables, profile.as_deref());
         }
     }
 }
 
-pub fn run_validate(file: Option<String>, _var: Option<String>) {
-    // Note: Variables are not used in validation, only for actual test execution
-    match file {
-        Some(f) => validate(Some(&f)),
-        None => validate(None),
-    }
+pub fn run_validate(file: Option<String>, var: Option<String>, profile: Option<String>) {
+    // CLI and profile variables only count as definitions for the undefined-variable check
+    let cli_variables = Commands::parse_variables(var);
+    validate_with_variables(file.as_deref(), &cli_variables, profile.as_deref());
+}
+
+/// Run `catalyst bench`, print its report and tell whether every threshold held
+pub fn run_bench_command(
+    options: BenchOptions,
+    thresholds: &[String],
+    disable_color: bool,
+) -> bool {
+    let thresholds: Vec<Threshold> = thresholds
+        .iter()
+        .map(|t| Threshold::parse(t).unwrap_or_else(|e| exit_with(&e)))
+        .collect();
+
+    println!(
+        "Benchmarking with {} workers for {:.0}s...",
+        options.concurrency,
+        options.duration.as_secs_f64()
+    );
+    let stats = match tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(run_bench(&options))
+    {
+        Ok(stats) => stats,
+        Err(e) => exit_with(&e),
+    };
+
+    let formatter = BenchReportFormatter::new(disable_color);
+    print!("{}", formatter.format_report(&stats, &thresholds));
+    thresholds.iter().all(|t| t.passes(&stats))
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{message}");
+    std::process::exit(1);
 }
 
 pub async fn run_tests(
+    mut runner: TestRunner,
     filter: Option<String>,
     verbose: bool,
-    disable_color: bool,
     file: Option<String>,
     var: Option<String>,
     no_fail_summary: bool,
 ) {
-    let mut runner = TestRunner::new(disable_color);
     runner.execute_tests(filter, verbose, file, var, no_fail_summary).await;
 }
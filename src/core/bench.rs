@@ -0,0 +1,463 @@
+//! Load mode: drive existing tests repeatedly with concurrent workers and
+//! summarize latency, throughput and errors.
+
+use crate::checker::parse_tests;
+use crate::core::runner::TestRunner;
+use crate::core::selection::TestSelection;
+use crate::engine::dataset::{bind_parameters, expand_tests, restore_variables};
+use crate::engine::execution;
+use crate::engine::variables::{load_env_files, resolve_variables};
+use crate::http::client::HttpClient;
+use crate::models::suite::TestSuite;
+use crate::utils::secrets;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+/// Upper bounds of the latency histogram buckets, in ms; the last bucket is open-ended
+pub const HISTOGRAM_BOUNDS_MS: &[u64] = &[1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000];
+
+/// Options of `catalyst bench`
+pub struct BenchOptions {
+    pub file: Option<String>,
+    pub filter: Option<String>,
+    pub concurrency: usize,
+    pub duration: Duration,
+    pub var: Option<String>,
+    pub profile: Option<String>,
+}
+
+/// One request made during the bench
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub test: String,
+    pub latency: Duration,
+    pub expected_status: u16,
+    /// Response status, 0 when no response was received
+    pub status: u16,
+    pub success: bool,
+    pub timed_out: bool,
+}
+
+/// Why a sample counts as an error
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ErrorKind {
+    /// The response had an unexpected status
+    Status(u16),
+    /// The status matched but an assertion failed
+    Assertion,
+    Timeout,
+    /// Connection refused, reset, TLS failure...
+    Transport,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Status(status) => write!(f, "status {status}"),
+            ErrorKind::Assertion => f.write_str("assertion failed"),
+            ErrorKind::Timeout => f.write_str("timeout"),
+            ErrorKind::Transport => f.write_str("transport error"),
+        }
+    }
+}
+
+/// Aggregated results of a bench run
+#[derive(Debug, Clone, Default)]
+pub struct BenchStats {
+    pub requests: usize,
+    pub errors: usize,
+    pub elapsed: Duration,
+    /// All latencies, sorted ascending
+    pub latencies: Vec<Duration>,
+    pub errors_by_kind: BTreeMap<ErrorKind, usize>,
+    pub status_counts: BTreeMap<u16, usize>,
+    /// Requests and errors per test name
+    pub per_test: BTreeMap<String, (usize, usize)>,
+}
+
+impl BenchStats {
+    #[must_use]
+    pub fn from_samples(samples: &[Sample], elapsed: Duration) -> Self {
+        let mut stats = BenchStats {
+            requests: samples.len(),
+            elapsed,
+            ..Default::default()
+        };
+
+        for sample in samples {
+            stats.latencies.push(sample.latency);
+            *stats.status_counts.entry(sample.status).or_default() += 1;
+            let per_test = stats.per_test.entry(sample.test.clone()).or_default();
+            per_test.0 += 1;
+
+            if let Some(kind) = classify(sample) {
+                stats.errors += 1;
+                per_test.1 += 1;
+                *stats.errors_by_kind.entry(kind).or_default() += 1;
+            }
+        }
+        stats.latencies.sort();
+        stats
+    }
+
+    /// Latency at `percentile` (0-100), nearest-rank
+    #[must_use]
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = ((percentile / 100.0) * self.latencies.len() as f64).ceil() as usize;
+        self.latencies[rank.clamp(1, self.latencies.len()) - 1]
+    }
+
+    #[must_use]
+    pub fn mean(&self) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        self.latencies.iter().sum::<Duration>() / self.latencies.len() as u32
+    }
+
+    #[must_use]
+    pub fn min(&self) -> Duration {
+        self.latencies.first().copied().unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn max(&self) -> Duration {
+        self.latencies.last().copied().unwrap_or_default()
+    }
+
+    /// Requests per second
+    #[must_use]
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.requests as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Share of failed requests, in percent
+    #[must_use]
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.errors as f64 * 100.0 / self.requests as f64
+        }
+    }
+
+    /// Request counts per histogram bucket, paired with the bucket's upper bound (None = open-ended)
+    #[must_use]
+    pub fn histogram(&self) -> Vec<(Option<u64>, usize)> {
+        let mut buckets: Vec<(Option<u64>, usize)> = HISTOGRAM_BOUNDS_MS
+            .iter()
+            .map(|b| (Some(*b), 0))
+            .chain(std::iter::once((None, 0)))
+            .collect();
+        for latency in &self.latencies {
+            let ms = latency.as_secs_f64() * 1000.0;
+            let index = HISTOGRAM_BOUNDS_MS
+                .iter()
+                .position(|bound| ms <= *bound as f64)
+                .unwrap_or(HISTOGRAM_BOUNDS_MS.len());
+            buckets[index].1 += 1;
+        }
+        buckets
+    }
+}
+
+fn classify(sample: &Sample) -> Option<ErrorKind> {
+    if sample.success {
+        None
+    } else if sample.timed_out {
+        Some(ErrorKind::Timeout)
+    } else if sample.status == 0 {
+        Some(ErrorKind::Transport)
+    } else if sample.status != sample.expected_status {
+        Some(ErrorKind::Status(sample.status))
+    } else {
+        Some(ErrorKind::Assertion)
+    }
+}
+
+/// A metric a threshold applies to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    Percentile(f64),
+    Mean,
+    Min,
+    Max,
+    Throughput,
+    ErrorRate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+/// A pass/fail condition such as `p95 < 300ms`, `error_rate < 1%` or `rps > 100`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Threshold {
+    pub source: String,
+    pub metric: Metric,
+    pub comparison: Comparison,
+    /// Milliseconds for latency metrics, requests per second or percent otherwise
+    pub value: f64,
+}
+
+impl Threshold {
+    /// Parse `<metric> <op> <value>`.
+    ///
+    /// Metrics: `pNN` (e.g. `p95`, `p99.9`), `mean`/`avg`, `min`, `max`, `rps`/`throughput`,
+    /// `error_rate`/`errors`. Latency values take `ms`, `s` or `m` (bare numbers are ms),
+    /// error rates an optional `%`.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let operators = [
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ];
+        let (metric, comparison, value) = operators
+            .iter()
+            .find_map(|(op, cmp)| {
+                input
+                    .split_once(op)
+                    .map(|(m, v)| (m.trim(), *cmp, v.trim()))
+            })
+            .ok_or_else(|| {
+                format!(
+                    "Invalid threshold '{input}': expected <metric> <op> <value>, e.g. p95 < 300ms"
+                )
+            })?;
+
+        let metric = match metric.to_lowercase().as_str() {
+            "mean" | "avg" => Metric::Mean,
+            "min" => Metric::Min,
+            "max" => Metric::Max,
+            "rps" | "throughput" => Metric::Throughput,
+            "error_rate" | "errors" => Metric::ErrorRate,
+            m => match m.strip_prefix('p').and_then(|p| p.parse::<f64>().ok()) {
+                Some(p) if (0.0..=100.0).contains(&p) => Metric::Percentile(p),
+                _ => {
+                    return Err(format!(
+                        "Invalid threshold '{input}': unknown metric '{metric}'"
+                    ));
+                }
+            },
+        };
+
+        let value = match metric {
+            Metric::Throughput => value.trim_end_matches("/s").trim().parse::<f64>().ok(),
+            Metric::ErrorRate => value.trim_end_matches('%').trim().parse::<f64>().ok(),
+            _ => parse_duration(value).ok().map(|d| d.as_secs_f64() * 1000.0),
+        }
+        .ok_or_else(|| format!("Invalid threshold '{input}': cannot parse value '{value}'"))?;
+
+        Ok(Threshold {
+            source: input.trim().to_string(),
+            metric,
+            comparison,
+            value,
+        })
+    }
+
+    /// The measured value of this threshold's metric
+    #[must_use]
+    pub fn actual(&self, stats: &BenchStats) -> f64 {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        match self.metric {
+            Metric::Percentile(p) => ms(stats.percentile(p)),
+            Metric::Mean => ms(stats.mean()),
+            Metric::Min => ms(stats.min()),
+            Metric::Max => ms(stats.max()),
+            Metric::Throughput => stats.throughput(),
+            Metric::ErrorRate => stats.error_rate(),
+        }
+    }
+
+    #[must_use]
+    pub fn passes(&self, stats: &BenchStats) -> bool {
+        let actual = self.actual(stats);
+        match self.comparison {
+            Comparison::Less => actual < self.value,
+            Comparison::LessOrEqual => actual <= self.value,
+            Comparison::Greater => actual > self.value,
+            Comparison::GreaterOrEqual => actual >= self.value,
+        }
+    }
+}
+
+/// Parse a duration such as `300ms`, `1.5s`, `2m` or `1h`; a bare number is milliseconds
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid duration '{input}'"))?;
+    let seconds = match unit.trim() {
+        "ms" | "" => number / 1000.0,
+        "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        other => {
+            return Err(format!(
+                "Invalid duration '{input}': unknown unit '{other}'"
+            ));
+        }
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("Invalid duration '{input}': out of range"))
+}
+
+/// Parse `--duration`, where a bare number means seconds
+pub fn parse_bench_duration(input: &str) -> Result<Duration, String> {
+    if input.trim().parse::<f64>().is_ok() {
+        parse_duration(&format!("{}s", input.trim()))
+    } else {
+        parse_duration(input)
+    }
+}
+
+/// Run the bench: resolve variables and run the suite setup once, then let `concurrency`
+/// workers loop over the selected tests until `duration` elapses. The suite teardown
+/// runs at the end, or as soon as setup fails.
+///
+/// Each worker keeps its own copy of the variables so `store` chains work per
+/// worker; `before`/`after` command hooks are not run, and scenarios and `for_each`
+/// tests are left out.
+pub async fn run_bench(options: &BenchOptions) -> Result<BenchStats, String> {
+    load_env_files();
+    let file_path = options.file.as_deref().unwrap_or(".catalyst/tests.toml");
+    let test_file_dir = Path::new(file_path)
+        .parent()
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+
+    let mut suite =
+        parse_tests(options.file.as_deref()).map_err(|e| format!("Failed to parse tests: {e}"))?;
+    suite.tests = expand_tests(&suite.tests, &test_file_dir)
+        .map_err(|e| format!("Failed to load test data: {e}"))?;
+    for tests in suite.setup_tests.iter_mut().chain(suite.teardown_tests.iter_mut()) {
+        *tests = expand_tests(tests, &test_file_dir)
+            .map_err(|e| format!("Failed to load test data: {e}"))?;
+    }
+    let mut variables: HashMap<String, Value> = HashMap::new();
+    let cli_variables = crate::cli::Commands::parse_variables(options.var.clone());
+    for variable in resolve_variables(&suite, options.profile.as_deref(), &cli_variables)? {
+        variables.insert(variable.name, variable.value);
+    }
+    if let Some(names) = &suite.config.secrets {
+        secrets::declare_secret_names(names);
+    }
+
+    let selection = TestSelection {
+        filter: options.filter.clone(),
+        ..Default::default()
+    };
+    let selector = selection.selector(None);
+    let selected: Vec<usize> = suite
+        .tests
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.skip.is_none() && !t.is_scenario() && t.for_each.is_none() && selector.matches(t))
+        .map(|(i, _)| i)
+        .collect();
+    if selected.is_empty() {
+        return Err("No tests match the filter".to_string());
+    }
+
+    let mut runner = TestRunner::new(true);
+    runner.variables = variables;
+    // The benched requests share the cookie jars that setup filled, a login included
+    let client = Arc::new(HttpClient::new(&suite.config).with_base_dir(&test_file_dir));
+    if let Err(e) = runner.execute_setup(&suite, &client, &test_file_dir).await {
+        runner.execute_teardown(&suite, &client, &test_file_dir).await;
+        return Err(e);
+    }
+    secrets::register_variables(&runner.variables);
+
+    let suite = Arc::new(suite);
+    let stats = drive(
+        Arc::clone(&client),
+        Arc::clone(&suite),
+        Arc::new(selected),
+        runner.variables.clone(),
+        &test_file_dir,
+        options,
+    )
+    .await;
+
+    runner.execute_teardown(&suite, &client, &test_file_dir).await;
+    Ok(stats)
+}
+
+async fn drive(
+    client: Arc<HttpClient>,
+    suite: Arc<TestSuite>,
+    selected: Arc<Vec<usize>>,
+    variables: HashMap<String, Value>,
+    test_file_dir: &Path,
+    options: &BenchOptions,
+) -> BenchStats {
+    let start = Instant::now();
+    let deadline = start + options.duration;
+
+    let mut workers = JoinSet::new();
+    for _ in 0..options.concurrency.max(1) {
+        let client = Arc::clone(&client);
+        let suite = Arc::clone(&suite);
+        let selected = Arc::clone(&selected);
+        let test_file_dir = test_file_dir.to_path_buf();
+        let mut variables = variables.clone();
+        workers.spawn(async move {
+            let mut samples = Vec::new();
+            'run: loop {
+                for &index in selected.iter() {
+                    if Instant::now() >= deadline {
+                        break 'run;
+                    }
+                    let test = &suite.tests[index];
+                    let sent = Instant::now();
+                    let shadowed = bind_parameters(&test.parameters, &mut variables);
+                    let request = execution::run(&client, test, &test_file_dir, &mut variables, false);
+                    // A request still pending at the deadline is dropped, not counted
+                    let Ok(result) = tokio::time::timeout_at(deadline.into(), request).await else {
+                        break 'run;
+                    };
+                    restore_variables(shadowed, &mut variables);
+                    samples.push(Sample {
+                        test: test.name.clone(),
+                        // Without a response, count the time until the failure
+                        latency: result.timings.map_or_else(|| sent.elapsed(), |t| t.total),
+                        expected_status: result.status.0,
+                        status: result.status.1,
+                        success: result.success,
+                        timed_out: result.timed_out,
+                    });
+                }
+            }
+            samples
+        });
+    }
+
+    let mut samples = Vec::new();
+    while let Some(worker) = workers.join_next().await {
+        samples.extend(worker.unwrap_or_default());
+    }
+    BenchStats::from_samples(&samples, start.elapsed())
+}
@@ -0,0 +1,327 @@
+//! `catalyst mock`: serve canned responses described by a tests file, from its
+//! `[[mocks]]` and from the tests' expectations.
+
+use crate::checker::parse_tests;
+use crate::engine::assertions::body_matches;
+use crate::models::mock::Mock;
+use crate::models::suite::TestSuite;
+use crate::models::test::{JsonAssertion, Test};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response};
+use hyper_util::rt::TokioIo;
+use regex::Regex;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{\{[^}]*\}\}").unwrap());
+
+/// A request as the routes see it
+#[derive(Debug, Default)]
+pub struct IncomingRequest {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    /// Names lowercased
+    pub headers: HashMap<String, String>,
+    /// `None` when the body is empty or not JSON
+    pub body: Option<Value>,
+}
+
+/// A request matcher and the response it serves
+#[derive(Debug)]
+pub struct Route {
+    pub name: String,
+    pub method: Option<String>,
+    /// The path as written, for display
+    pub pattern: String,
+    path: Regex,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    body: Option<Value>,
+    pub status: u16,
+    response_headers: Vec<(String, String)>,
+    response_body: Option<Value>,
+    delay: Option<Duration>,
+}
+
+impl Route {
+    /// # Errors
+    /// Returns an error if the status is not a valid HTTP status
+    pub fn from_mock(mock: &Mock, index: usize) -> Result<Self, String> {
+        let name = mock.name.clone().unwrap_or_else(|| format!("mock #{}", index + 1));
+        check_status(&name, mock.status)?;
+        let pairs = |map: &Option<HashMap<String, String>>, lowercase: bool| {
+            let mut pairs: Vec<(String, String)> = map
+                .iter()
+                .flatten()
+                .map(|(k, v)| (if lowercase { k.to_lowercase() } else { k.clone() }, v.clone()))
+                .collect();
+            pairs.sort();
+            pairs
+        };
+        Ok(Route {
+            method: mock.method.as_ref().map(|m| m.to_uppercase()),
+            pattern: mock.path.clone(),
+            path: path_regex(&mock.path),
+            query: pairs(&mock.query, false),
+            headers: pairs(&mock.headers, true),
+            body: mock.body.clone(),
+            status: mock.status,
+            response_headers: pairs(&mock.response_headers, false),
+            response_body: mock.response_body.clone(),
+            delay: mock.delay_ms.map(Duration::from_millis),
+            name,
+        })
+    }
+
+    /// A route answering the test's request the way the test expects
+    ///
+    /// # Errors
+    /// Returns an error if the expected status is not a valid HTTP status
+    pub fn from_test(test: &Test) -> Result<Self, String> {
+        check_status(&test.name, test.expected_status)?;
+        let path = test.endpoint.split('?').next().unwrap_or_default();
+        Ok(Route {
+            name: test.name.clone(),
+            method: Some(test.method.to_uppercase()),
+            pattern: path.to_string(),
+            path: path_regex(path),
+            query: Vec::new(),
+            headers: Vec::new(),
+            // GraphQL tests share an endpoint; tell their operations apart
+            body: test
+                .graphql
+                .as_ref()
+                .and_then(|g| g.operation_name.as_ref())
+                .map(|name| json!({ "operationName": name })),
+            status: test.expected_status,
+            response_headers: test.expected_headers.clone().unwrap_or_default(),
+            response_body: expected_response_body(test),
+            delay: None,
+        })
+    }
+
+    #[must_use]
+    pub fn matches(&self, request: &IncomingRequest) -> bool {
+        let accepts = |expected: &str, actual: Option<&String>| {
+            actual.is_some_and(|actual| is_placeholder(expected) || expected == actual)
+        };
+        self.method.as_ref().is_none_or(|m| *m == request.method)
+            && self.path.is_match(&request.path)
+            && self.query.iter().all(|(k, v)| accepts(v, request.query.get(k)))
+            && self.headers.iter().all(|(k, v)| accepts(v, request.headers.get(k)))
+            && self
+                .body
+                .as_ref()
+                .is_none_or(|expected| request.body.as_ref().is_some_and(|actual| body_matches(expected, actual)))
+    }
+
+    fn response(&self) -> Response<Full<Bytes>> {
+        let mut builder = Response::builder().status(self.status);
+        for (name, value) in &self.response_headers {
+            builder = builder.header(name, value);
+        }
+        let has_content_type = self
+            .response_headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("content-type"));
+        let (content_type, body) = match &self.response_body {
+            None => (None, String::new()),
+            Some(Value::String(text)) => (Some("text/plain"), text.clone()),
+            Some(value) => (Some("application/json"), value.to_string()),
+        };
+        if let Some(content_type) = content_type
+            && !has_content_type
+        {
+            builder = builder.header("Content-Type", content_type);
+        }
+        builder
+            .body(Full::new(Bytes::from(body)))
+            .unwrap_or_else(|e| plain_response(500, &format!("Invalid mock response `{}`: {e}", self.name)))
+    }
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let method = self.method.as_deref().unwrap_or("*");
+        write!(f, "{method} {} -> {} ({})", self.pattern, self.status, self.name)
+    }
+}
+
+fn check_status(name: &str, status: u16) -> Result<(), String> {
+    if (100..=999).contains(&status) {
+        Ok(())
+    } else {
+        Err(format!("`{name}` has an invalid status {status}"))
+    }
+}
+
+fn is_placeholder(value: &str) -> bool {
+    PLACEHOLDER.find(value).is_some_and(|m| m.as_str() == value)
+}
+
+/// Literal text matches itself; a `{{name}}` placeholder matches anything but `/`
+fn path_regex(path: &str) -> Regex {
+    let mut pattern = String::from("^");
+    let mut last = 0;
+    for placeholder in PLACEHOLDER.find_iter(path) {
+        pattern.push_str(&regex::escape(&path[last..placeholder.start()]));
+        pattern.push_str("[^/]+");
+        last = placeholder.end();
+    }
+    pattern.push_str(&regex::escape(&path[last..]));
+    pattern.push('$');
+    Regex::new(&pattern).unwrap()
+}
+
+/// `expected_body`, else an `Exact` assertion, else the `Contains` objects merged,
+/// plus a placeholder for each field the test stores so later tests get a value
+fn expected_response_body(test: &Test) -> Option<Value> {
+    let assertions = test.assertions.as_deref().unwrap_or_default();
+    let mut body = test.expected_body.clone().or_else(|| {
+        assertions.iter().find_map(|a| match a {
+            JsonAssertion::Exact(value) => Some(value.clone()),
+            _ => None,
+        })
+    });
+    if body.is_none() {
+        let mut merged = Map::new();
+        for assertion in assertions {
+            if let JsonAssertion::Contains(Value::Object(fields)) = assertion {
+                merged.extend(fields.clone());
+            }
+        }
+        if !merged.is_empty() {
+            body = Some(Value::Object(merged));
+        }
+    }
+
+    let mut stored: Vec<_> = test.store.iter().flatten().collect();
+    stored.sort();
+    for (variable, path) in stored {
+        let Some(keys) = path.strip_prefix("$.") else {
+            continue;
+        };
+        if keys.contains('[') {
+            continue;
+        }
+        let mut current = body.get_or_insert_with(|| json!({}));
+        for key in keys.split('.') {
+            if current.is_null() {
+                *current = json!({});
+            }
+            let Value::Object(fields) = current else {
+                break;
+            };
+            current = fields.entry(key).or_insert(Value::Null);
+        }
+        if current.is_null() {
+            *current = Value::String(format!("mock-{variable}"));
+        }
+    }
+    body
+}
+
+/// `[[mocks]]` first, in order, then one route per HTTP test or scenario step
+///
+/// # Errors
+/// Returns an error if a mock or test has an invalid status
+pub fn routes(suite: &TestSuite) -> Result<Vec<Route>, String> {
+    let mut routes = Vec::new();
+    for (index, mock) in suite.mocks.iter().flatten().enumerate() {
+        routes.push(Route::from_mock(mock, index)?);
+    }
+    let requests = suite.all_tests().flat_map(|t| t.steps.as_deref().unwrap_or(std::slice::from_ref(t)));
+    for test in requests.filter(|t| t.is_http()) {
+        routes.push(Route::from_test(test)?);
+    }
+    Ok(routes)
+}
+
+/// Answer connections on `listener` with the first matching route, or 404
+pub async fn serve(listener: TcpListener, routes: Vec<Route>) {
+    let routes = Arc::new(routes);
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let routes = routes.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |request| respond(routes.clone(), request));
+            let _ = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await;
+        });
+    }
+}
+
+async fn respond(routes: Arc<Vec<Route>>, request: Request<Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let bytes = body.collect().await.map(|b| b.to_bytes()).unwrap_or_default();
+    let incoming = IncomingRequest {
+        method: parts.method.to_string(),
+        path: parts.uri.path().to_string(),
+        query: reqwest::Url::parse(&format!("http://mock{}", parts.uri))
+            .map(|url| url.query_pairs().into_owned().collect())
+            .unwrap_or_default(),
+        headers: parts
+            .headers
+            .iter()
+            .filter_map(|(k, v)| Some((k.as_str().to_string(), v.to_str().ok()?.to_string())))
+            .collect(),
+        body: serde_json::from_slice(&bytes).ok(),
+    };
+
+    let Some(route) = routes.iter().find(|r| r.matches(&incoming)) else {
+        println!("{} {} -> 404 (no mock matches)", incoming.method, incoming.path);
+        return Ok(plain_response(
+            404,
+            &format!("No mock matches {} {}", incoming.method, incoming.path),
+        ));
+    };
+    if let Some(delay) = route.delay {
+        tokio::time::sleep(delay).await;
+    }
+    println!("{} {} -> {} ({})", incoming.method, incoming.path, route.status, route.name);
+    Ok(route.response())
+}
+
+fn plain_response(status: u16, message: &str) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(json!({ "error": message }).to_string())));
+    *response.status_mut() = hyper::StatusCode::from_u16(status).unwrap_or(hyper::StatusCode::NOT_FOUND);
+    response
+        .headers_mut()
+        .insert("Content-Type", hyper::header::HeaderValue::from_static("application/json"));
+    response
+}
+
+/// Serve the mocks of `file` on `host:port` until Ctrl-C
+///
+/// # Errors
+/// Returns a message if the file cannot be loaded or the address cannot be bound
+pub async fn run_mock(file: Option<&str>, host: &str, port: u16) -> Result<(), String> {
+    let suite = parse_tests(file).map_err(|e| format!("Failed to parse tests: {e}"))?;
+    let routes = routes(&suite)?;
+    let listener = TcpListener::bind((host, port))
+        .await
+        .map_err(|e| format!("Cannot listen on {host}:{port}: {e}"))?;
+    let address = listener.local_addr().map_err(|e| e.to_string())?;
+
+    println!("Mock server listening on http://{address} with {} routes:", routes.len());
+    for route in &routes {
+        println!("  {route}");
+    }
+    tokio::select! {
+        () = serve(listener, routes) => {}
+        _ = tokio::signal::ctrl_c() => println!("Mock server stopped."),
+    }
+    Ok(())
+}
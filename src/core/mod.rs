@@ -1 +1,6 @@
+pub mod bench;
+pub mod mock;
+pub mod record;
 pub mod runner;
+pub mod selection;
+pub mod watch;
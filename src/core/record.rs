@@ -0,0 +1,333 @@
+//! `catalyst record`: a reverse proxy writing the traffic it forwards into a tests file.
+
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response};
+use hyper_util::rt::TokioIo;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+/// Request bodies whose JSON is longer than this go to a `body_file`
+pub const LARGE_BODY_BYTES: usize = 1024;
+
+/// Request headers left out of the recorded tests: set by the client, hop-by-hop or credentials
+const SKIPPED_HEADERS: &[&str] = &[
+    "host",
+    "content-length",
+    "connection",
+    "keep-alive",
+    "transfer-encoding",
+    "te",
+    "upgrade",
+    "proxy-authorization",
+    "proxy-connection",
+    "accept-encoding",
+    "user-agent",
+    "authorization",
+    "cookie",
+];
+
+/// Response headers the proxy does not pass back as they are
+const HOP_BY_HOP: &[&str] = &["connection", "keep-alive", "transfer-encoding", "content-length"];
+
+/// A forwarded request and the response it got
+#[derive(Debug, Clone, Default)]
+pub struct Exchange {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub status: u16,
+    pub response_body: Vec<u8>,
+}
+
+/// The tests recorded so far, written out after each exchange
+pub struct Recorder {
+    base_url: String,
+    output: PathBuf,
+    tests: Vec<String>,
+    names: HashMap<String, usize>,
+}
+
+impl Recorder {
+    #[must_use]
+    pub fn new(target: &str, output: impl Into<PathBuf>) -> Self {
+        Recorder {
+            base_url: target.trim_end_matches('/').to_string(),
+            output: output.into(),
+            tests: Vec::new(),
+            names: HashMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn output(&self) -> &Path {
+        &self.output
+    }
+
+    /// Add a test for the exchange and rewrite the output file
+    ///
+    /// # Errors
+    /// Returns an error if the tests file or a body file cannot be written
+    pub fn record(&mut self, exchange: &Exchange) -> Result<String, String> {
+        let name = self.unique_name(&format!("{} {}", exchange.method, exchange.path));
+        let mut test = String::from("[[tests]]\n");
+        line(&mut test, "name", toml::Value::String(name.clone()));
+        line(&mut test, "method", toml::Value::String(exchange.method.clone()));
+        line(&mut test, "endpoint", toml::Value::String(exchange.path.clone()));
+        if !exchange.query.is_empty() {
+            line(&mut test, "query_params", string_table(&exchange.query));
+        }
+        let headers: Vec<(String, String)> = exchange
+            .headers
+            .iter()
+            .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.to_lowercase().as_str()))
+            .cloned()
+            .collect();
+        if !headers.is_empty() {
+            line(&mut test, "headers", string_table(&headers));
+        }
+        self.record_body(&mut test, exchange)?;
+        line(&mut test, "expected_status", toml::Value::Integer(exchange.status.into()));
+        if let Some(shape) = serde_json::from_slice(&exchange.response_body).ok().and_then(|b| shape(&b))
+            && let Some(shape) = to_toml(&shape)
+        {
+            let mut assertion = toml::Table::new();
+            assertion.insert("type".to_string(), toml::Value::String("Contains".to_string()));
+            assertion.insert("value".to_string(), shape);
+            line(&mut test, "assertions", toml::Value::Array(vec![toml::Value::Table(assertion)]));
+        }
+        self.tests.push(test);
+        self.write()?;
+        Ok(name)
+    }
+
+    fn unique_name(&mut self, base: &str) -> String {
+        let count = self.names.entry(base.to_string()).or_insert(0);
+        *count += 1;
+        if *count == 1 { base.to_string() } else { format!("{base} ({count})") }
+    }
+
+    /// Small JSON bodies inline, others into a file next to the tests file
+    fn record_body(&self, test: &mut String, exchange: &Exchange) -> Result<(), String> {
+        if exchange.body.is_empty() {
+            return Ok(());
+        }
+        let json: Option<Value> = serde_json::from_slice(&exchange.body).ok();
+        if let Some(json) = &json
+            && exchange.body.len() <= LARGE_BODY_BYTES
+            && let Some(body) = to_toml(json)
+        {
+            line(test, "body", body);
+            return Ok(());
+        }
+
+        let extension = if json.is_some() { "json" } else { "txt" };
+        let relative = format!("{}/{:03}-{}.{extension}", self.bodies_dir(), self.tests.len() + 1, slug(&exchange.method, &exchange.path));
+        let path = self.output.parent().unwrap_or(Path::new(".")).join(&relative);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {e}", dir.display()))?;
+        }
+        fs::write(&path, &exchange.body).map_err(|e| format!("Cannot write {}: {e}", path.display()))?;
+        line(test, "body_file", toml::Value::String(relative));
+        Ok(())
+    }
+
+    /// `recorded_bodies` for `recorded.toml`
+    fn bodies_dir(&self) -> String {
+        let stem = self.output.file_stem().map_or_else(|| "recorded".into(), |s| s.to_string_lossy());
+        format!("{stem}_bodies")
+    }
+
+    /// The tests file as recorded so far
+    #[must_use]
+    pub fn to_toml(&self) -> String {
+        let mut content = String::from("[config]\n");
+        line(&mut content, "base_url", toml::Value::String(self.base_url.clone()));
+        for test in &self.tests {
+            content.push('\n');
+            content.push_str(test);
+        }
+        content
+    }
+
+    fn write(&self) -> Result<(), String> {
+        fs::write(&self.output, self.to_toml()).map_err(|e| format!("Cannot write {}: {e}", self.output.display()))
+    }
+}
+
+fn line(out: &mut String, key: &str, value: toml::Value) {
+    let _ = writeln!(out, "{key} = {value}");
+}
+
+fn string_table(pairs: &[(String, String)]) -> toml::Value {
+    toml::Value::Table(pairs.iter().map(|(k, v)| (k.clone(), toml::Value::String(v.clone()))).collect())
+}
+
+fn slug(method: &str, path: &str) -> String {
+    let mut slug = method.to_lowercase();
+    for part in path.split(|c: char| !c.is_ascii_alphanumeric()).filter(|p| !p.is_empty()) {
+        slug.push('-');
+        slug.push_str(&part.to_lowercase());
+    }
+    slug
+}
+
+/// What a `Contains` assertion checks of a recorded body: the values as they were,
+/// with arrays left empty so that any array matches. `None` when nothing is left.
+#[must_use]
+pub fn shape(body: &Value) -> Option<Value> {
+    match body {
+        Value::Null => None,
+        Value::Array(_) => None,
+        Value::Object(fields) => {
+            let fields: Map<String, Value> = fields
+                .iter()
+                .filter_map(|(key, value)| match value {
+                    Value::Array(_) => Some((key.clone(), Value::Array(Vec::new()))),
+                    _ => Some((key.clone(), shape(value)?)),
+                })
+                .collect();
+            (!fields.is_empty()).then_some(Value::Object(fields))
+        }
+        scalar => Some(scalar.clone()),
+    }
+}
+
+/// TOML has no null: null fields and array items are left out
+fn to_toml(value: &Value) -> Option<toml::Value> {
+    Some(match value {
+        Value::Null => return None,
+        Value::Bool(b) => toml::Value::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => toml::Value::Integer(i),
+            None => toml::Value::Float(n.as_f64()?),
+        },
+        Value::String(s) => toml::Value::String(s.clone()),
+        Value::Array(items) => toml::Value::Array(items.iter().filter_map(to_toml).collect()),
+        Value::Object(fields) => toml::Value::Table(
+            fields
+                .iter()
+                .filter_map(|(k, v)| Some((k.clone(), to_toml(v)?)))
+                .collect(),
+        ),
+    })
+}
+
+/// Forward connections on `listener` to `target`, recording each exchange
+pub async fn serve(listener: TcpListener, target: String, recorder: Arc<Mutex<Recorder>>) {
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap_or_default();
+    let target = Arc::new(target.trim_end_matches('/').to_string());
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let (client, target, recorder) = (client.clone(), target.clone(), recorder.clone());
+        tokio::spawn(async move {
+            let service = service_fn(move |request| {
+                forward(client.clone(), target.clone(), recorder.clone(), request)
+            });
+            let _ = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await;
+        });
+    }
+}
+
+async fn forward(
+    client: reqwest::Client,
+    target: Arc<String>,
+    recorder: Arc<Mutex<Recorder>>,
+    request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = body.collect().await.map(|b| b.to_bytes()).unwrap_or_default();
+    let path_and_query = parts.uri.path_and_query().map_or("/", |p| p.as_str());
+
+    let mut upstream = client.request(parts.method.clone(), format!("{target}{path_and_query}"));
+    for (name, value) in &parts.headers {
+        if name.as_str() != "host" {
+            upstream = upstream.header(name, value);
+        }
+    }
+    let response = match upstream.body(body.clone()).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("{} {path_and_query} -> 502 ({e})", parts.method);
+            let mut response = Response::new(Full::new(Bytes::from(format!("Cannot reach {target}: {e}"))));
+            *response.status_mut() = hyper::StatusCode::BAD_GATEWAY;
+            return Ok(response);
+        }
+    };
+
+    let status = response.status();
+    let headers = response.headers().clone();
+    let response_body = response.bytes().await.unwrap_or_default();
+
+    let exchange = Exchange {
+        method: parts.method.to_string(),
+        path: parts.uri.path().to_string(),
+        query: reqwest::Url::parse(&format!("http://record{path_and_query}"))
+            .map(|url| url.query_pairs().into_owned().collect())
+            .unwrap_or_default(),
+        headers: parts
+            .headers
+            .iter()
+            .filter_map(|(k, v)| Some((k.as_str().to_string(), v.to_str().ok()?.to_string())))
+            .collect(),
+        body: body.to_vec(),
+        status: status.as_u16(),
+        response_body: response_body.to_vec(),
+    };
+    let outcome = recorder.lock().map_err(|e| e.to_string()).and_then(|mut r| r.record(&exchange));
+    match outcome {
+        Ok(name) => println!("{} {path_and_query} -> {} recorded as `{name}`", parts.method, status.as_u16()),
+        Err(e) => println!("{} {path_and_query} -> {} not recorded: {e}", parts.method, status.as_u16()),
+    }
+
+    let mut builder = Response::builder().status(status);
+    for (name, value) in &headers {
+        if !HOP_BY_HOP.contains(&name.as_str()) {
+            builder = builder.header(name, value);
+        }
+    }
+    Ok(builder
+        .body(Full::new(response_body))
+        .unwrap_or_else(|_| Response::new(Full::new(Bytes::new()))))
+}
+
+/// Proxy `host:port` to `target` until Ctrl-C, writing the tests to `output`
+///
+/// # Errors
+/// Returns a message if `output` already exists or the address cannot be bound
+pub async fn run_record(target: &str, host: &str, port: u16, output: &str) -> Result<(), String> {
+    if Path::new(output).exists() {
+        return Err(format!("{output} already exists; choose another file with --output"));
+    }
+    let listener = TcpListener::bind((host, port))
+        .await
+        .map_err(|e| format!("Cannot listen on {host}:{port}: {e}"))?;
+    let address = listener.local_addr().map_err(|e| e.to_string())?;
+    let recorder = Arc::new(Mutex::new(Recorder::new(target, output)));
+
+    println!("Recording http://{address} -> {target} into {output}");
+    tokio::select! {
+        () = serve(listener, target.to_string(), recorder.clone()) => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
+    let recorded = recorder.lock().map(|r| r.tests.len()).unwrap_or_default();
+    println!("Recorded {recorded} tests into {output}.");
+    Ok(())
+}
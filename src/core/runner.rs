@@ -1,14 +1,36 @@
 use crate::checker::parse_tests;
 use crate::debug;
-use crate::engine::variables::load_env_files;
-use crate::http::client::HttpClient;
+use crate::core::selection::{LastRun, Selector, TestSelection};
+use crate::engine::dataset::{bind_parameters, expand_tests, instance_name, restore_variables};
+use crate::engine::expression::{self, ConditionContext, LastResponse};
+use crate::engine::variables::{load_env_files, path_root_key, resolve_variables, select_json_values};
+use crate::engine::verify::Check;
+use crate::http::client::{HttpClient, RequestData};
+use crate::http::timing::Timings;
+use crate::models::suite::TestSuite;
 use crate::models::test::Test;
 use crate::output::TestSummaryFormatter;
+use crate::utils::secrets;
 use colored::Colorize;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::env;
 use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Printed when Ctrl-C stops setup or the tests; teardown still runs
+const INTERRUPTED: &str = "Interrupted";
+/// Printed when Ctrl-C stops a teardown, usually a second one
+const TEARDOWN_INTERRUPTED: &str = "Interrupted, teardown abandoned";
+
+/// Resolves on Ctrl-C or when `interrupt` is notified
+async fn interrupted(interrupt: &Notify) {
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        () = interrupt.notified() => {}
+    }
+}
 
 fn get_terminal_width() -> usize {
     env::var("COLUMNS")
@@ -17,7 +39,7 @@ fn get_terminal_width() -> usize {
         .unwrap_or(80)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct TestResult {
     pub name: String,
     pub success: bool,
@@ -28,22 +50,163 @@ pub struct TestResult {
     pub messages: Vec<String>,
     pub method: String,
     pub endpoint: String,
+    /// The request never completed because a timeout elapsed
+    pub timed_out: bool,
+    /// Phase breakdown of the request, when a response was received
+    pub timings: Option<Timings>,
+    /// Why the test was not run (`skip` or `skip_if`)
+    pub skipped: Option<String>,
+    /// The `xfail` reason of a test that failed as expected
+    pub xfailed: Option<String>,
+    /// The request as sent, when one was built
+    pub request: Option<RequestData>,
+    /// Every expectation checked against the response
+    pub checks: Vec<Check>,
+    /// The result of each step of a scenario, in order
+    pub steps: Vec<TestResult>,
+}
+
+impl TestResult {
+    /// Result of a test that was not run; it neither passes nor fails the suite
+    #[must_use]
+    pub fn skipped(test: &Test, reason: String) -> Self {
+        TestResult {
+            name: test.name.clone(),
+            success: true,
+            expected_status: test.expected_status,
+            method: test.method.clone(),
+            endpoint: test.endpoint.clone(),
+            skipped: Some(reason),
+            ..Default::default()
+        }
+    }
+
+    /// Result of a test that failed before its request could be sent
+    #[must_use]
+    pub fn failed(test: &Test, messages: Vec<String>) -> Self {
+        TestResult {
+            name: test.name.clone(),
+            success: false,
+            expected_status: test.expected_status,
+            messages,
+            method: test.method.clone(),
+            endpoint: test.endpoint.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Result of a scenario from those of its steps. It fails at its first failing step,
+    /// whose request, status and messages it reports; otherwise it reports the last step run.
+    #[must_use]
+    pub fn scenario(test: &Test, steps: Vec<TestResult>) -> Self {
+        let failed = steps.iter().position(|step| !step.success);
+        let shown = failed.or_else(|| steps.iter().rposition(|step| step.skipped.is_none()));
+        let mut result = match shown {
+            Some(index) => {
+                let step = &steps[index];
+                TestResult {
+                    expected_status: step.expected_status,
+                    actual_status: step.actual_status,
+                    response_body: step.response_body.clone(),
+                    headers: step.headers.clone(),
+                    method: step.method.clone(),
+                    endpoint: step.endpoint.clone(),
+                    timed_out: step.timed_out,
+                    ..Default::default()
+                }
+            }
+            None => TestResult::default(),
+        };
+        if let Some(index) = failed {
+            let step = &steps[index];
+            result.messages = step
+                .messages
+                .iter()
+                .map(|message| format!("Step {} `{}`: {message}", index + 1, step.name))
+                .collect();
+        }
+        let total = steps.iter().filter_map(|step| step.timings).map(|t| t.total).sum();
+        result.timings = steps.iter().any(|step| step.timings.is_some()).then(|| Timings {
+            total,
+            ..Timings::default()
+        });
+        result.checks = steps
+            .iter()
+            .flat_map(|step| {
+                step.checks
+                    .iter()
+                    .map(|check| Check::new(format!("{}: {}", step.name, check.description), check.passed))
+            })
+            .collect();
+        TestResult {
+            name: test.name.clone(),
+            success: failed.is_none(),
+            steps,
+            ..result
+        }
+    }
+
+    /// The actual status as shown in reports, or `timed out` when there was no response
+    #[must_use]
+    pub fn actual_status_label(&self) -> String {
+        if self.timed_out {
+            "timed out".to_string()
+        } else {
+            self.actual_status.to_string()
+        }
+    }
+
+    /// Mask registered secrets and credential headers before the result reaches any reporter
+    pub fn redact_secrets(&mut self) {
+        self.response_body = self.response_body.as_ref().map(secrets::redact_json);
+        self.headers = secrets::redact_headers(&self.headers);
+        self.messages = self.messages.iter().map(|m| secrets::redact(m)).collect();
+        if let Some(request) = &mut self.request {
+            let headers: HashMap<String, String> = request.headers.iter().cloned().collect();
+            let redacted = secrets::redact_headers(&headers);
+            for (name, value) in &mut request.headers {
+                if let Some(masked) = redacted.get(name) {
+                    value.clone_from(masked);
+                }
+            }
+            request.url = secrets::redact(&request.url);
+            for (_, value) in &mut request.params {
+                *value = secrets::redact(value);
+            }
+            request.body = request.body.as_ref().map(secrets::redact_json);
+        }
+    }
 }
 
 struct TestExecutionContext<'a> {
     test_suite: &'a crate::models::suite::TestSuite,
-    filter: Option<String>,
+    selector: Selector<'a>,
     verbose: bool,
     test_file_dir: &'a Path,
     client: &'a HttpClient,
-    total: usize,
 }
 
 pub struct TestRunner {
-    pub variables: HashMap<String, String>,
+    pub variables: HashMap<String, Value>,
     pub results: Vec<TestResult>,
+    /// Results of the suite `setup_tests` and `teardown_tests`, kept apart from `results`
+    pub setup_results: Vec<TestResult>,
+    pub teardown_results: Vec<TestResult>,
     pub disable_color: bool,
     pub no_fail_summary: bool,
+    pub allow_undefined: bool,
+    pub profile: Option<String>,
+    /// Tags, names and previous failures to select tests by; `filter` is set by `execute_tests`
+    pub selection: TestSelection,
+    /// The response of the most recent test, read by `status` and `body` in conditions
+    pub last_response: Option<LastResponse>,
+    /// Whether `execute_tests` runs the suite `setup` and `teardown` hooks; watch mode
+    /// turns them off to keep the variables from setup between reruns
+    pub run_setup: bool,
+    pub run_teardown: bool,
+    /// Stops the run like Ctrl-C when notified: the first interrupt ends setup or the
+    /// tests, one during teardown abandons it
+    pub interrupt: Arc<Notify>,
 }
 
 impl TestRunner {
@@ -52,8 +215,31 @@ impl TestRunner {
         TestRunner {
             variables: HashMap::new(),
             results: Vec::new(),
+            setup_results: Vec::new(),
+            teardown_results: Vec::new(),
             disable_color,
             no_fail_summary: false,
+            allow_undefined: false,
+            profile: None,
+            selection: TestSelection::default(),
+            last_response: None,
+            run_setup: true,
+            run_teardown: true,
+            interrupt: Arc::new(Notify::new()),
+        }
+    }
+
+    /// A runner with the same settings and selection, but no variables or results
+    #[must_use]
+    pub fn fresh(&self) -> Self {
+        TestRunner {
+            no_fail_summary: self.no_fail_summary,
+            allow_undefined: self.allow_undefined,
+            profile: self.profile.clone(),
+            selection: self.selection.clone(),
+            run_setup: self.run_setup,
+            run_teardown: self.run_teardown,
+            ..TestRunner::new(self.disable_color)
         }
     }
 
@@ -67,8 +253,14 @@ impl TestRunner {
             "Variables before test '{}': {:?}",
             test.name, self.variables
         );
-        let result =
-            crate::engine::execution::run(client, test, test_file_dir, &mut self.variables).await;
+        let result = crate::engine::execution::run(
+            client,
+            test,
+            test_file_dir,
+            &mut self.variables,
+            self.allow_undefined,
+        )
+        .await;
         debug!("Variables after test '{}': {:?}", test.name, self.variables);
         TestResult {
             name: test.name.clone(),
@@ -80,6 +272,11 @@ impl TestRunner {
             messages: result.errors,
             method: test.method.clone(),
             endpoint: test.endpoint.clone(),
+            timed_out: result.timed_out,
+            timings: result.timings,
+            request: result.request,
+            checks: result.checks,
+            ..Default::default()
         }
     }
 
@@ -92,6 +289,9 @@ impl TestRunner {
     ) -> TestResult {
         let mut test_failed = false;
         let mut error_messages = Vec::new();
+        // What a scenario stores, its hooks included, does not outlive it
+        let outer_variables = test.is_scenario().then(|| self.variables.clone());
+        let shadowed = bind_parameters(&test.parameters, &mut self.variables);
 
         // Execute before hooks
         if let Some(before_steps) = &test.before
@@ -101,6 +301,7 @@ impl TestRunner {
                 test_file_dir,
                 allowed_commands,
                 "before",
+                self.last_response.as_ref(),
             ).await {
             error_messages.push(format!("Before hook failed: {e}"));
             test_failed = true;
@@ -108,32 +309,44 @@ impl TestRunner {
 
         // Execute the actual HTTP test (only if before hooks succeeded)
         let mut result = if test_failed {
-            TestResult {
-                name: test.name.clone(),
-                success: false,
-                expected_status: test.expected_status,
-                actual_status: 0,
-                response_body: None,
-                headers: HashMap::new(),
-                messages: error_messages.clone(),
-                method: test.method.clone(),
-                endpoint: test.endpoint.clone(),
-            }
+            TestResult::failed(test, error_messages.clone())
+        } else if let Some(steps) = &test.steps {
+            self.execute_scenario(test, steps, client, test_file_dir, allowed_commands).await
         } else {
             self.execute_test(test, client, test_file_dir).await
         };
 
         let http_test_success = result.success && !test_failed;
+        if !test_failed {
+            self.last_response = Some(LastResponse {
+                status: result.actual_status,
+                body: result.response_body.clone(),
+            });
+        }
 
         // Execute after hooks (always run, but respect 'on' condition)
         if let Some(after_steps) = &test.after {
-            let filtered_steps: Vec<_> = after_steps.iter().filter(|step| {
-                match step.get_on_condition() {
+            let mut filtered_steps = Vec::new();
+            for step in after_steps {
+                let run = match step.get_on_condition() {
                     "success" => http_test_success,
                     "failure" => !http_test_success,
-                    _ => true,
+                    "always" => true,
+                    condition => {
+                        let context = ConditionContext {
+                            variables: &self.variables,
+                            response: self.last_response.as_ref(),
+                        };
+                        expression::evaluate(condition, &context).unwrap_or_else(|e| {
+                            error_messages.push(format!("After hook has invalid 'on' condition '{condition}': {e}"));
+                            false
+                        })
+                    }
+                };
+                if run {
+                    filtered_steps.push(step.clone());
                 }
-            }).cloned().collect();
+            }
 
             if !filtered_steps.is_empty()
                 && let Err(e) = crate::engine::commands::execute_command_steps(
@@ -142,6 +355,7 @@ impl TestRunner {
                     test_file_dir,
                     allowed_commands,
                     "after",
+                    self.last_response.as_ref(),
                 ).await {
                 result.messages.push(format!("After hook failed: {e}"));
                 // Note: after hook failures don't change the test result success status
@@ -149,9 +363,43 @@ impl TestRunner {
         }
 
         result.messages.extend(error_messages);
+        if let Some(reason) = &test.xfail {
+            if result.success {
+                result.success = false;
+                result.messages.push(format!("Expected to fail ({reason}) but passed"));
+            } else {
+                result.success = true;
+                result.xfailed = Some(reason.clone());
+            }
+        }
+        restore_variables(shadowed, &mut self.variables);
+        secrets::register_variables(&self.variables);
+        if let Some(outer) = outer_variables {
+            self.variables = outer;
+        }
+        result.redact_secrets();
         result
     }
 
+    /// Run the steps of a scenario in order; the steps after a failing one are skipped
+    async fn execute_scenario(
+        &mut self,
+        test: &Test,
+        steps: &[Test],
+        client: &HttpClient,
+        test_file_dir: &Path,
+        allowed_commands: Option<&[String]>,
+    ) -> TestResult {
+        let mut results: Vec<TestResult> = Vec::with_capacity(steps.len());
+        for step in steps {
+            match results.iter().position(|result| !result.success) {
+                Some(index) => results.push(TestResult::skipped(step, format!("step {} failed", index + 1))),
+                None => results.extend(Box::pin(self.run_each(step, client, test_file_dir, allowed_commands)).await),
+            }
+        }
+        TestResult::scenario(test, results)
+    }
+
     pub async fn execute_tests(
         &mut self,
         filter: Option<String>,
@@ -162,61 +410,264 @@ impl TestRunner {
     ) {
         load_env_files();
         self.no_fail_summary = no_fail_summary;
-
-        // Parse CLI variables and add them to the runner's variables
-        let cli_variables = crate::cli::Commands::parse_variables(var);
-        self.variables.extend(cli_variables);
+        if filter.is_some() {
+            self.selection.filter = filter;
+        }
 
         // Calculate test file directory
         let test_file_path = file.as_deref().unwrap_or(".catalyst/tests.toml");
         let test_file_dir = Path::new(test_file_path).parent().unwrap_or(Path::new("."));
 
-        let test_suite = match parse_tests(file.as_deref()) {
+        let test_suite = match self.load_suite(file.as_deref(), var) {
             Ok(suite) => suite,
             Err(e) => {
-                eprintln!("{}", format!("Failed to parse tests: {e}").red());
+                eprintln!("{}", e.red());
                 return;
             }
         };
 
-        let client = HttpClient::new(&test_suite.config);
+        let results_path = LastRun::path_for(Path::new(test_file_path));
+        let last_run = if self.selection.only_failed {
+            match LastRun::load(&results_path) {
+                Ok(last_run) => Some(last_run),
+                Err(e) => {
+                    eprintln!("{}", e.red());
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        let selection = self.selection.clone();
+
+        let client = HttpClient::new(&test_suite.config).with_base_dir(test_file_dir);
         let mut skipped = 0;
-        let total = test_suite.tests.len();
+        let interrupt = Arc::clone(&self.interrupt);
+
+        // Teardown runs however setup and the tests end, Ctrl-C included
+        let set_up = !self.run_setup
+            || tokio::select! {
+                setup = self.execute_setup(&test_suite, &client, test_file_dir) => match setup {
+                    Ok(()) => true,
+                    Err(e) => {
+                        eprintln!("{}", e.red());
+                        false
+                    }
+                },
+                () = interrupted(&interrupt) => {
+                    eprintln!("\n{}", INTERRUPTED.yellow());
+                    false
+                }
+            };
 
-        // Execute suite setup hooks
-        if let Some(setup_steps) = &test_suite.setup
-            && let Err(e) = crate::engine::commands::execute_command_steps(
+        if set_up {
+            let context = TestExecutionContext {
+                test_suite: &test_suite,
+                selector: selection.selector(last_run.as_ref()),
+                verbose,
+                test_file_dir,
+                client: &client,
+            };
+            let interrupted = tokio::select! {
+                () = self.execute_tests_with_hooks(context, &mut skipped) => false,
+                () = interrupted(&interrupt) => true,
+            };
+            if interrupted {
+                eprintln!("\n{}", INTERRUPTED.yellow());
+            } else {
+                // A narrowed run must not forget the failures of the tests it left out
+                let mut recorded = LastRun::load(&results_path).unwrap_or_default();
+                recorded.merge(LastRun::from_results(&self.results));
+                if let Err(e) = recorded.save(&results_path) {
+                    eprintln!("{}", e.yellow());
+                }
+            }
+            self.display_compact_results(skipped, verbose);
+            if !self.no_fail_summary && !verbose {
+                self.display_failure_details();
+            }
+        }
+
+        if self.run_teardown {
+            self.execute_teardown(&test_suite, &client, test_file_dir).await;
+        }
+    }
+
+    /// Parse the tests file, expand its datasets and seed the variables from `--var`,
+    /// the profile, `env.store`, dotenv and the process env
+    ///
+    /// # Errors
+    /// Returns a message if the file cannot be parsed, its data loaded or its variables resolved
+    pub fn load_suite(&mut self, file: Option<&str>, var: Option<String>) -> Result<TestSuite, String> {
+        let test_file_dir = Path::new(file.unwrap_or(".catalyst/tests.toml")).parent().unwrap_or(Path::new("."));
+        let mut test_suite = parse_tests(file).map_err(|e| format!("Failed to parse tests: {e}"))?;
+        test_suite.tests =
+            expand_tests(&test_suite.tests, test_file_dir).map_err(|e| format!("Failed to load test data: {e}"))?;
+        for tests in test_suite.setup_tests.iter_mut().chain(test_suite.teardown_tests.iter_mut()) {
+            *tests = expand_tests(tests, test_file_dir).map_err(|e| format!("Failed to load test data: {e}"))?;
+        }
+
+        let cli_variables = crate::cli::Commands::parse_variables(var);
+        let resolved = resolve_variables(&test_suite, self.profile.as_deref(), &cli_variables)
+            .map_err(|e| format!("Failed to resolve variables: {e}"))?;
+        for variable in resolved {
+            self.variables.entry(variable.name).or_insert(variable.value);
+        }
+        if let Some(names) = &test_suite.config.secrets {
+            secrets::declare_secret_names(names);
+        }
+        secrets::register_variables(&self.variables);
+        Ok(test_suite)
+    }
+
+    /// Run the suite `setup` hooks, then its `setup_tests` with `client`, printing their results
+    ///
+    /// # Errors
+    /// Returns the redacted failure of the first failing step, or names the first failing setup test
+    pub async fn execute_setup(
+        &mut self,
+        test_suite: &TestSuite,
+        client: &HttpClient,
+        test_file_dir: &Path,
+    ) -> Result<(), String> {
+        let allowed_commands = test_suite.config.allowed_commands.as_deref();
+        if let Some(setup_steps) = &test_suite.setup {
+            let setup_result = crate::engine::commands::execute_command_steps(
                 setup_steps,
                 &mut self.variables,
                 test_file_dir,
-                test_suite.config.allowed_commands.as_deref(),
+                allowed_commands,
                 "setup",
-            ).await {
-            eprintln!("{}", format!("Setup failed: {e}").red());
-            return;
+                self.last_response.as_ref(),
+            ).await;
+            secrets::register_variables(&self.variables);
+            setup_result.map_err(|e| secrets::redact(&format!("Setup failed: {e}")))?;
         }
 
-        // Execute tests with defer for teardown
-        let context = TestExecutionContext {
-            test_suite: &test_suite,
-            filter,
-            verbose,
-            test_file_dir,
-            client: &client,
-            total,
+        let Some(tests) = &test_suite.setup_tests else {
+            return Ok(());
+        };
+        let mut failed = None;
+        for test in tests {
+            let results = self.run_each(test, client, test_file_dir, allowed_commands).await;
+            failed = results.iter().find(|result| !result.success).map(|result| result.name.clone());
+            self.setup_results.extend(results);
+            if failed.is_some() {
+                break;
+            }
+        }
+        let formatter = TestSummaryFormatter::new(self.disable_color);
+        print!("{}", formatter.format_suite_tests("Setup", &self.setup_results));
+        match failed {
+            Some(name) => Err(format!("Setup failed: test `{name}` failed")),
+            None => Ok(()),
+        }
+    }
+
+    /// Run one test with its hooks, unless its `skip` or `skip_if` applies
+    pub async fn run_test(
+        &mut self,
+        test: &Test,
+        client: &HttpClient,
+        test_file_dir: &Path,
+        allowed_commands: Option<&[String]>,
+    ) -> TestResult {
+        match self.skip_reason(test) {
+            Ok(Some(reason)) => TestResult::skipped(test, reason),
+            Ok(None) => self.execute_test_with_hooks(test, client, test_file_dir, allowed_commands).await,
+            Err(e) => TestResult::failed(test, vec![e]),
+        }
+    }
+
+    /// Run a test once, or once per item of its `for_each`; each run is a result of its own,
+    /// named after its `index`
+    pub async fn run_each(
+        &mut self,
+        test: &Test,
+        client: &HttpClient,
+        test_file_dir: &Path,
+        allowed_commands: Option<&[String]>,
+    ) -> Vec<TestResult> {
+        let Some(path) = &test.for_each else {
+            return vec![self.run_test(test, client, test_file_dir, allowed_commands).await];
+        };
+        match self.skip_reason(test) {
+            Ok(Some(reason)) => return vec![TestResult::skipped(test, reason)],
+            Ok(None) => {}
+            Err(e) => return vec![TestResult::failed(test, vec![e])],
+        }
+        let items = match self.for_each_items(path) {
+            Ok(items) if items.is_empty() => {
+                return vec![TestResult::skipped(test, format!("for_each {path} selected no items"))];
+            }
+            Ok(items) => items,
+            Err(e) => return vec![TestResult::failed(test, vec![e])],
         };
-        self.execute_tests_with_hooks(context, &mut skipped).await;
 
-        // Execute suite teardown hooks (always runs)
+        let mut results = Vec::with_capacity(items.len());
+        for (index, item) in items.into_iter().enumerate() {
+            let index = Value::from(index);
+            let mut instance = test.clone();
+            instance.for_each = None;
+            instance.name = instance_name(&test.name, &[("index".to_string(), index.clone())]);
+            instance.parameters.extend([("item".to_string(), item), ("index".to_string(), index)]);
+            results.push(Box::pin(self.run_test(&instance, client, test_file_dir, allowed_commands)).await);
+        }
+        results
+    }
+
+    /// The items `for_each` selects from the variables; a path without `*` names an array
+    /// whose elements are the items
+    fn for_each_items(&self, path: &str) -> Result<Vec<Value>, String> {
+        if let Some(key) = path_root_key(path)
+            && !self.variables.contains_key(key)
+        {
+            return Err(format!("for_each {path}: `{key}` is not set"));
+        }
+        let variables = Value::Object(self.variables.iter().map(|(k, v)| (k.clone(), v.clone())).collect());
+        let selected = select_json_values(&variables, path);
+        if path.contains('*') {
+            return Ok(selected.into_iter().cloned().collect());
+        }
+        match selected.as_slice() {
+            [Value::Array(items)] => Ok(items.clone()),
+            [value] => Err(format!("for_each {path} is {value}, not an array")),
+            _ => Err(format!("for_each {path} selects nothing")),
+        }
+    }
+
+    /// Run every one of the suite `teardown_tests` with `client`, printing their results, then
+    /// the `teardown` hooks; all with the current variables. Ctrl-C abandons the rest, so a
+    /// hanging teardown can still be stopped after the run was interrupted
+    pub async fn execute_teardown(&mut self, test_suite: &TestSuite, client: &HttpClient, test_file_dir: &Path) {
+        let interrupt = Arc::clone(&self.interrupt);
+        tokio::select! {
+            () = self.teardown(test_suite, client, test_file_dir) => {}
+            () = interrupted(&interrupt) => eprintln!("\n{}", TEARDOWN_INTERRUPTED.yellow()),
+        }
+    }
+
+    async fn teardown(&mut self, test_suite: &TestSuite, client: &HttpClient, test_file_dir: &Path) {
+        let allowed_commands = test_suite.config.allowed_commands.as_deref();
+        if let Some(tests) = &test_suite.teardown_tests {
+            for test in tests {
+                let results = self.run_each(test, client, test_file_dir, allowed_commands).await;
+                self.teardown_results.extend(results);
+            }
+            let formatter = TestSummaryFormatter::new(self.disable_color);
+            print!("{}", formatter.format_suite_tests("Teardown", &self.teardown_results));
+        }
+
         if let Some(teardown_steps) = &test_suite.teardown
             && let Err(e) = crate::engine::commands::execute_command_steps(
                 teardown_steps,
                 &mut self.variables,
                 test_file_dir,
-                test_suite.config.allowed_commands.as_deref(),
+                allowed_commands,
                 "teardown",
+                self.last_response.as_ref(),
             ).await {
-            eprintln!("{}", format!("Teardown failed: {e}").red());
+            eprintln!("{}", secrets::redact(&format!("Teardown failed: {e}")).red());
         }
     }
 
@@ -225,9 +676,14 @@ impl TestRunner {
         context: TestExecutionContext<'_>,
         skipped: &mut usize,
     ) {
+        let only = context
+            .test_suite
+            .tests
+            .iter()
+            .any(|t| t.only == Some(true) && context.selector.matches(t));
+
         for test in &context.test_suite.tests {
-            if let Some(ref f) = context.filter
-                && !test.name.contains(f) {
+            if !context.selector.matches(test) || (only && test.only != Some(true)) {
                 *skipped += 1;
                 if context.verbose {
                     println!("{} {}", "SKIP".yellow(), test.name);
@@ -235,133 +691,187 @@ impl TestRunner {
                 continue;
             }
 
-            let result = self.execute_test_with_hooks(test, context.client, context.test_file_dir, context.test_suite.config.allowed_commands.as_deref()).await;
-            let status_matches = result.expected_status == result.actual_status;
+            let allowed_commands = context.test_suite.config.allowed_commands.as_deref();
+            for result in self.run_each(test, context.client, context.test_file_dir, allowed_commands).await {
+                match &result.skipped {
+                    Some(reason) => self.print_skipped(&result.name, reason, context.verbose),
+                    None => self.print_result(test, &result, context.verbose),
+                }
+                self.results.push(result);
+            }
+        }
+    }
 
-            if context.verbose {
-                println!("\n{}", "━".repeat(get_terminal_width()).blue());
-                println!("Test: {}", test.name.bold());
-                println!(
-                    "Endpoint: {} {}",
-                    test.method.yellow(),
-                    test.endpoint.yellow()
-                );
-                println!(
-                    "Success: {}",
-                    if result.success {
-                        "Yes".green()
-                    } else {
-                        "No".red()
-                    }
-                );
+    fn print_skipped(&self, name: &str, reason: &str, verbose: bool) {
+        if verbose {
+            println!("{} {name} ({reason})", "SKIP".yellow());
+        } else if self.disable_color {
+            println!("- {name} (skipped: {reason})");
+        } else {
+            println!("{} {name} {}", "-".yellow(), format!("(skipped: {reason})").yellow());
+        }
+    }
+
+    /// Print a result as it comes in; the details are left to the summary unless `verbose`
+    fn print_result(&self, test: &Test, result: &TestResult, verbose: bool) {
+        let status_matches = result.expected_status == result.actual_status;
+
+        if verbose {
+            println!("\n{}", "━".repeat(get_terminal_width()).blue());
+            println!("Test: {}", result.name.bold());
+            println!(
+                "Endpoint: {} {}",
+                test.method.yellow(),
+                test.endpoint.yellow()
+            );
+            println!(
+                "Success: {}",
+                if result.success {
+                    "Yes".green()
+                } else {
+                    "No".red()
+                }
+            );
+
+            let status_display = format!(
+                "Status: {} (expected {})",
+                if status_matches {
+                    result.actual_status_label().green()
+                } else {
+                    result.actual_status_label().red()
+                },
+                result.expected_status.to_string().bold()
+            );
+            println!("{status_display}");
+            if let Some(timings) = &result.timings {
+                println!("Timing: {timings}");
+            }
 
-                let status_display = format!(
-                    "Status: {} (expected {})",
-                    if status_matches {
-                        result.actual_status.to_string().green()
-                    } else {
-                        result.actual_status.to_string().red()
-                    },
-                    result.expected_status.to_string().bold()
+            if let Some(body) = &result.response_body {
+                println!(
+                    "\nResponse Body: {}",
+                    serde_json::to_string_pretty(body).unwrap_or(body.to_string())
                 );
-                println!("{status_display}");
+            }
 
-                if let Some(body) = &result.response_body {
+            if let (Some(expected_body), Some(actual_body)) =
+                (&test.expected_body, &result.response_body)
+            {
+                println!("\nBody comparison:");
+                let body_matches = expected_body == actual_body;
+                if body_matches {
+                    println!("  {}", "✓ Body matches expected value".green());
+                } else {
+                    println!("  {}", "✗ Body differs from expected value".red());
                     println!(
-                        "\nResponse Body: {}",
-                        serde_json::to_string_pretty(body).unwrap_or(body.to_string())
+                        "    Expected: {}",
+                        serde_json::to_string_pretty(&expected_body)
+                            .unwrap()
+                            .green()
+                    );
+                    println!(
+                        "    Actual:   {}",
+                        serde_json::to_string_pretty(&actual_body).unwrap().red()
                     );
                 }
+            }
 
-                if let (Some(expected_body), Some(actual_body)) =
-                    (&test.expected_body, &result.response_body)
-                {
-                    println!("\nBody comparison:");
-                    let body_matches = expected_body == actual_body;
-                    if body_matches {
-                        println!("  {}", "✓ Body matches expected value".green());
-                    } else {
-                        println!("  {}", "✗ Body differs from expected value".red());
-                        println!(
-                            "    Expected: {}",
-                            serde_json::to_string_pretty(&expected_body)
-                                .unwrap()
-                                .green()
-                        );
-                        println!(
-                            "    Actual:   {}",
-                            serde_json::to_string_pretty(&actual_body).unwrap().red()
-                        );
-                    }
-                }
+            if !result.steps.is_empty() {
+                println!("\nSteps:");
+                print!("{}", TestSummaryFormatter::new(self.disable_color).format_steps(result, "  "));
+            }
 
-                if !result.messages.is_empty() {
-                    println!("\nMessages:");
-                    for msg in &result.messages {
-                        println!("  {} {}", "-".bold(), msg.red());
-                    }
+            if let Some(reason) = &result.xfailed {
+                println!("Expected failure: {}", reason.yellow());
+            }
+
+            if !result.messages.is_empty() {
+                println!("\nMessages:");
+                for msg in &result.messages {
+                    println!("  {} {}", "-".bold(), msg.red());
                 }
-            } else if !self.disable_color {
-                let status_indicator = if status_matches {
-                    "✓".green()
+            }
+        } else if !self.disable_color {
+            let status_indicator = if status_matches {
+                "✓".green()
+            } else {
+                "✗".red()
+            };
+            println!(
+                "{} {} {}",
+                status_indicator,
+                result.name,
+                if status_matches {
+                    format!(
+                        "(expected {}, got {})",
+                        result.expected_status,
+                        result.actual_status_label().green()
+                    )
                 } else {
-                    "✗".red()
-                };
-                println!(
-                    "{} {} {}",
-                    status_indicator,
-                    test.name,
-                    if status_matches {
-                        format!(
-                            "(expected {}, got {})",
-                            result.expected_status,
-                            result.actual_status.to_string().green()
-                        )
-                    } else {
-                        format!(
-                            "(expected {}, got {})",
-                            result.expected_status,
-                            result.actual_status.to_string().red()
-                        )
-                    }
-                );
+                    format!(
+                        "(expected {}, got {})",
+                        result.expected_status,
+                        result.actual_status_label().red()
+                    )
+                }
+            );
 
-                if !result.success {
-                    if let (Some(expected_body), Some(actual_body)) =
-                        (&test.expected_body, &result.response_body)
-                        && expected_body != actual_body {
-                        println!("  {}", "Body mismatch".red());
-                    }
-                    for msg in &result.messages {
-                        println!("  {} {}", "-".bold(), msg.red());
-                    }
+            print!("{}", TestSummaryFormatter::new(false).format_steps(result, "    "));
+            if let Some(reason) = &result.xfailed {
+                println!("  {} {reason}", "expected failure:".yellow());
+            }
+            if !result.success {
+                if let (Some(expected_body), Some(actual_body)) =
+                    (&test.expected_body, &result.response_body)
+                    && expected_body != actual_body {
+                    println!("  {}", "Body mismatch".red());
                 }
-            } else {
-                println!("{} {}", if result.success { "✓" } else { "✗" }, test.name);
-                if !result.success {
-                    println!("  Expected status: {}", result.expected_status);
-                    println!("  Actual status: {}", result.actual_status);
-                    for msg in &result.messages {
-                        println!("  - {msg}");
-                    }
+                for msg in &result.messages {
+                    println!("  {} {}", "-".bold(), msg.red());
+                }
+            }
+        } else {
+            println!("{} {}", if result.success { "✓" } else { "✗" }, result.name);
+            print!("{}", TestSummaryFormatter::new(true).format_steps(result, "    "));
+            if let Some(reason) = &result.xfailed {
+                println!("  expected failure: {reason}");
+            }
+            if !result.success {
+                println!("  Expected status: {}", result.expected_status);
+                println!("  Actual status: {}", result.actual_status_label());
+                for msg in &result.messages {
+                    println!("  - {msg}");
                 }
             }
-
-            self.results.push(result);
         }
+    }
 
-        self.display_compact_results(*skipped, context.total, context.verbose);
-        if !self.no_fail_summary && !context.verbose {
-            self.display_failure_details();
+    /// Why `test` should not run: its `skip` reason, or its `skip_if` condition holding.
+    /// A `skip_if` that cannot be evaluated is an error, which fails the test
+    fn skip_reason(&self, test: &Test) -> Result<Option<String>, String> {
+        if let Some(reason) = &test.skip {
+            return Ok(Some(reason.clone()));
         }
+        let Some(condition) = &test.skip_if else {
+            return Ok(None);
+        };
+        let context = ConditionContext {
+            variables: &self.variables,
+            response: self.last_response.as_ref(),
+        };
+        expression::evaluate(condition, &context)
+            .map(|skip| skip.then(|| format!("skip_if {condition}")))
+            .map_err(|e| format!("Invalid `skip_if` condition '{condition}': {e}"))
     }
 
-    fn display_compact_results(&self, skipped: usize, total: usize, verbose: bool) {
+    fn display_compact_results(&self, skipped: usize, verbose: bool) {
         // Only display compact results in non-verbose mode
         if verbose {
             return;
         }
         
+        // Every run of a `for_each` test is a result of its own
+        let total = self.results.len() + skipped;
         let formatter = TestSummaryFormatter::new(self.disable_color);
         let output = formatter.format_compact_results(&self.results, skipped, total);
         print!("{output}");
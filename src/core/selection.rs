@@ -0,0 +1,130 @@
+use crate::core::runner::TestResult;
+use crate::models::test::Test;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Which tests of the suite to run, from the `run` selection flags
+#[derive(Debug, Clone, Default)]
+pub struct TestSelection {
+    /// Substring or regular expression matched against the test name
+    pub filter: Option<String>,
+    /// Keep tests carrying at least one of these tags
+    pub tags: Vec<String>,
+    /// Drop tests carrying any of these tags
+    pub exclude_tags: Vec<String>,
+    /// Keep only tests with exactly one of these names
+    pub names: Vec<String>,
+    /// Keep only the tests that failed in the previous run
+    pub only_failed: bool,
+}
+
+/// A `TestSelection` ready to match tests
+#[derive(Debug)]
+pub struct Selector<'a> {
+    selection: &'a TestSelection,
+    pattern: Option<Regex>,
+    previous_failures: Option<HashSet<String>>,
+}
+
+impl TestSelection {
+    /// Compile the filter; with `only_failed`, tests must be among the failures of `last_run`
+    #[must_use]
+    pub fn selector(&self, last_run: Option<&LastRun>) -> Selector<'_> {
+        Selector {
+            selection: self,
+            // A filter that is not a valid pattern still matches as a plain substring
+            pattern: self.filter.as_deref().and_then(|f| Regex::new(f).ok()),
+            previous_failures: if self.only_failed {
+                Some(last_run.map(|r| r.failed.iter().cloned().collect()).unwrap_or_default())
+            } else {
+                None
+            },
+        }
+    }
+}
+
+impl Selector<'_> {
+    #[must_use]
+    pub fn matches(&self, test: &Test) -> bool {
+        let selection = self.selection;
+        let tags = test.tags.as_deref().unwrap_or_default();
+
+        if let Some(filter) = &selection.filter
+            && !test.name.contains(filter.as_str())
+            && !self.pattern.as_ref().is_some_and(|p| p.is_match(&test.name))
+        {
+            return false;
+        }
+        if !selection.names.is_empty() && !selection.names.contains(&test.name) {
+            return false;
+        }
+        if !selection.tags.is_empty() && !tags.iter().any(|t| selection.tags.contains(t)) {
+            return false;
+        }
+        if tags.iter().any(|t| selection.exclude_tags.contains(t)) {
+            return false;
+        }
+        self.previous_failures
+            .as_ref()
+            .is_none_or(|failed| failed.contains(&test.name))
+    }
+}
+
+/// Names of the tests that passed and failed when they last ran, saved next to the test file;
+/// skipped tests are in neither list
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LastRun {
+    pub passed: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+impl LastRun {
+    #[must_use]
+    pub fn from_results(results: &[TestResult]) -> Self {
+        let (passed, failed): (Vec<_>, Vec<_>) = results
+            .iter()
+            .filter(|r| r.skipped.is_none())
+            .partition(|r| r.success);
+        LastRun {
+            passed: passed.into_iter().map(|r| r.name.clone()).collect(),
+            failed: failed.into_iter().map(|r| r.name.clone()).collect(),
+        }
+    }
+
+    /// Record the outcomes of `run` over those of the same tests; tests that `run` left out
+    /// keep the outcome of their own last run
+    pub fn merge(&mut self, run: LastRun) {
+        let ran: HashSet<String> = run.passed.iter().chain(&run.failed).cloned().collect();
+        self.passed.retain(|name| !ran.contains(name));
+        self.failed.retain(|name| !ran.contains(name));
+        self.passed.extend(run.passed);
+        self.failed.extend(run.failed);
+    }
+
+    /// `.catalyst/tests.toml` records its runs in `.catalyst/tests.last-run.json`
+    #[must_use]
+    pub fn path_for(test_file: &Path) -> PathBuf {
+        let stem = test_file.file_stem().and_then(|s| s.to_str()).unwrap_or("tests");
+        test_file.with_file_name(format!("{stem}.last-run.json"))
+    }
+
+    /// # Errors
+    /// Returns an error if the file is missing or not a results file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|_| format!("No previous run found at '{}'", path.display()))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid results file '{}': {e}", path.display()))
+    }
+
+    /// # Errors
+    /// Returns an error if the file cannot be written
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content)
+            .map_err(|e| format!("Cannot write results file '{}': {e}", path.display()))
+    }
+}
@@ -0,0 +1,396 @@
+use crate::checker::{load_document, parse_tests};
+use crate::core::runner::TestRunner;
+use crate::engine::dataset::expand_tests;
+use crate::engine::variables::path_root_key;
+use crate::http::client::HttpClient;
+use crate::models::test::GraphqlRequest;
+use crate::utils::string::find_variable_references;
+use colored::Colorize;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use toml::{Table, Value};
+
+/// How long to wait for an editor to finish writing before rerunning
+const SETTLE_TIME: Duration = Duration::from_millis(150);
+
+/// Settings of `catalyst run --watch`, on top of those of the runner
+#[derive(Debug, Clone, Default)]
+pub struct WatchOptions {
+    pub file: String,
+    pub filter: Option<String>,
+    pub verbose: bool,
+    pub var: Option<String>,
+    pub no_fail_summary: bool,
+    /// Run setup once and keep its variables (and those stored by tests) across reruns
+    pub keep_variables: bool,
+}
+
+/// What a change asks watch mode to run again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rerun {
+    /// The config, suite hooks or variables changed
+    Everything,
+    /// Names of the affected tests, in suite order
+    Tests(Vec<String>),
+}
+
+/// What one test reads and stores, taken from its TOML
+#[derive(Debug, Clone)]
+struct TestInputs {
+    name: String,
+    definition: Value,
+    files: BTreeSet<PathBuf>,
+    uses: HashSet<String>,
+    defines: HashSet<String>,
+}
+
+/// A tests file as watch mode compares it between runs
+#[derive(Debug, Clone)]
+pub struct SuiteSnapshot {
+    file: PathBuf,
+    globals: Table,
+    tests: Vec<TestInputs>,
+}
+
+impl SuiteSnapshot {
+    /// # Errors
+    /// Returns an error if the tests file cannot be read or parsed
+    pub fn load(file: &Path) -> Result<Self, String> {
+        let file = std::path::absolute(file).map_err(|e| e.to_string())?;
+        let mut globals = load_document(&file.to_string_lossy())?;
+        let dir = file.parent().unwrap_or(Path::new("."));
+
+        let tests = match globals.remove("tests") {
+            Some(Value::Array(tests)) => tests
+                .into_iter()
+                .filter_map(|definition| test_inputs(definition, dir))
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(SuiteSnapshot { file, globals, tests })
+    }
+
+    /// The tests file and every file its tests read, as absolute paths
+    #[must_use]
+    pub fn files(&self) -> BTreeSet<PathBuf> {
+        let mut files: BTreeSet<PathBuf> = self.tests.iter().flat_map(|t| t.files.iter().cloned()).collect();
+        files.insert(self.file.clone());
+        files
+    }
+
+    /// The tests of `self` whose definition differs from `previous` or that read one of
+    /// the `changed` files, plus the tests using variables they store
+    #[must_use]
+    pub fn affected(&self, previous: &SuiteSnapshot, changed: &HashSet<PathBuf>) -> Rerun {
+        if self.globals != previous.globals {
+            return Rerun::Everything;
+        }
+        let before: HashMap<&str, &Value> =
+            previous.tests.iter().map(|t| (t.name.as_str(), &t.definition)).collect();
+
+        let mut affected: Vec<bool> = self
+            .tests
+            .iter()
+            .map(|t| {
+                before.get(t.name.as_str()) != Some(&&t.definition) || t.files.iter().any(|f| changed.contains(f))
+            })
+            .collect();
+
+        // A later test using a variable stored by an affected test is affected too
+        for (i, test) in self.tests.iter().enumerate() {
+            if affected[i] {
+                continue;
+            }
+            affected[i] = self.tests[..i]
+                .iter()
+                .zip(&affected)
+                .any(|(earlier, hit)| *hit && !earlier.defines.is_disjoint(&test.uses));
+        }
+        Rerun::Tests(self.names_where(&affected))
+    }
+
+    /// `names` plus the earlier tests storing the variables they use, in suite order
+    #[must_use]
+    pub fn with_dependencies(&self, names: &[String]) -> Vec<String> {
+        let mut needed: Vec<bool> = self.tests.iter().map(|t| names.contains(&t.name)).collect();
+        for i in (0..self.tests.len()).rev() {
+            if !needed[i] {
+                continue;
+            }
+            let uses = &self.tests[i].uses;
+            for (j, earlier) in self.tests[..i].iter().enumerate() {
+                needed[j] |= !earlier.defines.is_disjoint(uses);
+            }
+        }
+        self.names_where(&needed)
+    }
+
+    fn names_where(&self, flags: &[bool]) -> Vec<String> {
+        self.tests
+            .iter()
+            .zip(flags)
+            .filter(|(_, flag)| **flag)
+            .map(|(t, _)| t.name.clone())
+            .collect()
+    }
+}
+
+fn test_inputs(definition: Value, dir: &Path) -> Option<TestInputs> {
+    let table = definition.as_table()?;
+    let name = table.get("name")?.as_str()?.to_string();
+
+    let mut files = BTreeSet::new();
+    let steps = table.get("steps").and_then(Value::as_array).into_iter().flatten();
+    for request in std::iter::once(table).chain(steps.filter_map(Value::as_table)) {
+        for key in ["body_file", "data_file"] {
+            if let Some(path) = request.get(key).and_then(Value::as_str) {
+                files.insert(resolve(dir, path));
+            }
+        }
+    }
+    if let Some(graphql) = table.get("graphql").and_then(|g| GraphqlRequest::deserialize(g.clone()).ok())
+        && let Some(query_file) = graphql.query_file()
+    {
+        files.insert(resolve(dir, query_file));
+    }
+    let mut uses = HashSet::new();
+    for text in strings(&definition) {
+        for caps in file_include_pattern().captures_iter(text) {
+            files.insert(resolve(dir, &caps[1]));
+        }
+        uses.extend(find_variable_references(text).into_iter().map(str::to_string));
+    }
+    if let Some(key) = table.get("for_each").and_then(Value::as_str).and_then(path_root_key) {
+        uses.insert(key.to_string());
+    }
+
+    let mut defines = HashSet::new();
+    let script = table.get("script").and_then(Value::as_array).into_iter().flatten();
+    for store in std::iter::once(table).chain(script.filter_map(Value::as_table)).filter_map(|t| t.get("store")) {
+        if let Some(store) = store.as_table() {
+            defines.extend(store.keys().cloned());
+        }
+    }
+    if let Some(cookies) = table.get("get_cookie").and_then(Value::as_table) {
+        defines.extend(cookies.values().filter_map(Value::as_str).map(str::to_string));
+    }
+    let hooks = ["before", "after"]
+        .iter()
+        .filter_map(|key| table.get(*key).and_then(Value::as_array))
+        .flatten();
+    for step in hooks {
+        if let Some(var) = step.get("capture").and_then(|c| c.get("var")).and_then(Value::as_str) {
+            defines.insert(var.to_string());
+            defines.insert(format!("{var}_stderr"));
+        }
+        if let Some(exports) = step.get("export").and_then(Value::as_table) {
+            defines.extend(exports.keys().cloned());
+        }
+    }
+
+    Some(TestInputs { name, files, uses, defines, definition })
+}
+
+/// Paths the way file events report them
+fn resolve(dir: &Path, path: &str) -> PathBuf {
+    let path = dir.join(path);
+    std::path::absolute(&path).unwrap_or(path)
+}
+
+fn file_include_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\{\{file:([^}]+)\}\}").unwrap())
+}
+
+/// Every string in a TOML value, keys excluded
+fn strings(value: &Value) -> Vec<&str> {
+    match value {
+        Value::String(s) => vec![s.as_str()],
+        Value::Array(items) => items.iter().flat_map(strings).collect(),
+        Value::Table(table) => table.values().flat_map(strings).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Run the suite, then rerun the affected tests whenever one of its files changes,
+/// until Ctrl-C
+pub async fn watch(settings: TestRunner, options: WatchOptions) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher = match notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        // Running the tests reads the files too; only writes count
+        if let Ok(event) = event
+            && (event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove())
+        {
+            let _ = tx.send(event.paths);
+        }
+    }) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("{}", format!("Cannot watch files: {e}").red());
+            return;
+        }
+    };
+    let mut watched_dirs = BTreeSet::new();
+
+    let mut snapshot = SuiteSnapshot::load(Path::new(&options.file)).ok();
+    let mut runner = start_runner(&settings, &options);
+    clear_screen();
+    run(&mut runner, &options).await;
+
+    loop {
+        let files = snapshot
+            .as_ref()
+            .map(SuiteSnapshot::files)
+            .unwrap_or_else(|| std::path::absolute(&options.file).into_iter().collect());
+        watch_dirs(&mut watcher, &mut watched_dirs, &files);
+        println!("\nWatching {} files for changes, press Ctrl-C to stop...", files.len());
+
+        let changed = tokio::select! {
+            changed = next_change(&mut rx, &files) => changed,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        let Some(changed) = changed else { break };
+
+        let current = match SuiteSnapshot::load(Path::new(&options.file)) {
+            Ok(current) => current,
+            Err(e) => {
+                clear_screen();
+                eprintln!("{}", format!("Failed to parse tests: {e}").red());
+                continue;
+            }
+        };
+        let rerun = snapshot
+            .as_ref()
+            .map_or(Rerun::Everything, |previous| current.affected(previous, &changed));
+        clear_screen();
+        println!("Changed: {}", describe_changes(&changed));
+
+        match rerun {
+            Rerun::Everything => {
+                finish(&mut runner, &options).await;
+                runner = start_runner(&settings, &options);
+            }
+            Rerun::Tests(names) if names.is_empty() => {
+                println!("No tests are affected.");
+                snapshot = Some(current);
+                continue;
+            }
+            Rerun::Tests(names) => {
+                let names = if options.keep_variables {
+                    runner.results.clear();
+                    runner.run_setup = false;
+                    names
+                } else {
+                    runner = start_runner(&settings, &options);
+                    current.with_dependencies(&names)
+                };
+                let instances = instance_names(&options.file, &names);
+                runner.selection.names = if settings.selection.names.is_empty() {
+                    instances
+                } else {
+                    instances.into_iter().filter(|n| settings.selection.names.contains(n)).collect()
+                };
+                println!("Rerunning: {}", names.join(", "));
+            }
+        }
+        snapshot = Some(current);
+        run(&mut runner, &options).await;
+    }
+
+    finish(&mut runner, &options).await;
+}
+
+fn start_runner(settings: &TestRunner, options: &WatchOptions) -> TestRunner {
+    let mut runner = settings.fresh();
+    // Teardown waits for the end of the watch when setup variables are kept
+    runner.run_teardown = !options.keep_variables;
+    runner
+}
+
+async fn run(runner: &mut TestRunner, options: &WatchOptions) {
+    runner
+        .execute_tests(
+            options.filter.clone(),
+            options.verbose,
+            Some(options.file.clone()),
+            options.var.clone(),
+            options.no_fail_summary,
+        )
+        .await;
+}
+
+/// Run the teardown that was held back while variables were kept
+async fn finish(runner: &mut TestRunner, options: &WatchOptions) {
+    if !options.keep_variables {
+        return;
+    }
+    if let Ok(suite) = parse_tests(Some(&options.file)) {
+        let dir = Path::new(&options.file).parent().unwrap_or(Path::new("."));
+        let client = HttpClient::new(&suite.config).with_base_dir(dir);
+        runner.execute_teardown(&suite, &client, dir).await;
+    }
+}
+
+/// Instance names of the tests called `names`, as the runner selects them
+fn instance_names(file: &str, names: &[String]) -> Vec<String> {
+    let Ok(suite) = parse_tests(Some(file)) else {
+        return names.to_vec();
+    };
+    let dir = Path::new(file).parent().unwrap_or(Path::new("."));
+    suite
+        .tests
+        .iter()
+        .filter(|t| names.contains(&t.name))
+        .flat_map(|t| match expand_tests(std::slice::from_ref(t), dir) {
+            Ok(instances) => instances.into_iter().map(|i| i.name).collect(),
+            Err(_) => vec![t.name.clone()],
+        })
+        .collect()
+}
+
+/// Editors often replace files instead of writing them, so watch their directories
+fn watch_dirs(watcher: &mut RecommendedWatcher, watched: &mut BTreeSet<PathBuf>, files: &BTreeSet<PathBuf>) {
+    for dir in files.iter().filter_map(|f| f.parent()) {
+        if !watched.contains(dir) && watcher.watch(dir, RecursiveMode::NonRecursive).is_ok() {
+            watched.insert(dir.to_path_buf());
+        }
+    }
+}
+
+/// Wait for a change to one of `files`, then for the writes to settle
+async fn next_change(
+    rx: &mut mpsc::UnboundedReceiver<Vec<PathBuf>>,
+    files: &BTreeSet<PathBuf>,
+) -> Option<HashSet<PathBuf>> {
+    let mut changed = HashSet::new();
+    while changed.is_empty() {
+        changed.extend(rx.recv().await?.into_iter().filter(|p| files.contains(p)));
+    }
+    tokio::time::sleep(SETTLE_TIME).await;
+    while let Ok(paths) = rx.try_recv() {
+        changed.extend(paths.into_iter().filter(|p| files.contains(p)));
+    }
+    Some(changed)
+}
+
+fn describe_changes(changed: &HashSet<PathBuf>) -> String {
+    let mut names: Vec<String> = changed
+        .iter()
+        .map(|p| p.file_name().map_or_else(|| p.display().to_string(), |n| n.to_string_lossy().to_string()))
+        .collect();
+    names.sort();
+    names.join(", ")
+}
+
+fn clear_screen() {
+    if std::io::stdout().is_terminal() {
+        print!("\x1B[2J\x1B[1;1H");
+    }
+}
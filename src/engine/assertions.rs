@@ -69,6 +69,10 @@ pub fn validate_assertion(assertion: &crate::models::test::JsonAssertion, actual
                 false
             }
         }
+        crate::models::test::JsonAssertion::PathContains(path, expected) => {
+            crate::engine::variables::resolve_json_value(actual, path)
+                .is_some_and(|value| contains_json_value(expected, value))
+        }
     }
 }
 
@@ -1,4 +1,6 @@
 use crate::models::command::CommandStep;
+use super::expression::LastResponse;
+use serde_json::Value;
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -45,7 +47,7 @@ impl std::error::Error for CommandError {}
 /// May panic if process spawning fails in unexpected ways
 pub async fn execute_command_step(
     step: &CommandStep,
-    variables: &mut HashMap<String, String>,
+    variables: &mut HashMap<String, Value>,
     work_dir: &Path,
     allowed_commands: Option<&[String]>,
 ) -> Result<CommandResult, CommandError> {
@@ -55,20 +57,10 @@ pub async fn execute_command_step(
 
 #[must_use]
 pub fn evaluate_condition(condition: &str) -> bool {
-    // Simple string comparison evaluation
-    // Supports formats like: "{{var}}" == "value" or "{{var}}" != "value"
-    if let Some(eq_pos) = condition.find("==") {
-        let left = condition[..eq_pos].trim().trim_matches('"');
-        let right = condition[eq_pos + 2..].trim().trim_matches('"');
-        return left == right;
-    } else if let Some(ne_pos) = condition.find("!=") {
-        let left = condition[..ne_pos].trim().trim_matches('"');
-        let right = condition[ne_pos + 2..].trim().trim_matches('"');
-        return left != right;
-    }
-    
-    // Default: treat non-empty strings as true
-    !condition.trim().is_empty() && condition.trim() != "false"
+    // Without variables or a response; an invalid condition is false
+    let variables = HashMap::new();
+    let context = super::expression::ConditionContext { variables: &variables, response: None };
+    super::expression::evaluate(condition, &context).unwrap_or(false)
 }
 
 #[must_use]
@@ -76,21 +68,24 @@ pub fn extract_json_path(json: &serde_json::Value, path: &str) -> Option<String>
     crate::utils::json_path::extract_json_path(json, path)
 }
 
-/// Executes multiple command steps in sequence
+/// Executes multiple command steps in sequence; `when` conditions see `response` as the last response
 /// 
 /// # Errors
 /// Returns `CommandError` if any step fails and has `ignore_error = false`
 pub async fn execute_command_steps(
     steps: &[CommandStep],
-    variables: &mut HashMap<String, String>,
+    variables: &mut HashMap<String, Value>,
     work_dir: &Path,
     allowed_commands: Option<&[String]>,
     phase_name: &str,
+    response: Option<&LastResponse>,
 ) -> Result<(), CommandError> {
     for (i, step) in steps.iter().enumerate() {
         crate::debug!("Executing {} step {}: {:?}", phase_name, i + 1, step.run);
         
-        match execute_command_step(step, variables, work_dir, allowed_commands).await {
+        let executor = super::executor::CommandExecutor::new(step, variables, work_dir, allowed_commands)
+            .with_response(response);
+        match executor.execute().await {
             Ok(result) => {
                 if !result.success && !step.should_ignore_error() {
                     return Err(CommandError::ExecutionFailed(format!(
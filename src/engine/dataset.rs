@@ -0,0 +1,152 @@
+use crate::error::{CatalystError, CatalystResult};
+use crate::models::test::Test;
+use crate::utils::string::value_to_string;
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// The values of one dataset row, in column order
+pub type Parameters = Vec<(String, Value)>;
+
+/// Replace every test declaring `matrix` or `data_file` by one instance per row.
+///
+/// Instances are named `name [key=value, ...]` and carry their row in `parameters`.
+///
+/// # Errors
+/// Returns an error if a test declares both sources or its data file cannot be loaded
+pub fn expand_tests(tests: &[Test], test_file_dir: &Path) -> CatalystResult<Vec<Test>> {
+    let mut expanded = Vec::with_capacity(tests.len());
+    for test in tests {
+        match load_rows(test, test_file_dir)? {
+            None => expanded.push(test.clone()),
+            Some(rows) => {
+                for row in rows {
+                    let mut instance = test.clone();
+                    instance.name = instance_name(&test.name, &row);
+                    instance.parameters = row;
+                    expanded.push(instance);
+                }
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+/// The rows of a data-driven test, or `None` for a plain test
+///
+/// # Errors
+/// Returns an error if the test declares both `matrix` and `data_file`, or the file is unusable
+pub fn load_rows(test: &Test, test_file_dir: &Path) -> CatalystResult<Option<Vec<Parameters>>> {
+    match (&test.matrix, &test.data_file) {
+        (Some(_), Some(_)) => Err(CatalystError::config_error(format!(
+            "Test '{}' cannot have both `matrix` and `data_file`",
+            test.name
+        ))),
+        (Some(matrix), None) => Ok(Some(matrix_rows(matrix))),
+        (None, Some(file)) => load_data_file(file, test_file_dir).map(Some),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Every combination of the matrix values; the last key varies fastest
+#[must_use]
+pub fn matrix_rows(matrix: &IndexMap<String, Vec<Value>>) -> Vec<Parameters> {
+    matrix.iter().fold(vec![Vec::new()], |rows, (key, values)| {
+        rows.iter()
+            .flat_map(|row| {
+                values.iter().map(move |value| {
+                    let mut row = row.clone();
+                    row.push((key.clone(), value.clone()));
+                    row
+                })
+            })
+            .collect()
+    })
+}
+
+/// Load a `.csv` file with a header row, or a `.json` array of objects
+///
+/// # Errors
+/// Returns an error if the path escapes the test directory or the file cannot be parsed
+pub fn load_data_file(file_path: &str, test_file_dir: &Path) -> CatalystResult<Vec<Parameters>> {
+    if file_path.contains("..") || Path::new(file_path).is_absolute() {
+        return Err(CatalystError::file_error("File path cannot escape test directory"));
+    }
+
+    let full_path = test_file_dir.join(file_path);
+    if !full_path.is_file() {
+        return Err(CatalystError::file_error(format!("Data file '{file_path}' does not exist")));
+    }
+
+    let content = fs::read_to_string(&full_path)
+        .map_err(|e| CatalystError::file_error(format!("Cannot read data file '{file_path}': {e}")))?;
+
+    if file_path.ends_with(".csv") {
+        parse_csv(&content)
+            .map_err(|e| CatalystError::file_error(format!("Invalid CSV in data file '{file_path}': {e}")))
+    } else if file_path.ends_with(".json") {
+        let rows: Vec<IndexMap<String, Value>> = serde_json::from_str(&content).map_err(|e| {
+            CatalystError::json_error(format!(
+                "Data file '{file_path}' must be an array of objects: {e}"
+            ))
+        })?;
+        Ok(rows.into_iter().map(|row| row.into_iter().collect()).collect())
+    } else {
+        Err(CatalystError::file_error(format!(
+            "Data file '{file_path}' must be a .csv or .json file"
+        )))
+    }
+}
+
+/// CSV cells are bound as strings, like `--var` values
+fn parse_csv(content: &str) -> Result<Vec<Parameters>, csv::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let headers = reader.headers()?.clone();
+    reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            Ok(headers
+                .iter()
+                .zip(record.iter())
+                .map(|(key, cell)| (key.to_string(), Value::String(cell.to_string())))
+                .collect())
+        })
+        .collect()
+}
+
+/// `create user [role=admin, locale=fr]`
+#[must_use]
+pub fn instance_name(name: &str, row: &[(String, Value)]) -> String {
+    let values = row
+        .iter()
+        .map(|(key, value)| format!("{key}={}", value_to_string(value)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{name} [{values}]")
+}
+
+/// Bind the row of an instance as variables, returning what they shadowed
+pub fn bind_parameters(
+    parameters: &[(String, Value)],
+    vars: &mut HashMap<String, Value>,
+) -> Vec<(String, Option<Value>)> {
+    parameters
+        .iter()
+        .map(|(key, value)| (key.clone(), vars.insert(key.clone(), value.clone())))
+        .collect()
+}
+
+/// Undo `bind_parameters` once the instance has run
+pub fn restore_variables(shadowed: Vec<(String, Option<Value>)>, vars: &mut HashMap<String, Value>) {
+    for (key, previous) in shadowed {
+        match previous {
+            Some(value) => vars.insert(key, value),
+            None => vars.remove(&key),
+        };
+    }
+}
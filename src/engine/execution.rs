@@ -1,31 +1,46 @@
-use super::{variables, verify};
+use super::verify::Check;
+use super::{graphql, grpc, stream, variables, verify, websocket};
 use crate::debug;
-use crate::http::client::{HttpClient, RequestData};
+use crate::error::CatalystError;
+use crate::http::client::{DEFAULT_SESSION, HttpClient, HttpResponse, RequestData};
+use crate::http::timing::Timings;
+use crate::models::config::Config;
 use crate::models::test::Test;
-use crate::utils::{file::load_body_from_file, string::replace_variables};
+use crate::utils::{
+    file::load_body_from_file,
+    secrets,
+    string::{find_unresolved_placeholders, replace_variables},
+};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use std::time::Instant;
 
 pub struct ExecutionResult {
     pub success: bool,
     pub status: (u16, u16),
+    /// Total time of the exchange, from sending the request to the end of the body
     pub time_ms: u64,
+    /// Phase breakdown, when a response was received
+    pub timings: Option<Timings>,
     pub body: Option<Value>,
     pub headers: HashMap<String, String>,
     pub errors: Vec<String>,
+    /// The request hit the connect or total timeout
+    pub timed_out: bool,
+    /// The request as sent, default headers included
+    pub request: Option<RequestData>,
+    /// Every expectation checked against the response
+    pub checks: Vec<Check>,
 }
 
 pub async fn run(
     client: &HttpClient,
     test: &Test,
     test_file_dir: &Path,
-    vars: &mut HashMap<String, String>,
+    vars: &mut HashMap<String, Value>,
+    allow_undefined: bool,
 ) -> ExecutionResult {
-    let start = Instant::now();
-
-    let headers = test
+    let mut headers: Vec<(String, String)> = test
         .headers
         .as_ref()
         .map(|h| {
@@ -34,6 +49,11 @@ pub async fn run(
                 .collect()
         })
         .unwrap_or_default();
+    if !headers.iter().any(|(name, _)| name.eq_ignore_ascii_case("authorization"))
+        && let Some(value) = authorization(test, client.config(), vars)
+    {
+        headers.push(("Authorization".to_string(), value));
+    }
 
     let params = test
         .query_params
@@ -45,7 +65,12 @@ pub async fn run(
         })
         .unwrap_or_default();
 
-    let body = if let Some(inline_body) = &test.body {
+    let body = if let Some(request) = &test.graphql {
+        match graphql::envelope(request, test_file_dir, vars) {
+            Ok(envelope) => Some(envelope),
+            Err(e) => return failed(test, format!("GraphQL query: {e}"), None),
+        }
+    } else if let Some(inline_body) = &test.body {
         match variables::replace_variables_in_json_with_files(inline_body, vars, test_file_dir) {
             Ok(processed_body) => Some(processed_body),
             Err(e) => {
@@ -73,14 +98,64 @@ pub async fn run(
         body,
     };
 
+    for (name, value) in &request.headers {
+        secrets::register_header(name, value);
+    }
+    let sent = RequestData {
+        headers: client.effective_headers(&request.headers),
+        ..request.clone()
+    };
+
     debug!(
         "Request for '{}': headers = {:?}, body = {:?}",
         test.name, request.headers, request.body
     );
 
-    match client.execute(request).await {
-        Ok((status, body, mut headers)) => {
-            let time_ms = start.elapsed().as_millis() as u64;
+    if !allow_undefined {
+        // A gRPC request carries its message in the body and its metadata in the headers
+        let mut unresolved = unresolved_variables(&request);
+        unresolved.extend(unresolved_in_script(test, vars));
+        if !unresolved.is_empty() {
+            return failed(
+                test,
+                format!(
+                    "Unresolved variables: {} (use --allow-undefined to send anyway)",
+                    unresolved.join(", ")
+                ),
+                Some(sent),
+            );
+        }
+    }
+
+    if test.is_websocket() {
+        return websocket::run(client, test, sent, vars).await;
+    }
+
+    let session = test.session.as_deref().unwrap_or(DEFAULT_SESSION);
+
+    let mut streamed = None;
+    let response = match &test.stream {
+        _ if test.is_grpc() => grpc::call(client, test, request, test_file_dir).await,
+        None => client.send(request, session, &test.http).await,
+        Some(expectation) => match client.open(request, session, &test.http).await {
+            Ok(pending) => {
+                let (response, read) = stream::read(pending, expectation, vars).await;
+                streamed = Some(read);
+                Ok(response)
+            }
+            Err(e) => Err(e),
+        },
+    };
+
+    match response {
+        Ok(HttpResponse {
+            status,
+            body,
+            mut headers,
+            peer_certificate,
+            timings,
+        }) => {
+            let time_ms = timings.total_ms();
 
             let header_keys: Vec<String> = headers.keys().cloned().collect();
             for key in header_keys {
@@ -88,6 +163,9 @@ pub async fn run(
                     headers.insert(key.to_lowercase(), value.clone());
                 }
             }
+            for (name, value) in &headers {
+                secrets::register_header(name, value);
+            }
 
             debug!("Response headers: {:?}", headers);
 
@@ -104,7 +182,19 @@ pub async fn run(
                 debug!("Variables after cookie extraction: {:?}", vars);
             }
 
-            let validation = verify::check(test, status, &body, time_ms, vars);
+            let mut validation = verify::check(test, status, &body, &headers, &timings, vars);
+            if let Some(expected) = &test.expected_certificate {
+                let certificate_errors = verify::check_certificate(expected, peer_certificate.as_ref());
+                validation
+                    .checks
+                    .push(Check::new("Certificate", certificate_errors.is_empty()));
+                validation.errors.extend(certificate_errors);
+                validation.ok = validation.errors.is_empty();
+            }
+            if let (Some(expectation), Some(read)) = (&test.stream, &streamed) {
+                stream::verify(expectation, read, vars, &mut validation.checks, &mut validation.errors);
+                validation.ok = validation.errors.is_empty();
+            }
 
             if validation.ok && test.store.is_some() {
                 variables::store_variables(
@@ -121,18 +211,122 @@ pub async fn run(
                 success: validation.ok,
                 status: (test.expected_status, status),
                 time_ms,
+                timings: Some(timings),
                 body: Some(body),
                 headers,
                 errors: validation.errors,
+                timed_out: false,
+                request: Some(sent),
+                checks: validation.checks,
             }
         }
         Err(err) => ExecutionResult {
             success: false,
             status: (test.expected_status, 0),
             time_ms: 0,
+            timings: None,
             body: None,
             headers: HashMap::new(),
             errors: vec![err.to_string()],
+            timed_out: matches!(err, CatalystError::TimeoutError(_)),
+            request: Some(sent),
+            checks: Vec::new(),
         },
     }
 }
+
+/// A test that failed before a request could be sent
+pub(super) fn failed(test: &Test, error: String, request: Option<RequestData>) -> ExecutionResult {
+    ExecutionResult {
+        success: false,
+        status: (test.expected_status, 0),
+        time_ms: 0,
+        timings: None,
+        body: None,
+        headers: HashMap::new(),
+        errors: vec![error],
+        timed_out: false,
+        request,
+        checks: Vec::new(),
+    }
+}
+
+/// `Authorization` value from the test's `auth_method`/`auth_token`, falling back to `[config]`
+fn authorization(test: &Test, config: &Config, vars: &HashMap<String, Value>) -> Option<String> {
+    let method = test.auth_method.as_ref().or(config.auth_method.as_ref())?;
+    let token = test.auth_token.as_ref().or(config.auth_token.as_ref())?;
+    let token = replace_variables(token, vars);
+    Some(match method.to_lowercase().as_str() {
+        "bearer" => format!("Bearer {token}"),
+        "basic" => format!("Basic {token}"),
+        _ => token,
+    })
+}
+
+/// List the placeholders left in a request after substitution, with where they appeared
+fn unresolved_variables(request: &RequestData) -> Vec<String> {
+    let mut unresolved = Vec::new();
+
+    for placeholder in find_unresolved_placeholders(&request.url) {
+        unresolved.push(format!("{placeholder} in endpoint"));
+    }
+    for (name, value) in &request.headers {
+        for placeholder in find_unresolved_placeholders(value) {
+            unresolved.push(format!("{placeholder} in header '{name}'"));
+        }
+    }
+    for (name, value) in &request.params {
+        for placeholder in find_unresolved_placeholders(value) {
+            unresolved.push(format!("{placeholder} in query param '{name}'"));
+        }
+    }
+    if let Some(body) = &request.body {
+        collect_unresolved_in_json(body, "$", &mut unresolved);
+    }
+
+    unresolved
+}
+
+/// List the placeholders left in the WebSocket messages to send, except the
+/// variables that an earlier step stores from a received message
+fn unresolved_in_script(test: &Test, vars: &HashMap<String, Value>) -> Vec<String> {
+    let mut stored = HashSet::new();
+    let mut unresolved = Vec::new();
+    for (index, step) in test.script.iter().flatten().enumerate() {
+        if let Some(message) = &step.send {
+            let text = match variables::replace_variables_in_json(message, vars) {
+                Value::String(text) => text,
+                other => other.to_string(),
+            };
+            for placeholder in find_unresolved_placeholders(&text) {
+                let name = placeholder.strip_prefix("{{").and_then(|p| p.strip_suffix("}}"));
+                if !name.is_some_and(|name| stored.contains(name)) {
+                    unresolved.push(format!("{placeholder} in script step {}", index + 1));
+                }
+            }
+        }
+        stored.extend(step.store.iter().flatten().map(|(name, _)| name.as_str()));
+    }
+    unresolved
+}
+
+fn collect_unresolved_in_json(json: &Value, path: &str, unresolved: &mut Vec<String>) {
+    match json {
+        Value::String(s) => {
+            for placeholder in find_unresolved_placeholders(s) {
+                unresolved.push(format!("{placeholder} in body at {path}"));
+            }
+        }
+        Value::Object(map) => {
+            for (k, v) in map {
+                collect_unresolved_in_json(v, &format!("{path}.{k}"), unresolved);
+            }
+        }
+        Value::Array(arr) => {
+            for (i, v) in arr.iter().enumerate() {
+                collect_unresolved_in_json(v, &format!("{path}[{i}]"), unresolved);
+            }
+        }
+        _ => {}
+    }
+}
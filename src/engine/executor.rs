@@ -1,5 +1,6 @@
 use crate::models::command::CommandStep;
 use crate::utils::string::replace_variables;
+use serde_json::Value;
 use std::collections::HashMap;
 use std::path::Path;
 use std::process::Stdio;
@@ -9,19 +10,21 @@ use tokio::process::Command;
 use tokio::time::timeout;
 
 use super::commands::{CommandError, CommandResult};
+use super::expression::{ConditionContext, LastResponse};
 
 /// Handles the execution of command steps with proper separation of concerns
 pub struct CommandExecutor<'a> {
     step: &'a CommandStep,
-    variables: &'a mut HashMap<String, String>,
+    variables: &'a mut HashMap<String, Value>,
     work_dir: &'a Path,
     allowed_commands: Option<&'a [String]>,
+    response: Option<&'a LastResponse>,
 }
 
 impl<'a> CommandExecutor<'a> {
     pub fn new(
         step: &'a CommandStep,
-        variables: &'a mut HashMap<String, String>,
+        variables: &'a mut HashMap<String, Value>,
         work_dir: &'a Path,
         allowed_commands: Option<&'a [String]>,
     ) -> Self {
@@ -30,9 +33,17 @@ impl<'a> CommandExecutor<'a> {
             variables,
             work_dir,
             allowed_commands,
+            response: None,
         }
     }
 
+    /// Let `when` read `status` and `body` of this response
+    #[must_use]
+    pub fn with_response(mut self, response: Option<&'a LastResponse>) -> Self {
+        self.response = response;
+        self
+    }
+
     /// Execute the command step with all validation and processing
     pub async fn execute(mut self) -> Result<CommandResult, CommandError> {
         // Step 1: Check conditional execution
@@ -56,8 +67,9 @@ impl<'a> CommandExecutor<'a> {
     /// Check if the command should execute based on conditions
     fn should_execute(&self) -> Result<bool, CommandError> {
         if let Some(when_condition) = &self.step.when {
-            let condition = replace_variables(when_condition, self.variables);
-            Ok(super::commands::evaluate_condition(&condition))
+            let context = ConditionContext { variables: &*self.variables, response: self.response };
+            super::expression::evaluate(when_condition, &context)
+                .map_err(|e| CommandError::Validation(format!("Invalid `when` condition '{when_condition}': {e}")))
         } else {
             Ok(true)
         }
@@ -144,10 +156,7 @@ impl<'a> CommandExecutor<'a> {
             for (key, value) in env_vars {
                 let substituted_value = replace_variables(value, self.variables);
                 // Redact sensitive environment variables in debug output
-                if key.to_lowercase().contains("token") 
-                    || key.to_lowercase().contains("password") 
-                    || key.to_lowercase().contains("secret") 
-                    || key.to_lowercase().contains("key") {
+                if crate::utils::secrets::is_sensitive_key(key) {
                     crate::debug!("Setting env var {}: [REDACTED]", key);
                 } else {
                     crate::debug!("Setting env var {}: {}", key, substituted_value);
@@ -227,7 +236,7 @@ impl<'a> CommandExecutor<'a> {
 
         // Update variables with captured values
         for (key, value) in &captured_vars {
-            self.variables.insert(key.clone(), value.clone());
+            self.variables.insert(key.clone(), Value::String(value.clone()));
         }
 
         CommandResult {
@@ -0,0 +1,436 @@
+//! The condition language of `when`, `skip_if` and `on`.
+//!
+//! ```text
+//! condition  := and ("||" and)*
+//! and        := not ("&&" not)*
+//! not        := "!" not | comparison
+//! comparison := operand (("==" | "!=" | "<" | "<=" | ">" | ">=" | "contains" | "=~") operand)?
+//! operand    := "(" condition ")" | "defined(" name ")" | status | body | body.$.path
+//!             | "string" | 'string' | number | true | false | null | {{var}} | unquoted text
+//! ```
+//!
+//! Nothing is executed: conditions only read variables and the last response.
+
+use crate::engine::variables::resolve_json_value;
+use crate::utils::string::{replace_variables, sole_variable_name, value_to_string};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Status and body of the most recent HTTP response, as seen by `status` and `body`
+#[derive(Debug, Clone, Default)]
+pub struct LastResponse {
+    pub status: u16,
+    pub body: Option<Value>,
+}
+
+/// What a condition can read
+#[derive(Debug, Clone, Copy)]
+pub struct ConditionContext<'a> {
+    pub variables: &'a HashMap<String, Value>,
+    pub response: Option<&'a LastResponse>,
+}
+
+/// A parsed condition, ready to be evaluated any number of times
+#[derive(Debug, Clone)]
+pub struct Condition {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    Matches,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    /// Quoted or unquoted text, `{{var}}` placeholders substituted when evaluated
+    Text(String),
+    Variable(String),
+    Status,
+    Body(Option<String>),
+    Defined(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, Op, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Op(Op),
+    Quoted(String),
+    Word(String),
+}
+
+impl Condition {
+    /// # Errors
+    /// Returns a message pointing at the offending column if `source` is not a valid condition
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        if tokens.is_empty() {
+            return Err("Empty condition".to_string());
+        }
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some((token, column)) = parser.tokens.get(parser.pos) {
+            return Err(format!("Unexpected {} at column {column}", describe(token)));
+        }
+        Ok(Condition { expr })
+    }
+
+    /// # Errors
+    /// Returns an error if a regex built from a variable is invalid
+    pub fn evaluate(&self, context: &ConditionContext) -> Result<bool, String> {
+        Ok(truthy(&self.expr.eval(context)?))
+    }
+}
+
+/// Parse and evaluate `condition` in one go
+///
+/// # Errors
+/// Returns the parse or evaluation error
+pub fn evaluate(condition: &str, context: &ConditionContext) -> Result<bool, String> {
+    Condition::parse(condition)?.evaluate(context)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::LParen => "`(`".to_string(),
+        Token::RParen => "`)`".to_string(),
+        Token::And => "`&&`".to_string(),
+        Token::Or => "`||`".to_string(),
+        Token::Not => "`!`".to_string(),
+        Token::Op(op) => format!("`{}`", op_symbol(*op)),
+        Token::Quoted(s) => format!("\"{s}\""),
+        Token::Word(w) => format!("`{w}`"),
+    }
+}
+
+fn op_symbol(op: Op) -> &'static str {
+    match op {
+        Op::Eq => "==",
+        Op::Ne => "!=",
+        Op::Lt => "<",
+        Op::Le => "<=",
+        Op::Gt => ">",
+        Op::Ge => ">=",
+        Op::Contains => "contains",
+        Op::Matches => "=~",
+    }
+}
+
+/// Tokens paired with the 1-based column they start at
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Op(Op::Eq), 2),
+            ('=', Some('~')) => (Token::Op(Op::Matches), 2),
+            ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+            ('!', _) => (Token::Not, 1),
+            ('<', Some('=')) => (Token::Op(Op::Le), 2),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('&' | '|' | '=', _) => return Err(format!("Unexpected `{c}` at column {column}")),
+            ('"' | '\'', _) => {
+                let mut text = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(format!("Unterminated string starting at column {column}")),
+                        Some('\\') if chars.get(j + 1).is_some_and(|e| *e == c || *e == '\\') => {
+                            text.push(chars[j + 1]);
+                            j += 2;
+                        }
+                        Some(ch) if *ch == c => break,
+                        Some(ch) => {
+                            text.push(*ch);
+                            j += 1;
+                        }
+                    }
+                }
+                (Token::Quoted(text), j + 1 - i)
+            }
+            _ => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|ch| !ch.is_whitespace() && !"()&|=!<>\"'".contains(**ch))
+                    .count();
+                let word: String = chars[i..i + len].iter().collect();
+                let token = if word == "contains" { Token::Op(Op::Contains) } else { Token::Word(word) };
+                (token, len)
+            }
+        };
+        tokens.push((token, column));
+        i += len;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Error for a missing operand, at the current token or the end of input
+    fn expected_value(&self) -> String {
+        match self.tokens.get(self.pos) {
+            Some((token, column)) => format!("Expected a value but found {} at column {column}", describe(token)),
+            None => "Expected a value at the end of the condition".to_string(),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat(&Token::Or) {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.not()?;
+        while self.eat(&Token::And) {
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.operand()?;
+        let Some(Token::Op(op)) = self.peek().cloned() else {
+            return Ok(left);
+        };
+        self.pos += 1;
+        let right = self.operand()?;
+
+        // Catch bad patterns at parse time when they are written out in full
+        if op == Op::Matches
+            && let Expr::Text(pattern) = &right
+            && !pattern.contains("{{")
+            && let Err(e) = Regex::new(pattern)
+        {
+            return Err(format!("Invalid regex `{pattern}`: {e}"));
+        }
+        Ok(Expr::Compare(Box::new(left), op, Box::new(right)))
+    }
+
+    fn operand(&mut self) -> Result<Expr, String> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.expected_value());
+        };
+        match token {
+            Token::LParen => {
+                let column = self.tokens[self.pos].1;
+                self.pos += 1;
+                let inner = self.or()?;
+                if !self.eat(&Token::RParen) {
+                    return Err(format!("Missing `)` for the `(` at column {column}"));
+                }
+                Ok(inner)
+            }
+            Token::Quoted(text) => {
+                self.pos += 1;
+                Ok(Expr::Text(text))
+            }
+            Token::Word(word) if word == "defined" && self.tokens.get(self.pos + 1).map(|(t, _)| t) == Some(&Token::LParen) => {
+                self.pos += 2;
+                let name = match self.peek().cloned() {
+                    Some(Token::Word(name)) => sole_variable_name(&name).map_or(name.clone(), str::to_string),
+                    _ => return Err(format!("`defined` expects a variable name, {}", self.expected_value())),
+                };
+                self.pos += 1;
+                if !self.eat(&Token::RParen) {
+                    return Err(format!("Missing `)` after `defined({name}`"));
+                }
+                Ok(Expr::Defined(name))
+            }
+            Token::Word(word) => {
+                self.pos += 1;
+                let mut words = vec![word];
+                // Unquoted text may span several words, like `{{name}} == John Smith`
+                while let Some(Token::Word(next)) = self.peek().cloned() {
+                    self.pos += 1;
+                    words.push(next);
+                }
+                if words.len() > 1 {
+                    return Ok(Expr::Text(words.join(" ")));
+                }
+                Ok(word_expr(words.remove(0)))
+            }
+            _ => Err(self.expected_value()),
+        }
+    }
+}
+
+fn word_expr(word: String) -> Expr {
+    match word.as_str() {
+        "true" => Expr::Literal(Value::Bool(true)),
+        "false" => Expr::Literal(Value::Bool(false)),
+        "null" => Expr::Literal(Value::Null),
+        "status" => Expr::Status,
+        "body" => Expr::Body(None),
+        _ => {
+            if let Some(path) = word.strip_prefix("body.") {
+                return Expr::Body(Some(path.to_string()));
+            }
+            if let Some(name) = sole_variable_name(&word) {
+                return Expr::Variable(name.to_string());
+            }
+            match word.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+                Some(n) => Expr::Literal(Value::Number(n)),
+                None => Expr::Text(word),
+            }
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, context: &ConditionContext) -> Result<Value, String> {
+        Ok(match self {
+            Expr::Literal(value) => value.clone(),
+            Expr::Text(text) => Value::String(replace_variables(text, context.variables)),
+            Expr::Variable(name) => context.variables.get(name).cloned().unwrap_or(Value::Null),
+            Expr::Status => context.response.map_or(Value::Null, |r| Value::from(r.status)),
+            Expr::Body(path) => {
+                let body = context.response.and_then(|r| r.body.as_ref());
+                match (body, path) {
+                    (Some(body), None) => body.clone(),
+                    (Some(body), Some(path)) => resolve_json_value(body, path).cloned().unwrap_or(Value::Null),
+                    (None, _) => Value::Null,
+                }
+            }
+            Expr::Defined(name) => Value::Bool(context.variables.contains_key(name)),
+            Expr::Not(inner) => Value::Bool(!truthy(&inner.eval(context)?)),
+            Expr::And(left, right) => {
+                Value::Bool(truthy(&left.eval(context)?) && truthy(&right.eval(context)?))
+            }
+            Expr::Or(left, right) => {
+                Value::Bool(truthy(&left.eval(context)?) || truthy(&right.eval(context)?))
+            }
+            Expr::Compare(left, op, right) => {
+                Value::Bool(compare(&left.eval(context)?, *op, &right.eval(context)?)?)
+            }
+        })
+    }
+}
+
+fn compare(left: &Value, op: Op, right: &Value) -> Result<bool, String> {
+    Ok(match op {
+        Op::Eq => loose_eq(left, right),
+        Op::Ne => !loose_eq(left, right),
+        Op::Lt | Op::Le | Op::Gt | Op::Ge => {
+            let ordering = match (as_number(left), as_number(right)) {
+                (Some(a), Some(b)) => a.partial_cmp(&b),
+                _ if left.is_null() || right.is_null() => None,
+                _ => Some(text(left).cmp(&text(right))),
+            };
+            ordering.is_some_and(|o| match op {
+                Op::Lt => o.is_lt(),
+                Op::Le => o.is_le(),
+                Op::Gt => o.is_gt(),
+                _ => o.is_ge(),
+            })
+        }
+        Op::Contains => match left {
+            Value::Null => false,
+            Value::Array(items) => items.iter().any(|item| loose_eq(item, right)),
+            Value::Object(map) => map.contains_key(&text(right)),
+            _ => text(left).contains(&text(right)),
+        },
+        Op::Matches => {
+            let pattern = text(right);
+            let regex = Regex::new(&pattern).map_err(|e| format!("Invalid regex `{pattern}`: {e}"))?;
+            !left.is_null() && regex.is_match(&text(left))
+        }
+    })
+}
+
+/// Numbers compare numerically even when one side is text, so `status == "200"` holds
+fn loose_eq(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Null, Value::Null) => true,
+        (Value::Null, _) | (_, Value::Null) => false,
+        _ => match (as_number(left), as_number(right)) {
+            (Some(a), Some(b)) => a == b,
+            _ => text(left) == text(right),
+        },
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        _ => value_to_string(value),
+    }
+}
+
+/// `false`, `null`, `0`, empty text, `"false"` and empty arrays or objects are false
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.trim().is_empty() && s.trim() != "false",
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
@@ -0,0 +1,48 @@
+//! GraphQL over HTTP: the POST envelope of a `graphql` test and the check of the `errors` it gets back.
+
+use super::variables::replace_variables_in_json;
+use crate::error::CatalystResult;
+use crate::models::test::GraphqlRequest;
+use crate::utils::file::load_body_from_file;
+use crate::utils::string::replace_variables;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The JSON body carrying the operation, with `{{variables}}` resolved
+///
+/// # Errors
+/// Returns an error if the query file cannot be loaded
+pub fn envelope(request: &GraphqlRequest, test_file_dir: &Path, vars: &HashMap<String, Value>) -> CatalystResult<Value> {
+    let query = match request.query_file() {
+        Some(file) => match load_body_from_file(file, test_file_dir, vars)? {
+            Value::String(query) => query,
+            other => other.to_string(),
+        },
+        None => replace_variables(&request.query, vars),
+    };
+
+    let mut body = Map::new();
+    body.insert("query".to_string(), Value::String(query));
+    if let Some(variables) = &request.variables {
+        body.insert("variables".to_string(), replace_variables_in_json(variables, vars));
+    }
+    if let Some(name) = &request.operation_name {
+        body.insert("operationName".to_string(), Value::String(replace_variables(name, vars)));
+    }
+    Ok(Value::Object(body))
+}
+
+/// The `message` of each entry of the response's `errors` array
+#[must_use]
+pub fn error_messages(body: &Value) -> Vec<String> {
+    body.get("errors")
+        .and_then(Value::as_array)
+        .map(|errors| {
+            errors
+                .iter()
+                .map(|e| e.get("message").and_then(Value::as_str).map_or_else(|| e.to_string(), str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
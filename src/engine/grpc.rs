@@ -0,0 +1,325 @@
+//! gRPC unary calls: the service described by `.proto` files or by server reflection,
+//! the request built from the test's JSON body and the reply turned back into JSON.
+
+use crate::error::{CatalystError, CatalystResult};
+use crate::http::client::{HttpClient, HttpResponse, RequestData};
+use crate::http::timing::Timings;
+use crate::models::config::HttpSettings;
+use crate::models::test::Test;
+use prost_reflect::prost::Message;
+use prost_reflect::prost_types::FileDescriptorProto;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, SerializeOptions};
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use hyper_util::rt::TokioIo;
+use rustls::pki_types::ServerName;
+use std::error::Error;
+use std::future::Future;
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::http::Uri;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Status, client::Grpc};
+use tonic_reflection::pb::v1::server_reflection_client::ServerReflectionClient;
+use tonic_reflection::pb::v1::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::v1::server_reflection_response::MessageResponse;
+use tonic_reflection::pb::v1::ServerReflectionRequest;
+use tower_service::Service;
+
+/// Call the test's unary method with `request.body` as the request message.
+///
+/// The response status is the gRPC code (0 for OK) and the body the reply as
+/// JSON, or `{ code, message }` when the call fails; the headers are the metadata.
+///
+/// # Errors
+/// Returns an error if the server cannot be reached, the service or method
+/// cannot be found, or the body does not fit the request message
+pub async fn call(
+    client: &HttpClient,
+    test: &Test,
+    request: RequestData,
+    test_file_dir: &Path,
+) -> CatalystResult<HttpResponse> {
+    let started = Instant::now();
+    let service_name = test
+        .service
+        .as_deref()
+        .ok_or_else(|| CatalystError::validation_error(format!("gRPC test `{}` has no `service`", test.name)))?;
+    let settings = client.config().http.merged_with(&test.http);
+    let channel = connect(client, &settings).await?;
+    let pool = match &test.proto {
+        Some(files) => load_proto_files(files, test.proto_path.as_deref().unwrap_or_default(), test_file_dir)?,
+        None => reflect(channel.clone(), service_name).await?,
+    };
+
+    let service = pool
+        .get_service_by_name(service_name)
+        .ok_or_else(|| CatalystError::validation_error(format!("Service `{service_name}` not found")))?;
+    let method = service
+        .methods()
+        .find(|m| m.name() == request.method)
+        .ok_or_else(|| {
+            CatalystError::validation_error(format!("Method `{}` not found in `{service_name}`", request.method))
+        })?;
+    if method.is_client_streaming() || method.is_server_streaming() {
+        return Err(CatalystError::validation_error(format!(
+            "`{service_name}/{}` is a streaming method; only unary methods are supported",
+            method.name()
+        )));
+    }
+
+    let message = match request.body.clone() {
+        Some(body) => DynamicMessage::deserialize(method.input(), body).map_err(|e| {
+            CatalystError::json_error(format!("Body does not fit `{}`: {e}", method.input().full_name()))
+        })?,
+        None => DynamicMessage::new(method.input()),
+    };
+    let mut call = tonic::Request::new(message);
+    for (name, value) in client.effective_headers(&request.headers) {
+        let key = MetadataKey::from_bytes(name.to_lowercase().as_bytes());
+        let value = MetadataValue::try_from(value.as_str());
+        let (Ok(key), Ok(value)) = (key, value) else {
+            return Err(CatalystError::validation_error(format!("Invalid metadata '{name}'")));
+        };
+        call.metadata_mut().insert(key, value);
+    }
+
+    let path = PathAndQuery::try_from(format!("/{}/{}", service.full_name(), method.name()))
+        .map_err(|e| CatalystError::validation_error(e.to_string()))?;
+    let mut grpc = Grpc::new(channel);
+    grpc.ready()
+        .await
+        .map_err(|e| CatalystError::http_error(format!("gRPC channel not ready: {}", describe(&e))))?;
+    let (status, body, metadata) = match grpc.unary(call, path, DynamicCodec(method.output())).await {
+        Ok(response) => {
+            let (metadata, message, _) = response.into_parts();
+            (0, to_json(&message)?, metadata)
+        }
+        Err(status) => (
+            status.code() as u16,
+            json!({ "code": status.code() as i32, "message": status.message() }),
+            status.metadata().clone(),
+        ),
+    };
+
+    Ok(HttpResponse {
+        status,
+        body,
+        headers: metadata_headers(&metadata),
+        peer_certificate: None,
+        timings: Timings {
+            total: started.elapsed(),
+            ..Timings::default()
+        },
+    })
+}
+
+async fn connect(client: &HttpClient, settings: &HttpSettings) -> CatalystResult<Channel> {
+    let base_url = &client.config().base_url;
+    let invalid = |e: &dyn Error| CatalystError::config_error(format!("Invalid base_url {base_url}: {}", describe(e)));
+    let origin = Uri::try_from(base_url.as_str()).map_err(|e| invalid(&e))?;
+    let tls = origin.scheme_str() == Some("https");
+    // tonic's own TLS cannot take a rustls configuration, so the endpoint dials the
+    // plain address and the connector runs the handshake with the suite's settings
+    let address = if tls { base_url.replacen("https://", "http://", 1) } else { base_url.clone() };
+    let mut endpoint = Endpoint::from_shared(address).map_err(|e| invalid(&e))?.origin(origin);
+    if let Some(ms) = settings.connect_timeout_ms {
+        endpoint = endpoint.connect_timeout(Duration::from_millis(ms));
+    }
+    if let Some(ms) = settings.timeout_ms {
+        endpoint = endpoint.timeout(Duration::from_millis(ms));
+    }
+    let connected = if tls {
+        let mut config = client.tls_config()?;
+        config.alpn_protocols = vec![b"h2".to_vec()];
+        endpoint.connect_with_connector(TlsConnector(Arc::new(config).into())).await
+    } else {
+        endpoint.connect().await
+    };
+    connected.map_err(|e| CatalystError::http_error(format!("Cannot connect to {base_url}: {}", describe(&e))))
+}
+
+/// Opens the TCP connection to the endpoint and runs the TLS handshake on it
+#[derive(Clone)]
+struct TlsConnector(tokio_rustls::TlsConnector);
+
+impl Service<Uri> for TlsConnector {
+    type Response = TokioIo<TlsStream<TcpStream>>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<Self::Response>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let connector = self.0.clone();
+        Box::pin(async move {
+            let host = uri.host().unwrap_or_default().trim_start_matches('[').trim_end_matches(']').to_string();
+            let stream = TcpStream::connect((host.as_str(), uri.port_u16().unwrap_or(443))).await?;
+            let name = ServerName::try_from(host).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            Ok(TokioIo::new(connector.connect(name, stream).await?))
+        })
+    }
+}
+
+/// Parse `.proto` files relative to the test file; imports resolve from `proto_path`, then the test file's directory
+fn load_proto_files(files: &[String], proto_path: &[String], test_file_dir: &Path) -> CatalystResult<DescriptorPool> {
+    let dir = if test_file_dir.as_os_str().is_empty() { Path::new(".") } else { test_file_dir };
+    let parsed = protobuf_parse::Parser::new()
+        .pure()
+        .includes(proto_path.iter().map(|path| dir.join(path)))
+        .include(dir)
+        .inputs(files.iter().map(|file| dir.join(file)))
+        .parse_and_typecheck()
+        .map_err(|e| CatalystError::file_error(format!("Cannot load {}: {e:#}", files.join(", "))))?;
+    let descriptors = parsed
+        .file_descriptors
+        .iter()
+        .map(|file| {
+            let bytes = protobuf::Message::write_to_bytes(file).map_err(|e| e.to_string())?;
+            FileDescriptorProto::decode(bytes.as_slice()).map_err(|e| e.to_string())
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(CatalystError::file_error)?;
+    let mut pool = DescriptorPool::new();
+    pool.add_file_descriptor_protos(descriptors)
+        .map_err(|e| CatalystError::file_error(format!("Invalid descriptors in {}: {e}", files.join(", "))))?;
+    Ok(pool)
+}
+
+/// Fetch the file defining `symbol`, and the files it imports, by server reflection
+async fn reflect(channel: Channel, symbol: &str) -> CatalystResult<DescriptorPool> {
+    let reflection_error = |e: String| CatalystError::http_error(format!("Server reflection failed: {e}"));
+    let mut client = ServerReflectionClient::new(channel);
+    let mut files: HashMap<String, FileDescriptorProto> = HashMap::new();
+    let mut requested = HashSet::new();
+    let mut requests = vec![MessageRequest::FileContainingSymbol(symbol.to_string())];
+
+    while !requests.is_empty() {
+        let batch: Vec<_> = requests
+            .drain(..)
+            .map(|request| ServerReflectionRequest {
+                host: String::new(),
+                message_request: Some(request),
+            })
+            .collect();
+        let mut responses = client
+            .server_reflection_info(futures_util::stream::iter(batch))
+            .await
+            .map_err(|status| reflection_error(status.message().to_string()))?
+            .into_inner();
+        while let Some(response) = responses
+            .message()
+            .await
+            .map_err(|status| reflection_error(status.message().to_string()))?
+        {
+            match response.message_response {
+                Some(MessageResponse::FileDescriptorResponse(found)) => {
+                    for bytes in found.file_descriptor_proto {
+                        let file = FileDescriptorProto::decode(bytes.as_slice())
+                            .map_err(|e| reflection_error(e.to_string()))?;
+                        files.entry(file.name().to_string()).or_insert(file);
+                    }
+                }
+                Some(MessageResponse::ErrorResponse(error)) => return Err(reflection_error(error.error_message)),
+                _ => {}
+            }
+        }
+
+        // Servers usually send the imports along; ask for any that are missing
+        let missing: Vec<String> = files
+            .values()
+            .flat_map(|file| file.dependency.iter())
+            .filter(|name| !files.contains_key(*name) && requested.insert((*name).clone()))
+            .cloned()
+            .collect();
+        requests.extend(missing.into_iter().map(MessageRequest::FileByFilename));
+    }
+
+    let mut pool = DescriptorPool::new();
+    pool.add_file_descriptor_protos(files.into_values())
+        .map_err(|e| reflection_error(e.to_string()))?;
+    Ok(pool)
+}
+
+/// The reply in the proto3 JSON mapping, with field names as in the `.proto`
+/// and default values included so they can be asserted on
+fn to_json(message: &DynamicMessage) -> CatalystResult<Value> {
+    let options = SerializeOptions::new()
+        .skip_default_fields(false)
+        .use_proto_field_name(true)
+        .stringify_64_bit_integers(false);
+    message
+        .serialize_with_options(serde_json::value::Serializer, &options)
+        .map_err(|e| CatalystError::json_error(e.to_string()))
+}
+
+fn metadata_headers(metadata: &MetadataMap) -> HashMap<String, String> {
+    metadata
+        .clone()
+        .into_headers()
+        .iter()
+        .filter_map(|(k, v)| Some((k.as_str().to_string(), v.to_str().ok()?.to_string())))
+        .collect()
+}
+
+/// An error with its causes, which tonic keeps out of `Display`
+fn describe(error: &dyn Error) -> String {
+    let mut text = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        text.push_str(": ");
+        text.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    text
+}
+
+/// Encodes and decodes messages known only by their descriptor
+#[derive(Clone)]
+struct DynamicCodec(MessageDescriptor);
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicCodec;
+    type Decoder = DynamicCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        self.clone()
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        self.clone()
+    }
+}
+
+impl Encoder for DynamicCodec {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(&mut self, item: DynamicMessage, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        item.encode(dst).map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+impl Decoder for DynamicCodec {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<DynamicMessage>, Status> {
+        DynamicMessage::decode(self.0.clone(), src)
+            .map(Some)
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+}
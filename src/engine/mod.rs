@@ -1,7 +1,12 @@
 pub mod assertions;
 pub mod commands;
+pub mod dataset;
 pub mod execution;
 pub mod executor;
-pub mod prep;
+pub mod expression;
+pub mod graphql;
+pub mod grpc;
+pub mod stream;
 pub mod variables;
 pub mod verify;
+pub mod websocket;
@@ -0,0 +1,263 @@
+//! Streaming response bodies: Server-Sent Events, NDJSON and raw chunks, read
+//! item by item until the stream ends, the `until` condition holds or the window closes.
+
+use super::assertions::body_matches;
+use super::expression::{Condition, ConditionContext, LastResponse};
+use super::variables::replace_variables_in_json;
+use super::verify::Check;
+use crate::http::client::{HttpResponse, PendingResponse};
+use crate::models::test::StreamExpectation;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `text/event-stream`: one item `{ event, data, id }` per event
+    Sse,
+    /// One JSON value per line
+    Ndjson,
+    /// One text item per chunk as received
+    Chunks,
+}
+
+impl Format {
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "sse" => Some(Format::Sse),
+            "ndjson" | "jsonl" => Some(Format::Ndjson),
+            "chunks" => Some(Format::Chunks),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn from_content_type(content_type: &str) -> Self {
+        let media_type = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+        match media_type.as_str() {
+            "text/event-stream" => Format::Sse,
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" | "application/x-jsonlines" => {
+                Format::Ndjson
+            }
+            _ => Format::Chunks,
+        }
+    }
+}
+
+/// Splits the bytes of a stream into items as they arrive
+#[derive(Debug)]
+pub struct Decoder {
+    format: Format,
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+}
+
+impl Decoder {
+    #[must_use]
+    pub fn new(format: Format) -> Self {
+        Decoder {
+            format,
+            buffer: Vec::new(),
+            event: None,
+            data: Vec::new(),
+            id: None,
+        }
+    }
+
+    /// The items completed by `bytes`
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Value> {
+        if self.format == Format::Chunks {
+            return vec![Value::String(String::from_utf8_lossy(bytes).to_string())];
+        }
+        self.buffer.extend_from_slice(bytes);
+        let mut items = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            items.extend(self.line(line.trim_end_matches(['\n', '\r'])));
+        }
+        items
+    }
+
+    /// The last item, when the stream ends without a final line break
+    pub fn finish(&mut self) -> Vec<Value> {
+        let rest = String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).to_string();
+        let mut items: Vec<Value> = self.line(rest.trim_end_matches('\r')).into_iter().collect();
+        items.extend(self.line(""));
+        items
+    }
+
+    fn line(&mut self, line: &str) -> Option<Value> {
+        match self.format {
+            Format::Chunks => None,
+            Format::Ndjson => (!line.trim().is_empty()).then(|| parse(line.trim())),
+            Format::Sse if line.is_empty() => self.dispatch(),
+            Format::Sse => {
+                if line.starts_with(':') {
+                    return None;
+                }
+                let (field, value) = line.split_once(':').unwrap_or((line, ""));
+                let value = value.strip_prefix(' ').unwrap_or(value).to_string();
+                match field {
+                    "event" => self.event = Some(value),
+                    "data" => self.data.push(value),
+                    "id" => self.id = Some(value),
+                    _ => {}
+                }
+                None
+            }
+        }
+    }
+
+    /// An event ends at a blank line; one without `data` is dropped, as browsers do
+    fn dispatch(&mut self) -> Option<Value> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        let mut item = Map::new();
+        item.insert("event".into(), Value::String(event.unwrap_or_else(|| "message".into())));
+        item.insert("data".into(), parse(&std::mem::take(&mut self.data).join("\n")));
+        if let Some(id) = &self.id {
+            item.insert("id".into(), Value::String(id.clone()));
+        }
+        Some(Value::Object(item))
+    }
+}
+
+fn parse(text: &str) -> Value {
+    serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+}
+
+/// How reading a stream ended
+#[derive(Debug, Default)]
+pub struct StreamRead {
+    pub items: Vec<Value>,
+    /// The `until` condition held for the last item
+    pub until_met: bool,
+    /// `window_ms` elapsed before the stream ended
+    pub window_closed: bool,
+    pub error: Option<String>,
+}
+
+/// Read `pending` item by item, the response body becoming the array of items read
+pub async fn read(
+    mut pending: PendingResponse,
+    expectation: &StreamExpectation,
+    vars: &HashMap<String, Value>,
+) -> (HttpResponse, StreamRead) {
+    let mut read = StreamRead::default();
+    let format = match &expectation.format {
+        Some(name) => Format::parse(name),
+        None => Some(Format::from_content_type(pending.headers.get("content-type").map_or("", String::as_str))),
+    };
+    let until = expectation.until.as_deref().map(Condition::parse).transpose();
+    let (Some(format), Ok(until)) = (format, until) else {
+        read.error = Some("Invalid `stream` settings; run `catalyst validate`".into());
+        return (pending.close(Value::Array(Vec::new())), read);
+    };
+
+    let mut decoder = Decoder::new(format);
+    let deadline = expectation.window_ms.map(|ms| Instant::now() + Duration::from_millis(ms));
+    'reading: loop {
+        let next = match deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline, pending.chunk()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    read.window_closed = true;
+                    break;
+                }
+            },
+            None => pending.chunk().await,
+        };
+        let (items, ended) = match next {
+            Ok(Some(bytes)) => (decoder.feed(&bytes), false),
+            Ok(None) => (decoder.finish(), true),
+            Err(e) => {
+                read.error = Some(format!("Stream read failed: {e}"));
+                break;
+            }
+        };
+        for item in items {
+            read.items.push(item);
+            if let Some(until) = &until {
+                let last = LastResponse {
+                    status: pending.status,
+                    body: read.items.last().cloned(),
+                };
+                let context = ConditionContext { variables: vars, response: Some(&last) };
+                match until.evaluate(&context) {
+                    Ok(true) => {
+                        read.until_met = true;
+                        break 'reading;
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        read.error = Some(format!("`until` condition: {e}"));
+                        break 'reading;
+                    }
+                }
+            }
+        }
+        if ended {
+            break;
+        }
+    }
+
+    (pending.close(Value::Array(read.items.clone())), read)
+}
+
+/// Check what was read against the expectation
+pub fn verify(
+    expectation: &StreamExpectation,
+    read: &StreamRead,
+    vars: &HashMap<String, Value>,
+    checks: &mut Vec<Check>,
+    errors: &mut Vec<String>,
+) {
+    let count = read.items.len();
+    if let Some(error) = &read.error {
+        checks.push(Check::new("Stream read", false));
+        errors.push(error.clone());
+    }
+    if let Some(until) = &expectation.until {
+        checks.push(Check::new(format!("Stream until {until}"), read.until_met));
+        if !read.until_met && read.error.is_none() {
+            errors.push(match (read.window_closed, expectation.window_ms) {
+                (true, Some(window)) => format!("`until` did not hold within {window}ms ({count} events)"),
+                _ => format!("Stream ended before `until` held ({count} events)"),
+            });
+        }
+    }
+    if let Some(min) = expectation.min_events {
+        checks.push(Check::new(format!("At least {min} events"), count >= min));
+        if count < min {
+            errors.push(format!("Received {count} events, expected at least {min}"));
+        }
+    }
+    if let Some(max) = expectation.max_events {
+        checks.push(Check::new(format!("At most {max} events"), count <= max));
+        if count > max {
+            errors.push(format!("Received {count} events, expected at most {max}"));
+        }
+    }
+    if let Some(events) = &expectation.events {
+        let mut remaining = read.items.iter();
+        let missing = events.iter().position(|expected| {
+            let expected = replace_variables_in_json(expected, vars);
+            !remaining.any(|item| body_matches(&expected, item))
+        });
+        checks.push(Check::new(format!("{} events in order", events.len()), missing.is_none()));
+        if let Some(index) = missing {
+            errors.push(format!(
+                "Event {} {} not received in order ({count} events read)",
+                index + 1,
+                events[index]
+            ));
+        }
+    }
+}
@@ -1,10 +1,17 @@
+use crate::checker::references::collect_references;
 use crate::debug;
-use crate::utils::string::{replace_variables, replace_variables_with_files};
+use crate::http::cookies::parse_set_cookies;
+use crate::models::suite::TestSuite;
+use crate::utils::string::{replace_variables, replace_variables_with_files, sole_variable_name};
 use dotenv::dotenv;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::path::Path;
 
+/// Dotenv files in the order they are looked up; the first one found wins
+const DOTENV_FILES: &[&str] = &[".env.local", ".env.dev", ".env"];
+
 pub fn load_env_files() {
     if dotenv::from_filename(".env.local").is_ok() {
         debug!("Loaded .env.local");
@@ -17,9 +24,147 @@ pub fn load_env_files() {
     }
 }
 
-pub fn replace_variables_in_json(json: &Value, vars: &HashMap<String, String>) -> Value {
+/// Look up a string that is exactly one `{{name}}` placeholder, so the typed
+/// value can replace the whole JSON string instead of being interpolated.
+fn typed_placeholder<'a>(s: &str, vars: &'a HashMap<String, Value>) -> Option<&'a Value> {
+    sole_variable_name(s).and_then(|name| vars.get(name))
+}
+
+/// Where a startup variable came from, from highest to lowest precedence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VariableSource {
+    Cli,
+    Profile(String),
+    EnvStore,
+    Dotenv(String),
+    ProcessEnv,
+}
+
+impl fmt::Display for VariableSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VariableSource::Cli => write!(f, "--var"),
+            VariableSource::Profile(name) => write!(f, "profile '{name}'"),
+            VariableSource::EnvStore => write!(f, "env.store"),
+            VariableSource::Dotenv(file) => write!(f, "dotenv ({file})"),
+            VariableSource::ProcessEnv => write!(f, "process env"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedVariable {
+    pub name: String,
+    pub value: Value,
+    pub source: VariableSource,
+}
+
+/// Resolve the variables available before the first test runs.
+///
+/// Precedence, highest first: CLI `--var` > profile > `[config.env].store` > dotenv > process env.
+/// Dotenv and process env only supply names the suite references as `{{name}}`.
+/// Profile and `env.store` values may themselves use `{{name}}` and `${{NAME}}`,
+/// which are resolved against the lower layers.
+///
+/// # Errors
+/// Returns an error if `profile` is not declared in `[config.profiles]`
+pub fn resolve_variables(
+    suite: &TestSuite,
+    profile: Option<&str>,
+    cli_variables: &HashMap<String, String>,
+) -> Result<Vec<ResolvedVariable>, String> {
+    let referenced: HashSet<String> = collect_references(suite)
+        .into_iter()
+        .map(|r| r.name)
+        .collect();
+    let mut resolved: HashMap<String, ResolvedVariable> = HashMap::new();
+
+    for name in &referenced {
+        if let Ok(value) = std::env::var(name) {
+            insert_resolved(&mut resolved, name, Value::String(value), VariableSource::ProcessEnv);
+        }
+    }
+
+    if let Some((file, pairs)) = read_dotenv_variables() {
+        for (name, value) in pairs {
+            if referenced.contains(&name) {
+                let source = VariableSource::Dotenv(file.clone());
+                insert_resolved(&mut resolved, &name, Value::String(value), source);
+            }
+        }
+    }
+
+    if let Some(store) = suite.config.env.as_ref().and_then(|e| e.store.as_ref()) {
+        insert_layer(&mut resolved, store, &VariableSource::EnvStore);
+    }
+
+    if let Some(profile) = profile {
+        let values = suite
+            .config
+            .profiles
+            .as_ref()
+            .and_then(|p| p.get(profile))
+            .ok_or_else(|| format!("Unknown profile '{profile}'"))?;
+        insert_layer(&mut resolved, values, &VariableSource::Profile(profile.to_string()));
+    }
+
+    for (name, value) in cli_variables {
+        insert_resolved(&mut resolved, name, Value::String(value.clone()), VariableSource::Cli);
+    }
+
+    let mut variables: Vec<_> = resolved.into_values().collect();
+    variables.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(variables)
+}
+
+fn insert_resolved(
+    resolved: &mut HashMap<String, ResolvedVariable>,
+    name: &str,
+    value: Value,
+    source: VariableSource,
+) {
+    resolved.insert(
+        name.to_string(),
+        ResolvedVariable {
+            name: name.to_string(),
+            value,
+            source,
+        },
+    );
+}
+
+/// Add a TOML-declared layer whose values are templates over the layers below it
+fn insert_layer(
+    resolved: &mut HashMap<String, ResolvedVariable>,
+    values: &HashMap<String, String>,
+    source: &VariableSource,
+) {
+    let lower: HashMap<String, Value> = resolved
+        .iter()
+        .map(|(k, v)| (k.clone(), v.value.clone()))
+        .collect();
+    for (name, value) in values {
+        let value = Value::String(replace_variables(value, &lower));
+        insert_resolved(resolved, name, value, source.clone());
+    }
+}
+
+/// Read the key/value pairs of the dotenv file `load_env_files` would load
+// `from_filename_iter` is the only dotenv 0.15 API that parses without touching the process env
+#[allow(deprecated)]
+fn read_dotenv_variables() -> Option<(String, Vec<(String, String)>)> {
+    DOTENV_FILES.iter().find_map(|file| {
+        let iter = dotenv::from_filename_iter(file).ok()?;
+        let pairs = iter.filter_map(Result::ok).collect();
+        Some(((*file).to_string(), pairs))
+    })
+}
+
+pub fn replace_variables_in_json(json: &Value, vars: &HashMap<String, Value>) -> Value {
     match json {
-        Value::String(s) => Value::String(replace_variables(s, vars)),
+        Value::String(s) => typed_placeholder(s, vars)
+            .cloned()
+            .unwrap_or_else(|| Value::String(replace_variables(s, vars))),
         Value::Object(map) => Value::Object(
             map.iter()
                 .map(|(k, v)| (k.clone(), replace_variables_in_json(v, vars)))
@@ -36,11 +181,14 @@ pub fn replace_variables_in_json(json: &Value, vars: &HashMap<String, String>) -
 
 pub fn replace_variables_in_json_with_files(
     json: &Value,
-    vars: &HashMap<String, String>,
+    vars: &HashMap<String, Value>,
     test_file_dir: &Path,
 ) -> Result<Value, String> {
     match json {
         Value::String(s) => {
+            if let Some(value) = typed_placeholder(s, vars) {
+                return Ok(value.clone());
+            }
             let result = replace_variables_with_files(s, vars, test_file_dir)?;
             Ok(Value::String(result))
         }
@@ -65,7 +213,27 @@ pub fn replace_variables_in_json_with_files(
 }
 
 pub fn get_json_value(json: &Value, path: &str) -> Option<String> {
-    let parts = path.strip_prefix("$.")?;
+    let current = resolve_json_value(json, path)?;
+
+    let result = match current {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => current.to_string().trim_matches('"').to_string(),
+    };
+
+    debug!("Extracted final value: {}", result);
+    Some(result)
+}
+
+/// Resolve a `$.path` expression to the JSON node it points at; `$[0].path` indexes a root array
+pub fn resolve_json_value<'a>(json: &'a Value, path: &str) -> Option<&'a Value> {
+    if path == "$" {
+        return Some(json);
+    }
+    let parts = path
+        .strip_prefix("$.")
+        .or_else(|| path.strip_prefix('$').filter(|rest| rest.starts_with('[')))?;
     let mut current = json;
 
     debug!("Extracting value from path: {}", path);
@@ -105,15 +273,55 @@ pub fn get_json_value(json: &Value, path: &str) -> Option<String> {
         debug!("Current value after access: {:?}", current);
     }
 
-    let result = match current {
-        Value::String(s) => s.clone(),
-        Value::Number(n) => n.to_string(),
-        Value::Bool(b) => b.to_string(),
-        _ => current.to_string().trim_matches('"').to_string(),
+    Some(current)
+}
+
+/// Every node a `$.path` expression selects: array elements by index, object values
+/// in key order. `[*]` (or `.*`) steps into each element of an array or each value
+/// of an object; the other parts are read as in `resolve_json_value`, and nodes
+/// lacking them are left out.
+#[must_use]
+pub fn select_json_values<'a>(json: &'a Value, path: &str) -> Vec<&'a Value> {
+    if path == "$" {
+        return vec![json];
+    }
+    let Some(parts) = path
+        .strip_prefix("$.")
+        .or_else(|| path.strip_prefix('$').filter(|rest| rest.starts_with('[')))
+    else {
+        return Vec::new();
     };
 
-    debug!("Extracted final value: {}", result);
-    Some(result)
+    let mut selected = vec![json];
+    for part in parts.split('.') {
+        let (key, indexes) = part.split_once('[').map_or((part, ""), |(key, rest)| (key, rest));
+        let mut steps: Vec<&str> = if key.is_empty() { Vec::new() } else { vec![key] };
+        steps.extend(indexes.split('[').filter(|i| !i.is_empty()).map(|i| i.trim_end_matches(']')));
+
+        for step in steps {
+            selected = selected
+                .into_iter()
+                .flat_map(|node| -> Vec<&Value> {
+                    match (step, node) {
+                        ("*", Value::Array(items)) => items.iter().collect(),
+                        ("*", Value::Object(map)) => map.values().collect(),
+                        _ => match step.parse::<usize>() {
+                            Ok(index) if node.is_array() => node.get(index).into_iter().collect(),
+                            _ => node.get(step).into_iter().collect(),
+                        },
+                    }
+                })
+                .collect();
+        }
+    }
+    selected
+}
+
+/// The variable a path over the variables starts from: `items` for `$.items[*].id`
+#[must_use]
+pub fn path_root_key(path: &str) -> Option<&str> {
+    let key = path.strip_prefix("$.")?.split(['.', '[']).next()?;
+    (!key.is_empty() && key != "*").then_some(key)
 }
 
 pub fn store_variables(
@@ -122,7 +330,7 @@ pub fn store_variables(
     headers: &HashMap<String, String>,
     cookie_map: &Option<HashMap<String, String>>,
     response_time_ms: u64,
-    vars: &mut HashMap<String, String>,
+    vars: &mut HashMap<String, Value>,
 ) {
     debug!("Starting variable storage");
     debug!("Current variables: {:?}", vars);
@@ -130,14 +338,17 @@ pub fn store_variables(
     debug!("Body received: {:?}", body);
     debug!("Store map: {:?}", store_map);
 
-    vars.insert("response_time_ms".to_string(), response_time_ms.to_string());
+    vars.insert("response_time_ms".to_string(), Value::from(response_time_ms));
 
     for (var_name, path) in store_map {
         debug!("Attempting to extract '{}' from path '{}'", var_name, path);
-        if let Some(value) = get_json_value(body, path) {
-            let clean_value = value.trim_matches('"').to_string();
-            debug!("Successfully stored {} = {}", var_name, clean_value);
-            vars.insert(var_name.clone(), clean_value);
+        if path.contains('*') {
+            // A wildcard stores the array of what it selects, empty when nothing matched
+            let values = select_json_values(body, path).into_iter().cloned().collect();
+            vars.insert(var_name.clone(), Value::Array(values));
+        } else if let Some(value) = resolve_json_value(body, path) {
+            debug!("Successfully stored {} = {}", var_name, value);
+            vars.insert(var_name.clone(), value.clone());
         } else {
             debug!(
                 "⚠️ Failed to extract value at path '{}' for variable '{}'",
@@ -151,7 +362,10 @@ pub fn store_variables(
     }
 
     for (name, value) in headers {
-        vars.insert(format!("header_{}", name.to_lowercase()), value.clone());
+        vars.insert(
+            format!("header_{}", name.to_lowercase()),
+            Value::String(value.clone()),
+        );
     }
 
     if let Some(cookies) = cookie_map {
@@ -169,7 +383,7 @@ pub fn store_variables(
             for (name, var_name) in cookies {
                 if let Some(value) = extract_cookie_value(header, name) {
                     debug!("Extracted cookie {}={}", name, value);
-                    vars.insert(var_name.clone(), value);
+                    vars.insert(var_name.clone(), Value::String(value));
                 } else {
                     debug!("Failed to extract cookie {}", name);
                 }
@@ -181,9 +395,8 @@ pub fn store_variables(
 }
 
 fn extract_cookie_value(header: &str, name: &str) -> Option<String> {
-    header
-        .split(';')
-        .find(|s| s.trim().starts_with(&format!("{name}=")))
-        .and_then(|cookie| cookie.split('=').nth(1))
-        .map(|v| v.trim().to_string())
+    parse_set_cookies(header)
+        .into_iter()
+        .find(|cookie| cookie.name == name)
+        .map(|cookie| cookie.value)
 }
@@ -1,22 +1,48 @@
-use crate::models::test::{JsonAssertion, Test};
+use crate::http::cookies::{ResponseCookie, parse_set_cookies};
+use crate::http::timing::{Phase, Timings};
+use crate::http::tls::PeerCertificate;
+use crate::models::test::{CertificateExpectation, CookieExpectation, JsonAssertion, Test};
+use crate::utils::string::value_to_string;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 86_400;
 
 pub struct Result {
     pub ok: bool,
     pub status: (u16, u16),
     pub time_ms: u64,
     pub errors: Vec<String>,
+    /// Every expectation checked, in order, with whether it held
+    pub checks: Vec<Check>,
+}
+
+/// One expectation of a test and its outcome
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub description: String,
+    pub passed: bool,
+}
+
+impl Check {
+    #[must_use]
+    pub fn new(description: impl Into<String>, passed: bool) -> Self {
+        Check { description: description.into(), passed }
+    }
 }
 
 pub fn check(
     test: &Test,
     status: u16,
     body: &Value,
-    time_ms: u64,
-    vars: &HashMap<String, String>,
+    headers: &HashMap<String, String>,
+    timings: &Timings,
+    vars: &HashMap<String, Value>,
 ) -> Result {
+    let time_ms = timings.total_ms();
     let mut errors = Vec::new();
+    let mut checks = vec![Check::new(format!("Status {}", test.expected_status), status == test.expected_status)];
 
     if status != test.expected_status {
         errors.push(format!(
@@ -25,35 +51,183 @@ pub fn check(
         ));
     }
 
-    if let Some(max) = test.max_response_time && time_ms > max {
-        errors.push(format!("Time {time_ms}ms > {max}ms"));
+    if let Some(limit) = &test.max_response_time {
+        for (phase, max) in limit.limits() {
+            let actual = timings.phase(phase).as_millis() as u64;
+            checks.push(Check::new(format!("{} time <= {max}ms", phase.name()), actual <= max));
+            if actual > max {
+                errors.push(match phase {
+                    Phase::Total => format!("Time {actual}ms > {max}ms"),
+                    _ => format!("{} time {actual}ms > {max}ms", phase.name().to_uppercase()),
+                });
+            }
+        }
     }
 
-    if let Some(expected) = &test.expected_body
-        && !super::assertions::body_matches(
+    if let Some(expected) = &test.expected_body {
+        let matches = super::assertions::body_matches(
             &super::variables::replace_variables_in_json(expected, vars),
             body,
-        ) {
-        errors.push("Body mismatch".into());
+        );
+        checks.push(Check::new("Body matches expected_body", matches));
+        if !matches {
+            errors.push("Body mismatch".into());
+        }
     }
 
     if let Some(assertions) = &test.assertions {
         for assertion in assertions {
-            if !super::assertions::validate_assertion(&process_assertion(assertion, vars), body) {
+            let held = super::assertions::validate_assertion(&process_assertion(assertion, vars), body);
+            checks.push(Check::new(format!("{assertion:?}"), held));
+            if !held {
                 errors.push(format!("Failed: {assertion:?}"));
             }
         }
     }
 
+    if let Some(graphql) = &test.graphql {
+        let messages = super::graphql::error_messages(body);
+        if graphql.expect_errors == Some(true) {
+            checks.push(Check::new("GraphQL errors", !messages.is_empty()));
+            if messages.is_empty() {
+                errors.push("Expected GraphQL errors, got none".into());
+            }
+        } else {
+            checks.push(Check::new("No GraphQL errors", messages.is_empty()));
+            if !messages.is_empty() {
+                errors.push(format!("GraphQL errors: {}", messages.join("; ")));
+            }
+        }
+    }
+
+    if let Some(expected_cookies) = &test.expected_cookies {
+        let cookies = headers
+            .get("set-cookie")
+            .map(|h| parse_set_cookies(h))
+            .unwrap_or_default();
+        for (name, expected) in expected_cookies {
+            let cookie_errors = check_cookie(name, expected, &cookies, vars);
+            checks.push(Check::new(format!("Cookie '{name}'"), cookie_errors.is_empty()));
+            errors.extend(cookie_errors);
+        }
+    }
+
     Result {
         ok: errors.is_empty(),
         status: (test.expected_status, status),
         time_ms,
         errors,
+        checks,
+    }
+}
+
+fn check_cookie(
+    name: &str,
+    expected: &CookieExpectation,
+    cookies: &[ResponseCookie],
+    vars: &HashMap<String, Value>,
+) -> Vec<String> {
+    let Some(cookie) = cookies.iter().find(|c| c.name == name) else {
+        return vec![format!("Cookie '{name}' was not set")];
+    };
+
+    let mut errors = Vec::new();
+    // The value and path are case-sensitive; domains and attribute keywords are not
+    let mut compare = |attribute: &str, expected: Option<String>, actual: Option<String>| {
+        let exact = matches!(attribute, "value" | "path");
+        if let Some(expected) = expected
+            && actual
+                .as_deref()
+                .is_none_or(|a| if exact { a != expected } else { !a.eq_ignore_ascii_case(&expected) })
+        {
+            errors.push(format!(
+                "Cookie '{name}' {attribute} is {}, expected {expected}",
+                actual.unwrap_or_else(|| "unset".to_string())
+            ));
+        }
+    };
+
+    compare(
+        "value",
+        expected.value.as_ref().map(|v| replace_vars(v, vars)),
+        Some(cookie.value.clone()),
+    );
+    compare("domain", expected.domain.clone(), cookie.domain.clone());
+    compare("path", expected.path.clone(), cookie.path.clone());
+    compare("SameSite", expected.same_site.clone(), cookie.same_site.clone());
+    compare(
+        "Secure",
+        expected.secure.map(|b| b.to_string()),
+        Some(cookie.secure.to_string()),
+    );
+    compare(
+        "HttpOnly",
+        expected.http_only.map(|b| b.to_string()),
+        Some(cookie.http_only.to_string()),
+    );
+
+    errors
+}
+
+/// Check the server certificate against `expected_certificate`.
+///
+/// `subject` and `issuer` match as case-insensitive substrings of the
+/// distinguished name; the fingerprint ignores case and `:` separators.
+#[must_use]
+pub fn check_certificate(
+    expected: &CertificateExpectation,
+    certificate: Option<&PeerCertificate>,
+) -> Vec<String> {
+    let Some(certificate) = certificate else {
+        return vec!["No server certificate (expected_certificate needs an HTTPS endpoint)".into()];
+    };
+
+    let mut errors = Vec::new();
+    let contains = |haystack: &str, needle: &str| haystack.to_lowercase().contains(&needle.to_lowercase());
+
+    if let Some(subject) = &expected.subject
+        && !contains(&certificate.subject, subject)
+    {
+        errors.push(format!(
+            "Certificate subject is '{}', expected it to contain '{subject}'",
+            certificate.subject
+        ));
+    }
+    if let Some(issuer) = &expected.issuer
+        && !contains(&certificate.issuer, issuer)
+    {
+        errors.push(format!(
+            "Certificate issuer is '{}', expected it to contain '{issuer}'",
+            certificate.issuer
+        ));
     }
+    if let Some(min_days) = expected.min_days_valid {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let days_left = (certificate.not_after - now).div_euclid(SECONDS_PER_DAY);
+        if days_left < min_days {
+            errors.push(format!(
+                "Certificate expires in {days_left} days, expected at least {min_days}"
+            ));
+        }
+    }
+    if let Some(pin) = &expected.sha256_fingerprint {
+        let normalize = |s: &str| s.replace(':', "").to_uppercase();
+        if normalize(pin) != normalize(&certificate.sha256_fingerprint) {
+            errors.push(format!(
+                "Certificate fingerprint is {}, expected {pin}",
+                certificate.sha256_fingerprint
+            ));
+        }
+    }
+
+    errors
 }
 
-fn process_assertion(assertion: &JsonAssertion, vars: &HashMap<String, String>) -> JsonAssertion {
+/// The assertion with its `{{variables}}` resolved
+pub(crate) fn process_assertion(assertion: &JsonAssertion, vars: &HashMap<String, Value>) -> JsonAssertion {
     match assertion {
         JsonAssertion::Exact(v) | JsonAssertion::Contains(v) => {
             let processed = super::variables::replace_variables_in_json(v, vars);
@@ -67,13 +241,17 @@ fn process_assertion(assertion: &JsonAssertion, vars: &HashMap<String, String>)
         JsonAssertion::PathRegex(path, pattern) => {
             JsonAssertion::PathRegex(replace_vars(path, vars), replace_vars(pattern, vars))
         }
+        JsonAssertion::PathContains(path, v) => JsonAssertion::PathContains(
+            replace_vars(path, vars),
+            super::variables::replace_variables_in_json(v, vars),
+        ),
     }
 }
 
-fn replace_vars(s: &str, vars: &HashMap<String, String>) -> String {
+fn replace_vars(s: &str, vars: &HashMap<String, Value>) -> String {
     let mut result = s.to_string();
     for (k, v) in vars {
-        result = result.replace(&format!("{{{{{k}}}}}"), v);
+        result = result.replace(&format!("{{{{{k}}}}}"), &value_to_string(v));
     }
     result
 }
@@ -0,0 +1,239 @@
+//! WebSocket tests: open the socket with the test's headers, then play its `script`.
+
+use super::assertions::{body_matches, validate_assertion};
+use super::execution::{ExecutionResult, failed};
+use super::variables::{replace_variables_in_json, store_variables};
+use super::verify::{Check, process_assertion};
+use crate::http::client::{DEFAULT_SESSION, HttpClient, RequestData};
+use crate::models::test::{Test, WsStep};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::{Connector, MaybeTlsStream, WebSocketStream, connect_async_tls_with_config};
+
+/// How long to wait for the connection or a message when no timeout is set
+pub const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// A received message: its text, and its JSON when it parses (the text as a string otherwise)
+struct Received {
+    text: String,
+    value: Value,
+}
+
+pub async fn run(
+    client: &HttpClient,
+    test: &Test,
+    request: RequestData,
+    vars: &mut HashMap<String, Value>,
+) -> ExecutionResult {
+    let started = Instant::now();
+    let url = match socket_url(&client.config().base_url, &request) {
+        Ok(url) => url,
+        Err(e) => return failed(test, e, Some(request)),
+    };
+    let mut handshake = match url.as_str().into_client_request() {
+        Ok(handshake) => handshake,
+        Err(e) => return failed(test, format!("Invalid WebSocket URL {url}: {e}"), Some(request)),
+    };
+    let session = test.session.as_deref().unwrap_or(DEFAULT_SESSION);
+    let cookies = client.cookie_header(session, &format!("{}{}", client.config().base_url, request.url));
+    let has_cookie = request.headers.iter().any(|(name, _)| name.eq_ignore_ascii_case("cookie"));
+    let extra = cookies.filter(|_| !has_cookie).map(|c| ("Cookie".to_string(), c));
+    for (name, value) in request.headers.iter().cloned().chain(extra) {
+        match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+            (Ok(name), Ok(value)) => {
+                handshake.headers_mut().append(name, value);
+            }
+            _ => return failed(test, format!("Invalid header '{name}'"), Some(request)),
+        }
+    }
+
+    // wss uses the suite's `tls` and `insecure` settings, like HTTPS requests
+    let connector = if url.scheme() == "wss" {
+        match client.tls_config() {
+            Ok(config) => Some(Connector::Rustls(Arc::new(config))),
+            Err(e) => return failed(test, e.to_string(), Some(request)),
+        }
+    } else {
+        None
+    };
+    let connecting = connect_async_tls_with_config(handshake, None, false, connector);
+
+    let settings = client.config().http.merged_with(&test.http);
+    let connect_ms = settings.connect_timeout_ms.or(settings.timeout_ms).unwrap_or(DEFAULT_TIMEOUT_MS);
+    let (mut socket, response) = match tokio::time::timeout(Duration::from_millis(connect_ms), connecting).await {
+        Ok(Ok(connected)) => connected,
+        Ok(Err(WsError::Http(response))) => {
+            // The server answered the upgrade with a plain HTTP response
+            let status = response.status().as_u16();
+            let mut result = failed(test, format!("Status {status}, expected {}", test.expected_status), Some(request));
+            result.status = (test.expected_status, status);
+            result.success = status == test.expected_status;
+            result.body = response
+                .body()
+                .as_ref()
+                .map(|body| Value::String(String::from_utf8_lossy(body).to_string()));
+            result.checks = vec![Check::new(format!("Status {}", test.expected_status), result.success)];
+            if result.success {
+                result.errors.clear();
+            }
+            return result;
+        }
+        Ok(Err(e)) => return failed(test, format!("WebSocket connection to {url} failed: {e}"), Some(request)),
+        Err(_) => {
+            let mut result = failed(test, format!("Connection to {url} timed out after {connect_ms}ms"), Some(request));
+            result.timed_out = true;
+            return result;
+        }
+    };
+
+    let status = response.status().as_u16();
+    let headers: HashMap<String, String> = response
+        .headers()
+        .iter()
+        .filter_map(|(k, v)| Some((k.as_str().to_string(), v.to_str().ok()?.to_string())))
+        .collect();
+    let mut checks = vec![Check::new(format!("Status {}", test.expected_status), status == test.expected_status)];
+    let mut errors = Vec::new();
+    let mut transcript = Vec::new();
+    if status == test.expected_status {
+        play(&mut socket, test, vars, started, &mut transcript, &mut checks, &mut errors).await;
+    } else {
+        errors.push(format!("Status {status}, expected {}", test.expected_status));
+    }
+    let _ = socket.close(None).await;
+
+    ExecutionResult {
+        success: errors.is_empty(),
+        status: (test.expected_status, status),
+        time_ms: started.elapsed().as_millis() as u64,
+        timings: None,
+        body: Some(Value::Array(transcript)),
+        headers,
+        errors,
+        timed_out: false,
+        request: Some(request),
+        checks,
+    }
+}
+
+/// `base_url` + endpoint + query parameters, with the `ws`/`wss` scheme
+fn socket_url(base_url: &str, request: &RequestData) -> Result<reqwest::Url, String> {
+    let address = format!("{base_url}{}", request.url);
+    let mut url = reqwest::Url::parse(&address).map_err(|e| format!("Invalid URL {address}: {e}"))?;
+    if !request.params.is_empty() {
+        url.query_pairs_mut().extend_pairs(&request.params);
+    }
+    let scheme = match url.scheme() {
+        "http" | "ws" => "ws",
+        "https" | "wss" => "wss",
+        other => return Err(format!("Cannot open a WebSocket over {other}")),
+    };
+    url.set_scheme(scheme).map_err(|()| format!("Cannot open a WebSocket to {address}"))?;
+    Ok(url)
+}
+
+/// Run the steps in order, stopping at the first that fails
+async fn play(
+    socket: &mut Socket,
+    test: &Test,
+    vars: &mut HashMap<String, Value>,
+    started: Instant,
+    transcript: &mut Vec<Value>,
+    checks: &mut Vec<Check>,
+    errors: &mut Vec<String>,
+) {
+    for (index, step) in test.script.iter().flatten().enumerate() {
+        let n = index + 1;
+        if let Some(message) = &step.send {
+            let text = match replace_variables_in_json(message, vars) {
+                Value::String(text) => text,
+                other => other.to_string(),
+            };
+            if let Err(e) = socket.send(Message::text(text)).await {
+                checks.push(Check::new(format!("Step {n}: message sent"), false));
+                errors.push(format!("Step {n}: cannot send: {e}"));
+                return;
+            }
+        }
+        if !step.receives() {
+            continue;
+        }
+
+        let timeout_ms = step.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
+        let received = match receive(socket, Duration::from_millis(timeout_ms)).await {
+            Ok(received) => received,
+            Err(e) => {
+                checks.push(Check::new(format!("Step {n}: message received"), false));
+                errors.push(format!("Step {n}: {e}"));
+                return;
+            }
+        };
+        transcript.push(received.value.clone());
+
+        let step_errors = check_message(step, n, &received, vars, checks);
+        if !step_errors.is_empty() {
+            errors.extend(step_errors);
+            return;
+        }
+        if let Some(store) = &step.store {
+            let elapsed = started.elapsed().as_millis() as u64;
+            store_variables(&received.value, store, &HashMap::new(), &None, elapsed, vars);
+        }
+    }
+}
+
+/// The next text or binary message; pings and pongs are skipped
+async fn receive(socket: &mut Socket, timeout: Duration) -> Result<Received, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let text = match tokio::time::timeout(remaining, socket.next()).await {
+            Err(_) => return Err(format!("no message within {}ms", timeout.as_millis())),
+            Ok(None | Some(Ok(Message::Close(_)))) => return Err("the server closed the connection".to_string()),
+            Ok(Some(Err(e))) => return Err(e.to_string()),
+            Ok(Some(Ok(Message::Text(text)))) => text.to_string(),
+            Ok(Some(Ok(Message::Binary(bytes)))) => String::from_utf8_lossy(&bytes).to_string(),
+            Ok(Some(Ok(_))) => continue,
+        };
+        let value = serde_json::from_str(&text).unwrap_or_else(|_| Value::String(text.clone()));
+        return Ok(Received { text, value });
+    }
+}
+
+fn check_message(
+    step: &WsStep,
+    n: usize,
+    received: &Received,
+    vars: &HashMap<String, Value>,
+    checks: &mut Vec<Check>,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    if let Some(expected) = &step.expect {
+        let expected = replace_variables_in_json(expected, vars);
+        let matches = match &expected {
+            Value::String(text) => received.text == *text,
+            json => body_matches(json, &received.value),
+        };
+        checks.push(Check::new(format!("Step {n}: message matches {expected}"), matches));
+        if !matches {
+            errors.push(format!("Step {n}: received {}, expected {expected}", received.text));
+        }
+    }
+    for assertion in step.assertions.iter().flatten() {
+        let held = validate_assertion(&process_assertion(assertion, vars), &received.value);
+        checks.push(Check::new(format!("Step {n}: {assertion:?}"), held));
+        if !held {
+            errors.push(format!("Step {n}: Failed: {assertion:?}"));
+        }
+    }
+    errors
+}
@@ -6,6 +6,7 @@ pub enum CatalystError {
     FileError(String),
     CommandError(crate::engine::commands::CommandError),
     HttpError(String),
+    TimeoutError(String),
     JsonError(String),
     ValidationError(String),
     ConfigError(String),
@@ -21,6 +22,7 @@ impl fmt::Display for CatalystError {
             CatalystError::FileError(msg) => write!(f, "File operation failed: {msg}"),
             CatalystError::CommandError(err) => write!(f, "Command execution failed: {err}"),
             CatalystError::HttpError(msg) => write!(f, "HTTP request failed: {msg}"),
+            CatalystError::TimeoutError(msg) => write!(f, "HTTP request timed out: {msg}"),
             CatalystError::JsonError(msg) => write!(f, "JSON processing failed: {msg}"),
             CatalystError::ValidationError(msg) => write!(f, "Validation failed: {msg}"),
             CatalystError::ConfigError(msg) => write!(f, "Configuration error: {msg}"),
@@ -75,6 +77,11 @@ impl CatalystError {
         CatalystError::HttpError(msg.into())
     }
     
+    /// Create a timeout error with context
+    pub fn timeout_error(msg: impl Into<String>) -> Self {
+        CatalystError::TimeoutError(msg.into())
+    }
+    
     /// Create a JSON error with context
     pub fn json_error(msg: impl Into<String>) -> Self {
         CatalystError::JsonError(msg.into())
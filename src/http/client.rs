@@ -1,8 +1,19 @@
-use crate::models::config::Config;
-use reqwest::Client;
+use crate::error::{CatalystError, CatalystResult};
+use crate::http::timing::{ConnectTimingLayer, Stopwatch, TimedResolver, Timings};
+use crate::http::tls::{self, PeerCertificate};
+use crate::models::config::{Config, HttpSettings};
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::{Client, Proxy, redirect};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+/// Session used by tests that don't set `session`
+pub const DEFAULT_SESSION: &str = "default";
+
+#[derive(Debug, Clone)]
 pub struct RequestData {
     pub method: String,
     pub url: String,
@@ -11,46 +22,246 @@ pub struct RequestData {
     pub body: Option<Value>,
 }
 
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Value,
+    /// Header values keyed by name; repeated headers are joined with `, `,
+    /// except `set-cookie` which keeps one cookie per line
+    pub headers: HashMap<String, String>,
+    /// Leaf certificate presented by the server, for HTTPS requests
+    pub peer_certificate: Option<PeerCertificate>,
+    /// Time spent in each phase, from sending the request to the end of the body
+    pub timings: Timings,
+}
+
+/// A response whose headers have arrived and whose body is yet to be read
+pub struct PendingResponse {
+    pub status: u16,
+    /// As in [`HttpResponse::headers`]
+    pub headers: HashMap<String, String>,
+    pub peer_certificate: Option<PeerCertificate>,
+    response: reqwest::Response,
+    stopwatch: Stopwatch,
+}
+
+impl PendingResponse {
+    /// The next piece of the body as it arrives, `None` at its end
+    ///
+    /// # Errors
+    /// Returns `CatalystError::TimeoutError` when the total timeout elapses,
+    /// and `CatalystError::HttpError` if the connection fails
+    pub async fn chunk(&mut self) -> CatalystResult<Option<Vec<u8>>> {
+        match self.response.chunk().await {
+            Ok(chunk) => Ok(chunk.map(|bytes| bytes.to_vec())),
+            Err(e) if e.is_timeout() => Err(CatalystError::timeout_error(e.to_string())),
+            Err(e) => Err(CatalystError::http_error(e.to_string())),
+        }
+    }
+
+    /// Read the rest of the body: JSON when it parses, text otherwise
+    pub async fn into_response(self) -> HttpResponse {
+        let body = if self.response.content_length().unwrap_or(0) == 0 {
+            Value::Null
+        } else {
+            match self.response.text().await {
+                Ok(text) => match serde_json::from_str(&text) {
+                    Ok(json) => json,
+                    Err(_) => Value::String(text),
+                },
+                Err(_) => Value::String("Failed to read response body".into()),
+            }
+        };
+        HttpResponse {
+            status: self.status,
+            body,
+            headers: self.headers,
+            peer_certificate: self.peer_certificate,
+            timings: self.stopwatch.finish(),
+        }
+    }
+
+    /// Stop reading, leaving the rest of the body unread, with `body` standing for what was read
+    #[must_use]
+    pub fn close(self, body: Value) -> HttpResponse {
+        HttpResponse {
+            status: self.status,
+            body,
+            headers: self.headers,
+            peer_certificate: self.peer_certificate,
+            timings: self.stopwatch.finish(),
+        }
+    }
+}
+
+/// HTTP client keeping one cookie jar per named session.
+///
+/// A `reqwest::Client` is built lazily for each session and combination of
+/// [`HttpSettings`]; clients of the same session share its cookie jar.
 pub struct HttpClient {
-    client: Client,
+    jars: Mutex<HashMap<String, Arc<Jar>>>,
+    clients: Mutex<HashMap<(String, HttpSettings), Client>>,
     config: Config,
+    base_dir: PathBuf,
 }
 
 impl HttpClient {
     pub fn new(config: &Config) -> Self {
-        let client = if config.insecure.unwrap_or(false) {
-            Client::builder()
-                .danger_accept_invalid_certs(true)
-                .build()
-                .expect("Failed to build HTTP client")
-        } else {
-            Client::new()
-        };
-        
         HttpClient {
-            client,
+            jars: Mutex::new(HashMap::new()),
+            clients: Mutex::new(HashMap::new()),
             config: config.clone(),
+            base_dir: PathBuf::from("."),
         }
     }
 
+    /// Resolve relative certificate paths against `dir` (the test file's directory)
+    #[must_use]
+    pub fn with_base_dir(mut self, dir: &Path) -> Self {
+        self.base_dir = dir.to_path_buf();
+        self
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// TLS configuration from the suite's `tls` and `insecure` settings
+    pub fn tls_config(&self) -> CatalystResult<rustls::ClientConfig> {
+        tls::client_config(&self.config.tls, self.config.insecure.unwrap_or(false), &self.base_dir)
+    }
+
+    fn build_client(&self, jar: Arc<Jar>, settings: &HttpSettings) -> CatalystResult<Client> {
+        let mut builder = Client::builder()
+            .cookie_provider(jar)
+            .use_preconfigured_tls(self.tls_config()?)
+            .tls_info(true)
+            .dns_resolver(Arc::new(TimedResolver))
+            .connector_layer(ConnectTimingLayer);
+        if let Some(ms) = settings.timeout_ms {
+            builder = builder.timeout(Duration::from_millis(ms));
+        }
+        if let Some(ms) = settings.connect_timeout_ms {
+            builder = builder.connect_timeout(Duration::from_millis(ms));
+        }
+        builder = builder.redirect(if settings.follow_redirects.unwrap_or(true) {
+            redirect::Policy::limited(
+                settings
+                    .max_redirects
+                    .unwrap_or(HttpSettings::DEFAULT_MAX_REDIRECTS),
+            )
+        } else {
+            redirect::Policy::none()
+        });
+        if let Some(proxy) = &settings.proxy {
+            let proxy = Proxy::all(proxy).map_err(|e| {
+                CatalystError::config_error(format!("Invalid proxy '{proxy}': {e}"))
+            })?;
+            builder = builder.proxy(proxy);
+        }
+        if settings.http2_prior_knowledge.unwrap_or(false) {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(user_agent) = &settings.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        builder
+            .build()
+            .map_err(|e| CatalystError::config_error(format!("Failed to build HTTP client: {e}")))
+    }
+
+    /// Get the client of `session` for `settings`, sharing the session's cookie jar
+    fn session_client(&self, session: &str, settings: &HttpSettings) -> CatalystResult<Client> {
+        let key = (session.to_string(), settings.clone());
+        if let Some(client) = self.clients.lock().unwrap().get(&key) {
+            return Ok(client.clone());
+        }
+
+        let jar = self
+            .jars
+            .lock()
+            .unwrap()
+            .entry(session.to_string())
+            .or_default()
+            .clone();
+        let client = self.build_client(jar, settings)?;
+        self.clients.lock().unwrap().insert(key, client.clone());
+        Ok(client)
+    }
+
     pub async fn execute(
         &self,
         request: RequestData,
     ) -> Result<(u16, Value, HashMap<String, String>), String> {
+        let response = self
+            .send(request, DEFAULT_SESSION, &HttpSettings::default())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok((response.status, response.body, response.headers))
+    }
+
+    /// The `Cookie` header the jar of `session` holds for `url`
+    #[must_use]
+    pub fn cookie_header(&self, session: &str, url: &str) -> Option<String> {
+        let url = reqwest::Url::parse(url).ok()?;
+        let jar = self.jars.lock().unwrap().get(session)?.clone();
+        jar.cookies(&url).and_then(|value| value.to_str().ok().map(str::to_string))
+    }
+
+    /// The headers sent with `headers`: the default headers first, then `headers`.
+    ///
+    /// Headers set by the test (or its template) replace defaults of the same name.
+    #[must_use]
+    pub fn effective_headers(&self, headers: &[(String, String)]) -> Vec<(String, String)> {
+        let defaults = self.config.default_headers.iter().flatten().filter(|(k, _)| {
+            !headers.iter().any(|(name, _)| name.eq_ignore_ascii_case(k))
+        });
+        defaults
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .chain(headers.iter().cloned())
+            .collect()
+    }
+
+    /// Send a request with the cookie jar of `session` and read the whole response.
+    ///
+    /// `overrides` are merged over the `[config]` HTTP settings.
+    ///
+    /// # Errors
+    /// Returns `CatalystError::TimeoutError` when the connect or total timeout
+    /// elapses, and `CatalystError::HttpError` for any other transport failure
+    pub async fn send(
+        &self,
+        request: RequestData,
+        session: &str,
+        overrides: &HttpSettings,
+    ) -> CatalystResult<HttpResponse> {
+        Ok(self.open(request, session, overrides).await?.into_response().await)
+    }
+
+    /// Send a request as [`send`](Self::send) does, returning once the response headers arrive
+    ///
+    /// # Errors
+    /// As for [`send`](Self::send)
+    pub async fn open(
+        &self,
+        request: RequestData,
+        session: &str,
+        overrides: &HttpSettings,
+    ) -> CatalystResult<PendingResponse> {
         let url = format!("{}{}", self.config.base_url, request.url);
+        let settings = self.config.http.merged_with(overrides);
+        let method = request
+            .method
+            .to_uppercase()
+            .parse()
+            .map_err(|_| CatalystError::http_error(format!("Invalid method '{}'", request.method)))?;
 
         let mut builder = self
-            .client
-            .request(request.method.parse().unwrap(), url)
+            .session_client(session, &settings)?
+            .request(method, url)
             .query(&request.params);
 
-        if let Some(default_headers) = &self.config.default_headers {
-            for (k, v) in default_headers {
-                builder = builder.header(k, v);
-            }
-        }
-
-        for (k, v) in request.headers {
+        for (k, v) in self.effective_headers(&request.headers) {
             builder = builder.header(k, v);
         }
 
@@ -58,27 +269,40 @@ impl HttpClient {
             builder = builder.json(&body);
         }
 
-        let response = builder.send().await.map_err(|e| e.to_string())?;
+        let mut stopwatch = Stopwatch::start();
+        let response = stopwatch.measure(builder.send()).await.map_err(|e| {
+            if e.is_timeout() {
+                CatalystError::timeout_error(e.to_string())
+            } else {
+                CatalystError::http_error(e.to_string())
+            }
+        })?;
         let status = response.status().as_u16();
+        let peer_certificate = response
+            .extensions()
+            .get::<reqwest::tls::TlsInfo>()
+            .and_then(|info| info.peer_certificate())
+            .and_then(PeerCertificate::from_der);
 
-        let headers: HashMap<String, String> = response
-            .headers()
-            .iter()
-            .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or_default().to_string()))
-            .collect();
-
-        let body = if response.content_length().unwrap_or(0) == 0 {
-            Value::Null
-        } else {
-            match response.text().await {
-                Ok(text) => match serde_json::from_str(&text) {
-                    Ok(json) => json,
-                    Err(_) => Value::String(text),
-                },
-                Err(_) => Value::String("Failed to read response body".into()),
-            }
-        };
+        let mut headers: HashMap<String, String> = HashMap::new();
+        for (k, v) in response.headers() {
+            let value = v.to_str().unwrap_or_default();
+            headers
+                .entry(k.to_string())
+                .and_modify(|existing| {
+                    let separator = if k == reqwest::header::SET_COOKIE { "\n" } else { ", " };
+                    existing.push_str(separator);
+                    existing.push_str(value);
+                })
+                .or_insert_with(|| value.to_string());
+        }
 
-        Ok((status, body, headers))
+        Ok(PendingResponse {
+            status,
+            headers,
+            peer_certificate,
+            response,
+            stopwatch,
+        })
     }
 }
@@ -0,0 +1,56 @@
+/// A cookie received in a `Set-Cookie` response header, with its attributes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: Option<String>,
+    pub path: Option<String>,
+    pub same_site: Option<String>,
+    pub max_age: Option<i64>,
+    pub expires: Option<String>,
+    pub secure: bool,
+    pub http_only: bool,
+}
+
+/// Parse a single `Set-Cookie` header value.
+///
+/// Only the first `=` separates name from value, so values containing `=` survive intact.
+#[must_use]
+pub fn parse_set_cookie(header: &str) -> Option<ResponseCookie> {
+    let mut parts = header.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut cookie = ResponseCookie {
+        name: name.to_string(),
+        value: value.trim().trim_matches('"').to_string(),
+        ..ResponseCookie::default()
+    };
+
+    for attribute in parts {
+        let (key, val) = attribute
+            .split_once('=')
+            .map_or((attribute.trim(), ""), |(k, v)| (k.trim(), v.trim()));
+        match key.to_lowercase().as_str() {
+            "domain" => cookie.domain = Some(val.trim_start_matches('.').to_string()),
+            "path" => cookie.path = Some(val.to_string()),
+            "samesite" => cookie.same_site = Some(val.to_string()),
+            "max-age" => cookie.max_age = val.parse().ok(),
+            "expires" => cookie.expires = Some(val.to_string()),
+            "secure" => cookie.secure = true,
+            "httponly" => cookie.http_only = true,
+            _ => {}
+        }
+    }
+
+    Some(cookie)
+}
+
+/// Parse every cookie of a flattened `set-cookie` header (one cookie per line)
+#[must_use]
+pub fn parse_set_cookies(header: &str) -> Vec<ResponseCookie> {
+    header.lines().filter_map(parse_set_cookie).collect()
+}
@@ -1,3 +1,6 @@
 pub mod client;
+pub mod cookies;
+pub mod timing;
+pub mod tls;
 
 pub use client::*;
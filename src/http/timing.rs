@@ -0,0 +1,295 @@
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use rustls::crypto::{ActiveKeyExchange, CompletedKeyExchange, CryptoProvider, SupportedKxGroup};
+use rustls::ffdhe_groups::FfdheGroup;
+use rustls::{NamedGroup, ProtocolVersion};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower_layer::Layer;
+use tower_service::Service;
+
+tokio::task_local! {
+    /// Phase marks of the request currently being sent by this task
+    static CLOCK: Arc<Mutex<PhaseClock>>;
+}
+
+/// A phase of an HTTP exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    Dns,
+    Connect,
+    Tls,
+    Ttfb,
+    Download,
+    Total,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 6] = [
+        Phase::Dns,
+        Phase::Connect,
+        Phase::Tls,
+        Phase::Ttfb,
+        Phase::Download,
+        Phase::Total,
+    ];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Dns => "dns",
+            Phase::Connect => "connect",
+            Phase::Tls => "tls",
+            Phase::Ttfb => "ttfb",
+            Phase::Download => "download",
+            Phase::Total => "total",
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Time spent in each phase of one request.
+///
+/// DNS, connect and TLS are zero when a pooled connection was reused; TTFB runs
+/// from the moment the connection is ready until the response headers arrive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    pub dns: Duration,
+    pub connect: Duration,
+    pub tls: Duration,
+    pub ttfb: Duration,
+    pub download: Duration,
+    pub total: Duration,
+}
+
+impl Timings {
+    #[must_use]
+    pub fn phase(&self, phase: Phase) -> Duration {
+        match phase {
+            Phase::Dns => self.dns,
+            Phase::Connect => self.connect,
+            Phase::Tls => self.tls,
+            Phase::Ttfb => self.ttfb,
+            Phase::Download => self.download,
+            Phase::Total => self.total,
+        }
+    }
+
+    /// Total time in whole milliseconds
+    #[must_use]
+    pub fn total_ms(&self) -> u64 {
+        self.total.as_millis() as u64
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (", format_duration(self.total))?;
+        let phases = Phase::ALL[..5]
+            .iter()
+            .map(|p| format!("{p} {}", format_duration(self.phase(*p))))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "{phases})")
+    }
+}
+
+/// Format a duration as milliseconds, keeping one decimal below 10ms
+#[must_use]
+pub fn format_duration(duration: Duration) -> String {
+    let ms = duration.as_secs_f64() * 1000.0;
+    if ms < 10.0 {
+        format!("{ms:.1}ms")
+    } else {
+        format!("{ms:.0}ms")
+    }
+}
+
+#[derive(Debug, Default)]
+struct PhaseClock {
+    dns_start: Option<Instant>,
+    dns_end: Option<Instant>,
+    connect_start: Option<Instant>,
+    tls_start: Option<Instant>,
+    connect_end: Option<Instant>,
+}
+
+fn mark(update: impl FnOnce(&mut PhaseClock)) {
+    // Outside `measure` (e.g. a connection finishing in the background) there is nothing to record
+    let _ = CLOCK.try_with(|clock| update(&mut clock.lock().unwrap()));
+}
+
+fn between(from: Option<Instant>, to: Option<Instant>) -> Duration {
+    match (from, to) {
+        (Some(from), Some(to)) => to.saturating_duration_since(from),
+        _ => Duration::ZERO,
+    }
+}
+
+/// Measures the phases of one request while `send` runs, then the body download in `read_body`
+pub struct Stopwatch {
+    clock: Arc<Mutex<PhaseClock>>,
+    start: Instant,
+    headers_at: Option<Instant>,
+}
+
+impl Stopwatch {
+    #[must_use]
+    pub fn start() -> Self {
+        Stopwatch {
+            clock: Arc::default(),
+            start: Instant::now(),
+            headers_at: None,
+        }
+    }
+
+    /// Run `send` (up to the response headers) with this stopwatch recording connection phases
+    pub async fn measure<F: Future>(&mut self, send: F) -> F::Output {
+        let output = CLOCK.scope(self.clock.clone(), send).await;
+        self.headers_at = Some(Instant::now());
+        output
+    }
+
+    /// Stop the clock once the body has been read
+    #[must_use]
+    pub fn finish(self) -> Timings {
+        let end = Instant::now();
+        let headers_at = self.headers_at.unwrap_or(end);
+        let clock = self.clock.lock().unwrap();
+
+        let tcp_start = clock.dns_end.or(clock.connect_start);
+        let tcp_end = clock.tls_start.or(clock.connect_end);
+        let ready = clock.connect_end.unwrap_or(self.start);
+
+        Timings {
+            dns: between(clock.dns_start, clock.dns_end),
+            connect: between(tcp_start, tcp_end),
+            tls: if clock.tls_start.is_some() {
+                between(clock.tls_start, clock.connect_end)
+            } else {
+                Duration::ZERO
+            },
+            ttfb: headers_at.saturating_duration_since(ready),
+            download: end.saturating_duration_since(headers_at),
+            total: end.saturating_duration_since(self.start),
+        }
+    }
+}
+
+/// DNS resolver that records the lookup phase
+#[derive(Debug, Default)]
+pub struct TimedResolver;
+
+impl Resolve for TimedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            mark(|c| c.dns_start = Some(Instant::now()));
+            let addrs = tokio::net::lookup_host((host.as_str(), 0)).await;
+            mark(|c| c.dns_end = Some(Instant::now()));
+            let addrs: Addrs = Box::new(addrs?.collect::<Vec<_>>().into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Connector layer that records when connection establishment starts and ends
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConnectTimingLayer;
+
+impl<S> Layer<S> for ConnectTimingLayer {
+    type Service = ConnectTiming<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectTiming(inner)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConnectTiming<S>(S);
+
+impl<S, R> Service<R> for ConnectTiming<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let connecting = self.0.call(request);
+        Box::pin(async move {
+            mark(|c| c.connect_start = Some(Instant::now()));
+            let result = connecting.await;
+            mark(|c| c.connect_end = Some(Instant::now()));
+            result
+        })
+    }
+}
+
+/// Key exchange group wrapper; the client starts its key share while writing the
+/// ClientHello, which is the first step of the TLS handshake
+#[derive(Debug)]
+struct TimedKxGroup(&'static dyn SupportedKxGroup);
+
+impl SupportedKxGroup for TimedKxGroup {
+    fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, rustls::Error> {
+        mark(|c| {
+            c.tls_start.get_or_insert_with(Instant::now);
+        });
+        self.0.start()
+    }
+
+    fn start_and_complete(&self, peer_pub_key: &[u8]) -> Result<CompletedKeyExchange, rustls::Error> {
+        self.0.start_and_complete(peer_pub_key)
+    }
+
+    fn ffdhe_group(&self) -> Option<FfdheGroup<'static>> {
+        self.0.ffdhe_group()
+    }
+
+    fn name(&self) -> NamedGroup {
+        self.0.name()
+    }
+
+    fn fips(&self) -> bool {
+        self.0.fips()
+    }
+
+    fn usable_for_version(&self, version: ProtocolVersion) -> bool {
+        self.0.usable_for_version(version)
+    }
+}
+
+/// The ring crypto provider with key exchange groups that mark the start of the
+/// TLS handshake.
+///
+/// It is set on each client's own TLS configuration rather than installed as
+/// the process default, so embedding programs keep their own provider.
+pub fn crypto_provider() -> Arc<CryptoProvider> {
+    static PROVIDER: OnceLock<Arc<CryptoProvider>> = OnceLock::new();
+    PROVIDER
+        .get_or_init(|| {
+            let mut provider = rustls::crypto::ring::default_provider();
+            provider.kx_groups = provider
+                .kx_groups
+                .into_iter()
+                .map(|group| &*Box::leak(Box::new(TimedKxGroup(group))) as &'static dyn SupportedKxGroup)
+                .collect();
+            Arc::new(provider)
+        })
+        .clone()
+}
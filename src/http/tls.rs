@@ -0,0 +1,264 @@
+use crate::error::{CatalystError, CatalystResult};
+use crate::http::timing;
+use crate::models::config::TlsSettings;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, verify_tls12_signature, verify_tls13_signature};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme, SupportedProtocolVersion};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// The certificate presented by the server, as seen by assertions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerCertificate {
+    /// Distinguished name, e.g. `CN=api.example.com, O=Example`
+    pub subject: String,
+    pub issuer: String,
+    /// Expiry as seconds since the Unix epoch
+    pub not_after: i64,
+    /// Uppercase hex SHA-256 of the DER encoding, colon separated
+    pub sha256_fingerprint: String,
+}
+
+impl PeerCertificate {
+    /// Parse a DER encoded X.509 certificate
+    #[must_use]
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let (_, cert) = X509Certificate::from_der(der).ok()?;
+        Some(PeerCertificate {
+            subject: cert.subject().to_string(),
+            issuer: cert.issuer().to_string(),
+            not_after: cert.validity().not_after.timestamp(),
+            sha256_fingerprint: fingerprint(der),
+        })
+    }
+}
+
+/// Format the SHA-256 of `der` as `AB:CD:...`
+#[must_use]
+pub fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+const TLS13_ONLY: &[&SupportedProtocolVersion] = &[&rustls::version::TLS13];
+
+/// Parse `min_tls_version` into the protocol versions to offer; rustls only
+/// speaks TLS 1.2 and 1.3
+pub fn parse_tls_version(version: &str) -> CatalystResult<&'static [&'static SupportedProtocolVersion]> {
+    match version.trim().trim_start_matches("TLS").trim_start_matches("tls").trim() {
+        "1.2" => Ok(rustls::ALL_VERSIONS),
+        "1.3" => Ok(TLS13_ONLY),
+        other => Err(CatalystError::config_error(format!(
+            "Unsupported min_tls_version '{other}' (expected 1.2 or 1.3)"
+        ))),
+    }
+}
+
+/// Resolve a certificate path: absolute paths are used as is, relative paths
+/// are taken from the directory of the test file
+#[must_use]
+pub fn resolve_path(path: &str, base_dir: &Path) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base_dir.join(path)
+    }
+}
+
+fn read(kind: &str, path: &str, base_dir: &Path) -> CatalystResult<Vec<u8>> {
+    let full_path = resolve_path(path, base_dir);
+    fs::read(&full_path).map_err(|e| {
+        CatalystError::config_error(format!(
+            "Cannot read {kind} '{}': {e}",
+            full_path.display()
+        ))
+    })
+}
+
+fn is_pkcs12(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("p12") || ext.eq_ignore_ascii_case("pfx"))
+}
+
+fn is_der(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("der") || ext.eq_ignore_ascii_case("cer"))
+}
+
+/// Load the extra root certificates of `ca_cert` (PEM bundle or DER)
+pub fn load_ca_certificates(path: &str, base_dir: &Path) -> CatalystResult<Vec<CertificateDer<'static>>> {
+    let data = read("ca_cert", path, base_dir)?;
+    let certs = if is_der(path) {
+        vec![CertificateDer::from(data)]
+    } else {
+        CertificateDer::pem_slice_iter(&data)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CatalystError::config_error(format!("Invalid ca_cert '{path}': {e}")))?
+    };
+
+    if certs.is_empty() {
+        return Err(CatalystError::config_error(format!(
+            "ca_cert '{path}' contains no certificates"
+        )));
+    }
+    Ok(certs)
+}
+
+/// Load the client identity for mTLS.
+///
+/// `client_cert` is either a PKCS#12 archive (`.p12`/`.pfx`, unlocked with
+/// `client_cert_password`) or a PEM certificate chain. For PEM, the key comes
+/// from `client_key`, or from `client_cert` itself when it holds both.
+pub fn load_client_identity(
+    settings: &TlsSettings,
+    base_dir: &Path,
+) -> CatalystResult<Option<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)>> {
+    let Some(cert_path) = &settings.client_cert else {
+        if settings.client_key.is_some() {
+            return Err(CatalystError::config_error(
+                "client_key is set without client_cert",
+            ));
+        }
+        return Ok(None);
+    };
+
+    let pem = if is_pkcs12(cert_path) {
+        let data = read("client_cert", cert_path, base_dir)?;
+        pkcs12_to_pem(&data, settings.client_cert_password.as_deref().unwrap_or(""))
+            .map_err(|e| CatalystError::config_error(format!("Invalid client_cert '{cert_path}': {e}")))?
+    } else {
+        let mut pem = read("client_cert", cert_path, base_dir)?;
+        if let Some(key_path) = &settings.client_key {
+            pem.push(b'\n');
+            pem.extend(read("client_key", key_path, base_dir)?);
+        }
+        pem
+    };
+
+    let invalid = |e: String| CatalystError::config_error(format!("Invalid client certificate or key: {e}"));
+    let chain = CertificateDer::pem_slice_iter(&pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| invalid(e.to_string()))?;
+    if chain.is_empty() {
+        return Err(invalid("no certificate found".to_string()));
+    }
+    let key = PrivateKeyDer::from_pem_slice(&pem).map_err(|e| invalid(e.to_string()))?;
+    Ok(Some((chain, key)))
+}
+
+/// Build the TLS configuration of a connection: the bundled web roots plus
+/// `ca_cert`, the client identity and `min_tls_version`. With `insecure`, the
+/// server certificate is accepted whatever its issuer or host name.
+///
+/// The configuration carries its own crypto provider (see
+/// [`timing::crypto_provider`]), so the process-wide rustls default is untouched.
+pub fn client_config(settings: &TlsSettings, insecure: bool, base_dir: &Path) -> CatalystResult<ClientConfig> {
+    let versions = match &settings.min_tls_version {
+        Some(version) => parse_tls_version(version)?,
+        None => rustls::ALL_VERSIONS,
+    };
+    let mut roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    if let Some(path) = &settings.ca_cert {
+        for cert in load_ca_certificates(path, base_dir)? {
+            roots
+                .add(cert)
+                .map_err(|e| CatalystError::config_error(format!("Invalid ca_cert '{path}': {e}")))?;
+        }
+    }
+
+    let provider = timing::crypto_provider();
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_protocol_versions(versions)
+        .map_err(|e| CatalystError::config_error(format!("Invalid TLS settings: {e}")))?;
+    let builder = if insecure {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)))
+    } else {
+        builder.with_root_certificates(roots)
+    };
+    match load_client_identity(settings, base_dir)? {
+        Some((chain, key)) => builder
+            .with_client_auth_cert(chain, key)
+            .map_err(|e| CatalystError::config_error(format!("Invalid client certificate or key: {e}"))),
+        None => Ok(builder.with_no_client_auth()),
+    }
+}
+
+/// Server certificate verifier for `insecure`: any certificate is accepted, but
+/// the handshake signatures are still checked against it
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Convert the first key and certificate chain of a PKCS#12 archive to PEM
+fn pkcs12_to_pem(data: &[u8], password: &str) -> Result<Vec<u8>, String> {
+    let keystore = p12_keystore::KeyStore::from_pkcs12(data, password).map_err(|e| e.to_string())?;
+    let (_, chain) = keystore
+        .private_key_chain()
+        .ok_or("archive contains no private key")?;
+
+    let mut pem = pem_block("PRIVATE KEY", chain.key());
+    for cert in chain.chain() {
+        pem.push_str(&pem_block("CERTIFICATE", cert.as_der()));
+    }
+    Ok(pem.into_bytes())
+}
+
+fn pem_block(label: &str, der: &[u8]) -> String {
+    let encoded = STANDARD.encode(der);
+    let mut block = format!("-----BEGIN {label}-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        block.push_str(std::str::from_utf8(line).unwrap_or_default());
+        block.push('\n');
+    }
+    block.push_str(&format!("-----END {label}-----\n"));
+    block
+}
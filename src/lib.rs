@@ -18,6 +18,7 @@
 //! - **models**: Data structures and types
 //! - **core**: High-level orchestration
 //! - **cli**: Command-line interface
+//! - **tui**: Interactive terminal runner
 //! - **utils**: Utility functions
 
 pub mod checker;
@@ -28,6 +29,7 @@ pub mod error;
 pub mod http;
 pub mod models;
 pub mod output;
+pub mod tui;
 pub mod utils;
 
 // Re-export commonly used items
@@ -1,7 +1,7 @@
 use serde::Deserialize;
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct Config {
     pub base_url: String,
     pub auth_method: Option<String>,
@@ -10,6 +10,55 @@ pub struct Config {
     pub env: Option<EnvConfig>,
     pub insecure: Option<bool>,
     pub allowed_commands: Option<Vec<String>>,
+    pub profiles: Option<HashMap<String, HashMap<String, String>>>,
+    pub secrets: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub http: HttpSettings,
+    #[serde(flatten)]
+    pub tls: TlsSettings,
+}
+
+/// TLS trust and client identity; certificate paths are relative to the test file
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct TlsSettings {
+    pub ca_cert: Option<String>,
+    pub client_cert: Option<String>,
+    pub client_key: Option<String>,
+    pub client_cert_password: Option<String>,
+    pub min_tls_version: Option<String>,
+}
+
+/// HTTP client settings, set in `[config]` and overridable per test
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+pub struct HttpSettings {
+    pub timeout_ms: Option<u64>,
+    pub connect_timeout_ms: Option<u64>,
+    pub follow_redirects: Option<bool>,
+    pub max_redirects: Option<usize>,
+    pub proxy: Option<String>,
+    pub http2_prior_knowledge: Option<bool>,
+    pub user_agent: Option<String>,
+}
+
+impl HttpSettings {
+    /// Default number of redirects followed when `max_redirects` is not set
+    pub const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+    /// Overlay `overrides` on top of these settings, field by field
+    #[must_use]
+    pub fn merged_with(&self, overrides: &HttpSettings) -> HttpSettings {
+        HttpSettings {
+            timeout_ms: overrides.timeout_ms.or(self.timeout_ms),
+            connect_timeout_ms: overrides.connect_timeout_ms.or(self.connect_timeout_ms),
+            follow_redirects: overrides.follow_redirects.or(self.follow_redirects),
+            max_redirects: overrides.max_redirects.or(self.max_redirects),
+            proxy: overrides.proxy.clone().or_else(|| self.proxy.clone()),
+            http2_prior_knowledge: overrides
+                .http2_prior_knowledge
+                .or(self.http2_prior_knowledge),
+            user_agent: overrides.user_agent.clone().or_else(|| self.user_agent.clone()),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -0,0 +1,29 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A canned response served by `catalyst mock` to the requests it matches
+#[derive(Debug, Deserialize, Clone)]
+pub struct Mock {
+    pub name: Option<String>,
+    /// Any method when unset
+    pub method: Option<String>,
+    /// A `{{name}}` placeholder matches any value within a path segment
+    pub path: String,
+    /// Parameters the request must have; a `{{name}}` value accepts any value
+    pub query: Option<HashMap<String, String>>,
+    /// Headers the request must have, names case-insensitive; a `{{name}}` value accepts any value
+    pub headers: Option<HashMap<String, String>>,
+    /// Fields the JSON request body must contain
+    pub body: Option<Value>,
+    #[serde(default = "default_status")]
+    pub status: u16,
+    pub response_headers: Option<HashMap<String, String>>,
+    /// Sent as JSON, or as plain text when it is a string
+    pub response_body: Option<Value>,
+    pub delay_ms: Option<u64>,
+}
+
+fn default_status() -> u16 {
+    200
+}
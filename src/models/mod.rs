@@ -1,4 +1,5 @@
 pub mod command;
 pub mod config;
+pub mod mock;
 pub mod suite;
 pub mod test;
@@ -1,12 +1,33 @@
 use crate::models::command::CommandStep;
 use crate::models::config::Config;
+use crate::models::mock::Mock;
 use crate::models::test::Test;
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
 pub struct TestSuite {
     pub config: Config,
+    #[serde(default)]
     pub tests: Vec<Test>,
+    /// Canned responses of `catalyst mock`, matched before the ones derived from the tests
+    pub mocks: Option<Vec<Mock>>,
     pub setup: Option<Vec<CommandStep>>,
     pub teardown: Option<Vec<CommandStep>>,
+    /// Requests run after the `setup` commands and before any test, e.g. to seed fixtures;
+    /// the first failing one stops the run
+    pub setup_tests: Option<Vec<Test>>,
+    /// Requests run after the tests, before the `teardown` commands, whatever happened before;
+    /// their results are reported apart from those of the tests
+    pub teardown_tests: Option<Vec<Test>>,
+}
+
+impl TestSuite {
+    /// The setup tests, the tests and the teardown tests, in the order they run
+    pub fn all_tests(&self) -> impl Iterator<Item = &Test> {
+        self.setup_tests
+            .iter()
+            .flatten()
+            .chain(&self.tests)
+            .chain(self.teardown_tests.iter().flatten())
+    }
 }
@@ -1,8 +1,11 @@
 use crate::models::command::CommandStep;
+use crate::http::timing::Phase;
+use crate::models::config::HttpSettings;
+use indexmap::IndexMap;
 use serde::Deserialize;
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 #[serde(tag = "type", content = "value")]
 pub enum JsonAssertion {
     Exact(serde_json::Value),
@@ -12,28 +15,232 @@ pub enum JsonAssertion {
     Regex(String),
 
     PathRegex(String, String),
+
+    /// The value at the JSON path contains the JSON value, as with `Contains`
+    PathContains(String, serde_json::Value),
+}
+
+/// A GraphQL operation, sent as a POST with a `{query, variables, operationName}` JSON body
+#[derive(Debug, Deserialize, Clone)]
+pub struct GraphqlRequest {
+    /// The document, or the path of a `.graphql` file relative to the test file
+    pub query: String,
+    pub variables: Option<serde_json::Value>,
+    pub operation_name: Option<String>,
+    /// The response is expected to carry `errors`; otherwise a non-empty `errors` array fails the test
+    pub expect_errors: Option<bool>,
+}
+
+impl GraphqlRequest {
+    /// Whether `query` names a file rather than holding the document
+    #[must_use]
+    pub fn query_file(&self) -> Option<&str> {
+        let query = self.query.trim();
+        let is_file = (query.ends_with(".graphql") || query.ends_with(".gql"))
+            && !query.contains(char::is_whitespace);
+        is_file.then_some(query)
+    }
+}
+
+/// Expected attributes of a cookie set by the response; unset fields are not checked
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CookieExpectation {
+    pub value: Option<String>,
+    pub domain: Option<String>,
+    pub path: Option<String>,
+    pub same_site: Option<String>,
+    pub secure: Option<bool>,
+    pub http_only: Option<bool>,
+}
+
+/// Expected properties of the server certificate; unset fields are not checked
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CertificateExpectation {
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+    pub min_days_valid: Option<i64>,
+    pub sha256_fingerprint: Option<String>,
+}
+
+/// How a `stream` test reads the response body, and what it expects of the items read
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct StreamExpectation {
+    /// `sse`, `ndjson` or `chunks`; taken from the `Content-Type` when unset
+    pub format: Option<String>,
+    /// Stop reading once this condition holds, with `body` being the item just read
+    pub until: Option<String>,
+    /// Stop reading after this many milliseconds
+    pub window_ms: Option<u64>,
+    pub min_events: Option<usize>,
+    pub max_events: Option<usize>,
+    /// Items expected in this order, each matched as with `expected_body`; others may come in between
+    pub events: Option<Vec<serde_json::Value>>,
+}
+
+/// `max_response_time`: a total budget in ms, or a table of budgets per phase
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(untagged)]
+pub enum ResponseTimeLimit {
+    Total(u64),
+    Phases(PhaseLimits),
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(deny_unknown_fields)]
+pub struct PhaseLimits {
+    pub dns: Option<u64>,
+    pub connect: Option<u64>,
+    pub tls: Option<u64>,
+    pub ttfb: Option<u64>,
+    pub download: Option<u64>,
+    pub total: Option<u64>,
 }
 
-#[derive(Debug, Deserialize)]
+impl ResponseTimeLimit {
+    /// The budgets in ms, by phase
+    #[must_use]
+    pub fn limits(&self) -> Vec<(Phase, u64)> {
+        match self {
+            ResponseTimeLimit::Total(max) => vec![(Phase::Total, *max)],
+            ResponseTimeLimit::Phases(p) => [
+                (Phase::Dns, p.dns),
+                (Phase::Connect, p.connect),
+                (Phase::Tls, p.tls),
+                (Phase::Ttfb, p.ttfb),
+                (Phase::Download, p.download),
+                (Phase::Total, p.total),
+            ]
+            .into_iter()
+            .filter_map(|(phase, max)| max.map(|m| (phase, m)))
+            .collect(),
+        }
+    }
+}
+
+/// One step of a WebSocket test: send a message, wait for the next one and check it, or both
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct WsStep {
+    /// Sent as a text message; JSON values other than strings are sent as JSON text
+    pub send: Option<serde_json::Value>,
+    /// The next message must be this text, or match this JSON as with `expected_body`
+    pub expect: Option<serde_json::Value>,
+    pub assertions: Option<Vec<JsonAssertion>>,
+    /// Variables to set from the received message, by JSON path (`$` for the whole message)
+    pub store: Option<HashMap<String, String>>,
+    /// How long to wait for the message, 5000 by default
+    pub timeout_ms: Option<u64>,
+}
+
+impl WsStep {
+    /// Whether the step waits for a message
+    #[must_use]
+    pub fn receives(&self) -> bool {
+        self.expect.is_some() || self.assertions.is_some() || self.store.is_some()
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
 pub struct Test {
     pub name: String,
+    /// Template whose fields this test inherits; already merged in by the parser
+    pub extends: Option<String>,
+    /// Labels to select tests by with `--tag` and `--exclude-tag`
+    pub tags: Option<Vec<String>>,
+    /// Do not run the test, for this reason
+    pub skip: Option<String>,
+    /// Skip the test when this condition (as in a hook's `when`) holds
+    pub skip_if: Option<String>,
+    /// When any selected test sets it, run only those tests
+    pub only: Option<bool>,
+    /// The test is expected to fail, e.g. because of a known bug
+    pub xfail: Option<String>,
+    /// Defaults to POST for `graphql` tests and GET for `ws` tests; the RPC name for `grpc` tests
+    #[serde(default)]
     pub method: String,
+    /// Not used by `grpc` tests
+    #[serde(default)]
     pub endpoint: String,
     pub query_params: Option<HashMap<String, String>>,
     pub headers: Option<HashMap<String, String>>,
     pub body: Option<serde_json::Value>,
     pub body_file: Option<String>,
+    /// Send a GraphQL operation instead of `body`
+    pub graphql: Option<GraphqlRequest>,
+    /// Required, except on scenarios: the parser rejects a request without it
+    #[serde(default)]
     pub expected_status: u16,
 
+    /// `http` (the default), `ws` or `grpc`
+    pub protocol: Option<String>,
+    /// Messages sent and expected over the WebSocket of a `ws` test
+    pub script: Option<Vec<WsStep>>,
+    /// Fully qualified service of a `grpc` test, e.g. `users.v1.UserService`
+    pub service: Option<String>,
+    /// `.proto` files describing the service, relative to the test file; server reflection when unset
+    pub proto: Option<Vec<String>>,
+    /// Directories `proto` imports are resolved from, relative to the test file; the test file's directory by default
+    pub proto_path: Option<Vec<String>>,
+
     pub expected_body: Option<serde_json::Value>,
 
     pub assertions: Option<Vec<JsonAssertion>>,
     pub expected_headers: Option<Vec<(String, String)>>,
+    pub expected_cookies: Option<HashMap<String, CookieExpectation>>,
+    pub expected_certificate: Option<CertificateExpectation>,
+    /// Read the body as a stream of events instead of all at once
+    pub stream: Option<StreamExpectation>,
     pub store: Option<HashMap<String, String>>,
     pub get_cookie: Option<HashMap<String, String>>,
+    pub session: Option<String>,
+    /// Overrides `[config]` `auth_method`/`auth_token` for this test
+    pub auth_method: Option<String>,
+    pub auth_token: Option<String>,
+    #[serde(flatten)]
+    pub http: HttpSettings,
 
-    pub max_response_time: Option<u64>,
+    pub max_response_time: Option<ResponseTimeLimit>,
 
     pub before: Option<Vec<CommandStep>>,
     pub after: Option<Vec<CommandStep>>,
+
+    /// Requests run in order as one scenario, with variables of their own; the
+    /// scenario's own request fields are not used
+    pub steps: Option<Vec<Test>>,
+
+    /// Run once per combination of values, e.g. `{ role = ["admin", "user"] }`
+    pub matrix: Option<IndexMap<String, Vec<serde_json::Value>>>,
+    /// CSV (with a header row) or JSON array of objects; one instance per row
+    pub data_file: Option<String>,
+    /// Path over the variables, e.g. `$.items[*]`; the test runs once per item selected,
+    /// with `{{item}}` and `{{index}}` bound
+    pub for_each: Option<String>,
+    /// Row values of an expanded instance, bound as variables while it runs
+    #[serde(skip)]
+    pub parameters: Vec<(String, serde_json::Value)>,
+}
+
+impl Test {
+    /// Whether the test opens a WebSocket instead of sending an HTTP request
+    #[must_use]
+    pub fn is_websocket(&self) -> bool {
+        self.protocol.as_deref().is_some_and(|p| p.eq_ignore_ascii_case("ws"))
+    }
+
+    /// Whether the test sends a plain HTTP request
+    #[must_use]
+    pub fn is_http(&self) -> bool {
+        !self.is_websocket() && !self.is_grpc() && !self.is_scenario()
+    }
+
+    /// Whether the test calls a gRPC method instead of sending an HTTP request
+    #[must_use]
+    pub fn is_grpc(&self) -> bool {
+        self.protocol.as_deref().is_some_and(|p| p.eq_ignore_ascii_case("grpc"))
+    }
+
+    /// Whether the test runs `steps` instead of a request of its own
+    #[must_use]
+    pub fn is_scenario(&self) -> bool {
+        self.steps.is_some()
+    }
 }
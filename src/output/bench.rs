@@ -0,0 +1,147 @@
+use crate::core::bench::{BenchStats, Metric, Threshold};
+use crate::http::timing::format_duration;
+use colored::Colorize;
+
+/// Width of the longest histogram bar, in characters
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// Formats the report of `catalyst bench`
+pub struct BenchReportFormatter {
+    pub disable_color: bool,
+}
+
+impl BenchReportFormatter {
+    pub fn new(disable_color: bool) -> Self {
+        Self { disable_color }
+    }
+
+    /// Format the whole report: totals, latency percentiles, errors, histogram and thresholds
+    pub fn format_report(&self, stats: &BenchStats, thresholds: &[Threshold]) -> String {
+        let mut output = String::new();
+
+        output.push_str(&format!("\n{}\n", self.heading("Summary:")));
+        output.push_str(&format!(
+            "  Requests:   {} in {:.1}s\n",
+            stats.requests,
+            stats.elapsed.as_secs_f64()
+        ));
+        output.push_str(&format!("  Throughput: {:.1} req/s\n", stats.throughput()));
+        output.push_str(&format!(
+            "  Errors:     {} ({:.2}%)\n",
+            stats.errors,
+            stats.error_rate()
+        ));
+
+        output.push_str(&format!("\n{}\n", self.heading("Latency:")));
+        output.push_str(&format!(
+            "  min {}  mean {}  max {}\n",
+            format_duration(stats.min()),
+            format_duration(stats.mean()),
+            format_duration(stats.max())
+        ));
+        output.push_str(&format!(
+            "  p50 {}  p90 {}  p95 {}  p99 {}\n",
+            format_duration(stats.percentile(50.0)),
+            format_duration(stats.percentile(90.0)),
+            format_duration(stats.percentile(95.0)),
+            format_duration(stats.percentile(99.0))
+        ));
+
+        output.push_str(&format!("\n{}\n", self.heading("Status codes:")));
+        for (status, count) in &stats.status_counts {
+            let label = if *status == 0 {
+                "no response".to_string()
+            } else {
+                status.to_string()
+            };
+            output.push_str(&format!("  {label:<12} {count}\n"));
+        }
+
+        if !stats.errors_by_kind.is_empty() {
+            output.push_str(&format!("\n{}\n", self.heading("Errors:")));
+            for (kind, count) in &stats.errors_by_kind {
+                let rate = *count as f64 * 100.0 / stats.requests as f64;
+                output.push_str(&format!(
+                    "  {:<18} {count} ({rate:.2}%)\n",
+                    kind.to_string()
+                ));
+            }
+        }
+
+        if stats.per_test.len() > 1 {
+            output.push_str(&format!("\n{}\n", self.heading("Tests:")));
+            for (name, (requests, errors)) in &stats.per_test {
+                output.push_str(&format!("  {name}: {requests} requests, {errors} errors\n"));
+            }
+        }
+
+        output.push_str(&format!("\n{}\n", self.heading("Histogram:")));
+        output.push_str(&self.format_histogram(stats));
+
+        if !thresholds.is_empty() {
+            output.push_str(&format!("\n{}\n", self.heading("Thresholds:")));
+            for threshold in thresholds {
+                output.push_str(&self.format_threshold(threshold, stats));
+            }
+        }
+
+        output
+    }
+
+    /// One bar per bucket, from the first to the last non-empty one
+    pub fn format_histogram(&self, stats: &BenchStats) -> String {
+        let buckets = stats.histogram();
+        let Some(first) = buckets.iter().position(|(_, count)| *count > 0) else {
+            return "  (no requests)\n".to_string();
+        };
+        let last = buckets
+            .iter()
+            .rposition(|(_, count)| *count > 0)
+            .unwrap_or(first);
+        let max = buckets.iter().map(|(_, count)| *count).max().unwrap_or(1);
+
+        let mut output = String::new();
+        for (bound, count) in &buckets[first..=last] {
+            let label = match bound {
+                Some(ms) => format!("<= {ms}ms"),
+                None => format!(
+                    "> {}ms",
+                    crate::core::bench::HISTOGRAM_BOUNDS_MS.last().unwrap_or(&0)
+                ),
+            };
+            let bar = "█".repeat((count * HISTOGRAM_WIDTH).div_ceil(max));
+            let bar = if self.disable_color {
+                bar
+            } else {
+                bar.cyan().to_string()
+            };
+            output.push_str(&format!("  {label:>10} | {bar} {count}\n"));
+        }
+        output
+    }
+
+    fn format_threshold(&self, threshold: &Threshold, stats: &BenchStats) -> String {
+        let passed = threshold.passes(stats);
+        let marker = match (passed, self.disable_color) {
+            (true, true) => "✓".to_string(),
+            (false, true) => "✗".to_string(),
+            (true, false) => "✓".green().to_string(),
+            (false, false) => "✗".red().to_string(),
+        };
+        let actual = threshold.actual(stats);
+        let actual = match threshold.metric {
+            Metric::Throughput => format!("{actual:.1} req/s"),
+            Metric::ErrorRate => format!("{actual:.2}%"),
+            _ => format_duration(std::time::Duration::from_secs_f64(actual / 1000.0)),
+        };
+        format!("  {marker} {} (actual {actual})\n", threshold.source)
+    }
+
+    fn heading(&self, text: &str) -> String {
+        if self.disable_color {
+            text.to_string()
+        } else {
+            text.bold().to_string()
+        }
+    }
+}
@@ -3,8 +3,10 @@
 //! This module handles all test result formatting and display concerns,
 //! separating them from the core test execution logic in TestRunner.
 
+pub mod bench;
 pub mod formatter;
 pub mod summary;
 
+pub use bench::*;
 pub use formatter::*;
 pub use summary::*;
\ No newline at end of file
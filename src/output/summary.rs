@@ -1,4 +1,5 @@
 use crate::core::runner::TestResult;
+use crate::http::timing::format_duration;
 use colored::Colorize;
 use serde_json::Value;
 use std::env;
@@ -13,9 +14,12 @@ impl TestSummaryFormatter {
         Self { disable_color }
     }
 
-    /// Format compact results list (non-verbose mode only)
-    pub fn format_compact_results(&self, results: &[TestResult], skipped: usize, total: usize) -> String {
+    /// Format compact results list (non-verbose mode only).
+    ///
+    /// `not_selected` counts the tests left out by the selection flags or `only`.
+    pub fn format_compact_results(&self, results: &[TestResult], not_selected: usize, total: usize) -> String {
         let mut output = String::new();
+        let counts = ResultCounts::of(results);
 
         if !self.disable_color {
             output.push_str(&format!("\n{}\n", "━".repeat(get_terminal_width()).blue()));
@@ -28,61 +32,83 @@ impl TestSummaryFormatter {
                     "✗".red()
                 };
                 
-                if result.success {
-                    output.push_str(&format!("  {} {}\n", status_indicator, result.name));
+                if let Some(reason) = &result.skipped {
+                    output.push_str(&format!(
+                        "  {} {} {}\n",
+                        "-".yellow(),
+                        result.name,
+                        format!("(skipped: {reason})").yellow()
+                    ));
+                } else if let Some(reason) = &result.xfailed {
+                    output.push_str(&format!(
+                        "  {} {} {}\n",
+                        "x".yellow(),
+                        result.name,
+                        format!("(xfail: {reason})").yellow()
+                    ));
+                } else if result.success {
+                    output.push_str(&format!(
+                        "  {} {}{}\n",
+                        status_indicator,
+                        result.name,
+                        format_total_time(result).dimmed()
+                    ));
                 } else {
                     output.push_str(&format!(
                         "  {} {} (expected {}, got {})\n", 
                         status_indicator, 
                         result.name,
                         result.expected_status,
-                        result.actual_status.to_string().red()
+                        result.actual_status_label().red()
                     ));
                 }
+                output.push_str(&self.format_steps(result, "      "));
             }
             
             output.push('\n');
-            let success_count = results.iter().filter(|r| r.success).count();
-            let fail_count = results.len() - success_count;
-
-            if success_count > 0 {
-                output.push_str(&format!("{} passed", format!("{success_count} tests").green()));
-            }
-            if fail_count > 0 {
-                if success_count > 0 {
-                    output.push_str(", ");
-                }
-                output.push_str(&format!("{} failed", format!("{fail_count} tests").red()));
-            }
-            if skipped > 0 {
-                if success_count > 0 || fail_count > 0 {
-                    output.push_str(", ");
-                }
-                output.push_str(&format!("{} skipped", format!("{skipped} tests").yellow()));
-            }
+            let parts: Vec<String> = [
+                (counts.passed, "passed", "green"),
+                (counts.failed, "failed", "red"),
+                (counts.timed_out, "timed out", "red"),
+                (counts.xfailed, "xfailed", "yellow"),
+                (counts.skipped, "skipped", "yellow"),
+                (not_selected, "not selected", "yellow"),
+            ]
+            .into_iter()
+            .filter(|(count, _, _)| *count > 0)
+            .map(|(count, label, color)| format!("{} {label}", format!("{count} tests").color(color)))
+            .collect();
+            output.push_str(&parts.join(", "));
             output.push_str(&format!(" (total: {total})\n"));
         } else {
             output.push_str("\nResults:\n");
             for result in results {
-                if result.success {
-                    output.push_str(&format!("  ✓ {}\n", result.name));
+                if let Some(reason) = &result.skipped {
+                    output.push_str(&format!("  - {} (skipped: {reason})\n", result.name));
+                } else if let Some(reason) = &result.xfailed {
+                    output.push_str(&format!("  x {} (xfail: {reason})\n", result.name));
+                } else if result.success {
+                    output.push_str(&format!("  ✓ {}{}\n", result.name, format_total_time(result)));
                 } else {
                     output.push_str(&format!(
                         "  ✗ {} (expected {}, got {})\n", 
-                        result.name, result.expected_status, result.actual_status
+                        result.name, result.expected_status, result.actual_status_label()
                     ));
                 }
+                output.push_str(&self.format_steps(result, "      "));
             }
             
-            let success_count = results.iter().filter(|r| r.success).count();
-            let fail_count = results.len() - success_count;
-            
-            output.push_str(&format!("\n{success_count} tests passed"));
-            if fail_count > 0 {
-                output.push_str(&format!(", {fail_count} tests failed"));
-            }
-            if skipped > 0 {
-                output.push_str(&format!(", {skipped} tests skipped"));
+            output.push_str(&format!("\n{} tests passed", counts.passed));
+            for (count, label) in [
+                (counts.failed, "failed"),
+                (counts.timed_out, "timed out"),
+                (counts.xfailed, "xfailed"),
+                (counts.skipped, "skipped"),
+                (not_selected, "not selected"),
+            ] {
+                if count > 0 {
+                    output.push_str(&format!(", {count} tests {label}"));
+                }
             }
             output.push_str(&format!(" (total: {total})\n"));
         }
@@ -90,6 +116,62 @@ impl TestSummaryFormatter {
         output
     }
 
+    /// One line per step of a scenario, under it at `indent`; nothing for other tests
+    pub fn format_steps(&self, result: &TestResult, indent: &str) -> String {
+        let mut output = String::new();
+        for (i, step) in result.steps.iter().enumerate() {
+            let line = self.format_line(&format!("{}. {}", i + 1, step.name), step);
+            output.push_str(&format!("{indent}{line}\n"));
+            output.push_str(&self.format_steps(step, &format!("{indent}   ")));
+        }
+        output
+    }
+
+    /// The results of the suite's setup or teardown tests under `title`, with the messages
+    /// of the failing ones; nothing when there are none
+    pub fn format_suite_tests(&self, title: &str, results: &[TestResult]) -> String {
+        if results.is_empty() {
+            return String::new();
+        }
+        let mut output = format!("\n{title}:\n");
+        for result in results {
+            output.push_str(&format!("  {}\n", self.format_line(&result.name, result)));
+            output.push_str(&self.format_steps(result, "      "));
+            if !result.success {
+                for message in &result.messages {
+                    output.push_str(&format!("    - {}\n", self.paint(message.clone(), "red")));
+                }
+            }
+        }
+        output
+    }
+
+    /// `name` marked as skipped, expected to fail, passed or failed
+    fn format_line(&self, name: &str, result: &TestResult) -> String {
+        if let Some(reason) = &result.skipped {
+            self.paint(format!("- {name} (skipped: {reason})"), "yellow")
+        } else if let Some(reason) = &result.xfailed {
+            self.paint(format!("x {name} (xfail: {reason})"), "yellow")
+        } else if result.success {
+            format!("{} {name}{}", self.paint("✓".to_string(), "green"), format_total_time(result))
+        } else {
+            format!(
+                "{} {name} (expected {}, got {})",
+                self.paint("✗".to_string(), "red"),
+                result.expected_status,
+                self.paint(result.actual_status_label(), "red")
+            )
+        }
+    }
+
+    fn paint(&self, text: String, color: &str) -> String {
+        if self.disable_color {
+            text
+        } else {
+            text.color(color).to_string()
+        }
+    }
+
     /// Format failure details section
     pub fn format_failure_details(&self, results: &[TestResult]) -> String {
         let failed_results: Vec<_> = results.iter().filter(|r| !r.success).collect();
@@ -122,14 +204,18 @@ impl TestSummaryFormatter {
                 ));
                 output.push_str(&format!(
                     "Status: {} (expected {})\n", 
-                    result.actual_status.to_string().red(),
+                    result.actual_status_label().red(),
                     result.expected_status.to_string().bold()
                 ));
             } else {
                 output.push_str("---\n");
                 output.push_str(&format!("Test: {}\n", result.name));
                 output.push_str(&format!("Endpoint: {} {}\n", result.method, result.endpoint));
-                output.push_str(&format!("Status: {} (expected {})\n", result.actual_status, result.expected_status));
+                output.push_str(&format!("Status: {} (expected {})\n", result.actual_status_label(), result.expected_status));
+            }
+
+            if let Some(timings) = &result.timings {
+                output.push_str(&format!("Timing: {timings}\n"));
             }
             
             if !result.messages.is_empty() {
@@ -175,6 +261,45 @@ impl TestSummaryFormatter {
     }
 }
 
+/// Results by category; every result is in exactly one
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ResultCounts {
+    pub passed: usize,
+    pub failed: usize,
+    pub timed_out: usize,
+    pub skipped: usize,
+    pub xfailed: usize,
+}
+
+impl ResultCounts {
+    #[must_use]
+    pub fn of(results: &[TestResult]) -> Self {
+        let mut counts = ResultCounts::default();
+        for result in results {
+            if result.skipped.is_some() {
+                counts.skipped += 1;
+            } else if result.xfailed.is_some() {
+                counts.xfailed += 1;
+            } else if result.success {
+                counts.passed += 1;
+            } else if result.timed_out {
+                counts.timed_out += 1;
+            } else {
+                counts.failed += 1;
+            }
+        }
+        counts
+    }
+}
+
+/// ` (12ms)` after a result name, or nothing when no response was received
+fn format_total_time(result: &TestResult) -> String {
+    result
+        .timings
+        .map(|t| format!(" ({})", format_duration(t.total)))
+        .unwrap_or_default()
+}
+
 fn get_terminal_width() -> usize {
     env::var("COLUMNS")
         .ok()
@@ -0,0 +1,380 @@
+use crate::core::runner::TestResult;
+use crate::core::selection::TestSelection;
+use crate::models::test::Test;
+use crate::utils::secrets::{self, MASK, is_secret_variable};
+use crate::utils::string::value_to_string;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Where a test stands in the current session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    NotRun,
+    Queued,
+    Running,
+    Passed,
+    Failed,
+    Skipped,
+    XFailed,
+}
+
+impl Status {
+    #[must_use]
+    pub fn of(result: &TestResult) -> Self {
+        if result.skipped.is_some() {
+            Status::Skipped
+        } else if result.xfailed.is_some() {
+            Status::XFailed
+        } else if result.success {
+            Status::Passed
+        } else {
+            Status::Failed
+        }
+    }
+
+    #[must_use]
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Status::NotRun => "·",
+            Status::Queued => "○",
+            Status::Running => "…",
+            Status::Passed => "✓",
+            Status::Failed => "✗",
+            Status::Skipped => "-",
+            Status::XFailed => "x",
+        }
+    }
+}
+
+/// The outcome of one run of a test, with the variables around it, secrets masked
+#[derive(Debug)]
+pub struct TestRecord {
+    pub result: TestResult,
+    pub variables_before: BTreeMap<String, String>,
+    pub variables_after: BTreeMap<String, String>,
+}
+
+impl TestRecord {
+    #[must_use]
+    pub fn new(result: TestResult, before: &HashMap<String, Value>, after: &HashMap<String, Value>) -> Self {
+        TestRecord {
+            result,
+            variables_before: displayed_variables(before),
+            variables_after: displayed_variables(after),
+        }
+    }
+}
+
+fn displayed_variables(variables: &HashMap<String, Value>) -> BTreeMap<String, String> {
+    variables
+        .iter()
+        .map(|(name, value)| {
+            let shown = if is_secret_variable(name) {
+                MASK.to_string()
+            } else {
+                secrets::redact(&value_to_string(value))
+            };
+            (name.clone(), shown)
+        })
+        .collect()
+}
+
+/// Progress reported by the worker running the tests
+#[derive(Debug)]
+pub enum RunEvent {
+    Started(usize),
+    Finished(usize, Box<TestRecord>),
+    /// Every requested test has run
+    Idle,
+}
+
+/// What the terminal loop should do after a key press
+#[derive(Debug, PartialEq, Eq)]
+pub enum Action {
+    None,
+    Run(Vec<usize>),
+    Quit,
+}
+
+/// Tabs of the detail pane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Request,
+    Response,
+    Assertions,
+    Variables,
+}
+
+impl Pane {
+    pub const ALL: [Pane; 4] = [Pane::Request, Pane::Response, Pane::Assertions, Pane::Variables];
+
+    #[must_use]
+    pub fn title(self) -> &'static str {
+        match self {
+            Pane::Request => "Request",
+            Pane::Response => "Response",
+            Pane::Assertions => "Assertions",
+            Pane::Variables => "Variables",
+        }
+    }
+}
+
+pub struct Entry {
+    pub test: Test,
+    pub status: Status,
+    pub record: Option<TestRecord>,
+}
+
+/// State of `catalyst tui`, independent of the terminal
+pub struct App {
+    pub entries: Vec<Entry>,
+    pub base_url: String,
+    /// Substring or regex narrowing the list; `a` runs what it keeps
+    pub filter: String,
+    pub editing_filter: bool,
+    /// Position in the filtered list
+    pub selected: usize,
+    pub pane: Pane,
+    pub scroll: u16,
+    pub running: bool,
+}
+
+impl App {
+    #[must_use]
+    pub fn new(tests: Vec<Test>, base_url: String) -> Self {
+        App {
+            entries: tests
+                .into_iter()
+                .map(|test| Entry { test, status: Status::NotRun, record: None })
+                .collect(),
+            base_url,
+            filter: String::new(),
+            editing_filter: false,
+            selected: 0,
+            pane: Pane::Request,
+            scroll: 0,
+            running: false,
+        }
+    }
+
+    /// Indices of the entries the filter keeps
+    #[must_use]
+    pub fn visible(&self) -> Vec<usize> {
+        let selection = TestSelection {
+            filter: (!self.filter.is_empty()).then(|| self.filter.clone()),
+            ..Default::default()
+        };
+        let selector = selection.selector(None);
+        (0..self.entries.len())
+            .filter(|&i| selector.matches(&self.entries[i].test))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn current(&self) -> Option<&Entry> {
+        self.visible().get(self.selected).map(|&i| &self.entries[i])
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+        if self.editing_filter {
+            match key.code {
+                KeyCode::Enter => self.editing_filter = false,
+                KeyCode::Esc => {
+                    self.editing_filter = false;
+                    self.filter.clear();
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => {}
+            }
+            self.selected = 0;
+            self.scroll = 0;
+            return Action::None;
+        }
+
+        let count = self.visible().len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select((self.selected + 1).min(count.saturating_sub(1))),
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => self.cycle_pane(1),
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => self.cycle_pane(Pane::ALL.len() - 1),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::Char('/') => self.editing_filter = true,
+            KeyCode::Enter | KeyCode::Char('r') => {
+                let target = self.visible().get(self.selected).copied();
+                return self.queue(target.into_iter().collect());
+            }
+            KeyCode::Char('a') => return self.queue(self.visible()),
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn select(&mut self, index: usize) {
+        if index != self.selected {
+            self.selected = index;
+            self.scroll = 0;
+        }
+    }
+
+    fn cycle_pane(&mut self, step: usize) {
+        let position = Pane::ALL.iter().position(|p| *p == self.pane).unwrap_or(0);
+        self.pane = Pane::ALL[(position + step) % Pane::ALL.len()];
+        self.scroll = 0;
+    }
+
+    /// One batch runs at a time
+    fn queue(&mut self, indices: Vec<usize>) -> Action {
+        if self.running || indices.is_empty() {
+            return Action::None;
+        }
+        for &i in &indices {
+            self.entries[i].status = Status::Queued;
+        }
+        self.running = true;
+        Action::Run(indices)
+    }
+
+    pub fn apply(&mut self, event: RunEvent) {
+        match event {
+            RunEvent::Started(i) => self.entries[i].status = Status::Running,
+            RunEvent::Finished(i, record) => {
+                self.entries[i].status = Status::of(&record.result);
+                self.entries[i].record = Some(*record);
+            }
+            RunEvent::Idle => {
+                self.running = false;
+                for entry in &mut self.entries {
+                    // Queued tests the worker did not get to keep their last outcome
+                    if entry.status == Status::Queued {
+                        entry.status = entry.record.as_ref().map_or(Status::NotRun, |r| Status::of(&r.result));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Pass, fail and remaining counts for the title bar
+    #[must_use]
+    pub fn counts(&self) -> (usize, usize, usize) {
+        let count = |status| self.entries.iter().filter(|e| e.status == status).count();
+        (count(Status::Passed) + count(Status::XFailed), count(Status::Failed), count(Status::NotRun))
+    }
+
+    /// Text of the detail pane for the selected test
+    #[must_use]
+    pub fn detail_lines(&self) -> Vec<String> {
+        let Some(entry) = self.current() else {
+            return vec!["No test matches the filter.".to_string()];
+        };
+        let Some(record) = &entry.record else {
+            let test = &entry.test;
+            return vec![
+                format!("{} {}", test.method, test.endpoint),
+                String::new(),
+                "Not run yet. Press Enter to run it.".to_string(),
+            ];
+        };
+        let result = &record.result;
+        if let Some(reason) = &result.skipped {
+            return vec![format!("Skipped: {reason}")];
+        }
+        match self.pane {
+            Pane::Request => self.request_lines(result),
+            Pane::Response => response_lines(result),
+            Pane::Assertions => assertion_lines(result),
+            Pane::Variables => variable_lines(record),
+        }
+    }
+
+    fn request_lines(&self, result: &TestResult) -> Vec<String> {
+        let Some(request) = &result.request else {
+            return vec!["No request was sent (a before hook failed).".to_string()];
+        };
+        let query: Vec<String> = request.params.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        let query = if query.is_empty() { String::new() } else { format!("?{}", query.join("&")) };
+        let mut lines = vec![format!("{} {}{}{query}", request.method, self.base_url, request.url)];
+        lines.push(String::new());
+        lines.push("Headers:".to_string());
+        lines.extend(request.headers.iter().map(|(k, v)| format!("  {k}: {v}")));
+        if let Some(body) = &request.body {
+            lines.push(String::new());
+            lines.push("Body:".to_string());
+            lines.extend(pretty(body));
+        }
+        lines
+    }
+}
+
+fn response_lines(result: &TestResult) -> Vec<String> {
+    let mut lines = vec![format!(
+        "Status: {} (expected {})",
+        result.actual_status_label(),
+        result.expected_status
+    )];
+    if let Some(timings) = &result.timings {
+        lines.push(format!("Time: {}ms", timings.total_ms()));
+    }
+    if !result.headers.is_empty() {
+        lines.push(String::new());
+        lines.push("Headers:".to_string());
+        let headers: BTreeMap<_, _> = result.headers.iter().collect();
+        lines.extend(headers.iter().map(|(k, v)| format!("  {k}: {v}")));
+    }
+    if let Some(body) = &result.response_body {
+        lines.push(String::new());
+        lines.push("Body:".to_string());
+        lines.extend(pretty(body));
+    }
+    lines
+}
+
+fn assertion_lines(result: &TestResult) -> Vec<String> {
+    let mut lines: Vec<String> = result
+        .checks
+        .iter()
+        .map(|c| format!("{} {}", if c.passed { "✓" } else { "✗" }, c.description))
+        .collect();
+    if let Some(reason) = &result.xfailed {
+        lines.push(format!("Expected failure: {reason}"));
+    }
+    if !result.messages.is_empty() {
+        lines.push(String::new());
+        lines.push("Messages:".to_string());
+        lines.extend(result.messages.iter().map(|m| format!("  - {m}")));
+    }
+    lines
+}
+
+/// Variables after the test, `+` when it set them and `~` when it changed them
+fn variable_lines(record: &TestRecord) -> Vec<String> {
+    let mut lines = vec!["Before:".to_string()];
+    lines.extend(record.variables_before.iter().map(|(k, v)| format!("  {k} = {v}")));
+    lines.push(String::new());
+    lines.push("After:".to_string());
+    lines.extend(record.variables_after.iter().map(|(k, v)| {
+        let marker = match record.variables_before.get(k) {
+            None => '+',
+            Some(before) if before != v => '~',
+            Some(_) => ' ',
+        };
+        format!("{marker} {k} = {v}")
+    }));
+    lines
+}
+
+fn pretty(value: &Value) -> Vec<String> {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        _ => serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string()),
+    };
+    text.lines().map(|l| format!("  {l}")).collect()
+}
@@ -0,0 +1,125 @@
+//! `catalyst tui`: run tests interactively and inspect each exchange.
+
+pub mod app;
+mod view;
+
+use crate::core::runner::{TestResult, TestRunner};
+use crate::engine::variables::load_env_files;
+use crate::http::client::HttpClient;
+use crate::models::suite::TestSuite;
+use app::{Action, App, RunEvent, TestRecord};
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How often the screen refreshes while waiting for keys
+const TICK: Duration = Duration::from_millis(50);
+
+/// Load the suite, run its setup, then hand the terminal to the interactive runner.
+/// Teardown runs after quitting, or right away when setup fails.
+///
+/// # Errors
+/// Returns a message if the suite cannot be loaded, setup fails or the terminal cannot be used
+pub async fn run_tui(mut runner: TestRunner, file: Option<String>, var: Option<String>) -> Result<(), String> {
+    load_env_files();
+    let suite = runner.load_suite(file.as_deref(), var)?;
+    let test_file_dir = PathBuf::from(file.as_deref().unwrap_or(".catalyst/tests.toml"))
+        .parent()
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    let client = HttpClient::new(&suite.config).with_base_dir(&test_file_dir);
+    if let Err(e) = runner.execute_setup(&suite, &client, &test_file_dir).await {
+        runner.execute_teardown(&suite, &client, &test_file_dir).await;
+        return Err(e);
+    }
+
+    let mut app = App::new(suite.tests.clone(), suite.config.base_url.clone());
+    let (run_tx, run_rx) = mpsc::unbounded_channel();
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let worker = tokio::spawn(worker(runner, suite, client, test_file_dir, run_rx, event_tx, cancelled.clone()));
+
+    let outcome = match ratatui::try_init() {
+        Ok(mut terminal) => {
+            let outcome = event_loop(&mut terminal, &mut app, &mut event_rx, &run_tx);
+            ratatui::restore();
+            outcome
+        }
+        Err(e) => Err(format!("Cannot start the terminal UI: {e}")),
+    };
+
+    // Let the test in flight finish, then tear down
+    cancelled.store(true, Ordering::Relaxed);
+    drop(run_tx);
+    let _ = worker.await;
+    outcome
+}
+
+fn event_loop(
+    terminal: &mut ratatui::DefaultTerminal,
+    app: &mut App,
+    events: &mut mpsc::UnboundedReceiver<RunEvent>,
+    batches: &mpsc::UnboundedSender<Vec<usize>>,
+) -> Result<(), String> {
+    loop {
+        while let Ok(event) = events.try_recv() {
+            app.apply(event);
+        }
+        terminal.draw(|frame| view::draw(frame, app)).map_err(|e| e.to_string())?;
+        match tokio::task::block_in_place(|| read_key(app)).map_err(|e| e.to_string())? {
+            Action::Quit => return Ok(()),
+            Action::Run(indices) => {
+                let _ = batches.send(indices);
+            }
+            Action::None => {}
+        }
+    }
+}
+
+fn read_key(app: &mut App) -> std::io::Result<Action> {
+    if !event::poll(TICK)? {
+        return Ok(Action::None);
+    }
+    match event::read()? {
+        Event::Key(key) if key.kind == KeyEventKind::Press => Ok(app.handle_key(key)),
+        _ => Ok(Action::None),
+    }
+}
+
+/// Runs the batches of tests it is sent, one at a time, reporting progress
+async fn worker(
+    mut runner: TestRunner,
+    suite: TestSuite,
+    client: HttpClient,
+    test_file_dir: PathBuf,
+    mut batches: mpsc::UnboundedReceiver<Vec<usize>>,
+    events: mpsc::UnboundedSender<RunEvent>,
+    cancelled: Arc<AtomicBool>,
+) {
+    let allowed_commands = suite.config.allowed_commands.as_deref();
+
+    while let Some(batch) = batches.recv().await {
+        for index in batch {
+            if cancelled.load(Ordering::Relaxed) {
+                break;
+            }
+            let test = &suite.tests[index];
+            let _ = events.send(RunEvent::Started(index));
+            let before = runner.variables.clone();
+            let mut results = runner.run_each(test, &client, &test_file_dir, allowed_commands).await;
+            // One row per test: the runs of a `for_each` are shown as its steps
+            let result = if results.len() == 1 {
+                results.remove(0)
+            } else {
+                TestResult::scenario(test, results)
+            };
+            let record = TestRecord::new(result, &before, &runner.variables);
+            let _ = events.send(RunEvent::Finished(index, Box::new(record)));
+        }
+        let _ = events.send(RunEvent::Idle);
+    }
+    runner.execute_teardown(&suite, &client, &test_file_dir).await;
+}
@@ -0,0 +1,105 @@
+use super::app::{App, Pane, Status};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Tabs};
+
+const HELP: &str = "↑/↓ select  Enter run  a run filtered  / filter  Tab pane  PgUp/PgDn scroll  q quit";
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [title, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [list, detail] = Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(body);
+
+    let (passed, failed, not_run) = app.counts();
+    let state = if app.running { "running" } else { "idle" };
+    frame.render_widget(
+        Line::from(vec![
+            Span::raw(" catalyst ").bold(),
+            Span::raw(format!("{} tests  ", app.entries.len())),
+            Span::raw(format!("{passed} passed  ")).green(),
+            Span::raw(format!("{failed} failed  ")).red(),
+            Span::raw(format!("{not_run} not run  ")),
+            Span::raw(format!("[{state}]")).dim(),
+        ]),
+        title,
+    );
+
+    draw_list(frame, app, list);
+    draw_detail(frame, app, detail);
+
+    let footer_line = if app.editing_filter || !app.filter.is_empty() {
+        let cursor = if app.editing_filter { "_" } else { "" };
+        Line::from(format!(" filter: {}{cursor}   (Enter keep, Esc clear)", app.filter))
+    } else {
+        Line::from(format!(" {HELP}")).dim()
+    };
+    frame.render_widget(footer_line, footer);
+}
+
+fn draw_list(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let items: Vec<ListItem> = app
+        .visible()
+        .into_iter()
+        .map(|i| {
+            let entry = &app.entries[i];
+            let style = Style::default().fg(status_color(entry.status));
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{} ", entry.status.symbol()), style),
+                Span::raw(entry.test.name.clone()),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Tests "))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_detail(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let [tabs_area, content] = Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(area);
+    let selected = Pane::ALL.iter().position(|p| *p == app.pane).unwrap_or(0);
+    frame.render_widget(
+        Tabs::new(Pane::ALL.iter().map(|p| p.title()))
+            .select(selected)
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)),
+        tabs_area,
+    );
+
+    let lines: Vec<Line> = app
+        .detail_lines()
+        .into_iter()
+        .map(|line| {
+            if line.starts_with('✓') {
+                Line::from(line).green()
+            } else if line.starts_with('✗') {
+                Line::from(line).red()
+            } else {
+                Line::from(line)
+            }
+        })
+        .collect();
+    let title = app.current().map(|e| format!(" {} ", e.test.name)).unwrap_or_default();
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .scroll((app.scroll, 0)),
+        content,
+    );
+}
+
+fn status_color(status: Status) -> Color {
+    match status {
+        Status::Passed => Color::Green,
+        Status::Failed => Color::Red,
+        Status::Skipped | Status::XFailed => Color::Yellow,
+        Status::Running | Status::Queued => Color::Cyan,
+        Status::NotRun => Color::Gray,
+    }
+}
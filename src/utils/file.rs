@@ -8,7 +8,7 @@ use std::path::Path;
 pub fn load_body_from_file(
     file_path: &str,
     test_file_dir: &Path,
-    vars: &HashMap<String, String>,
+    vars: &HashMap<String, Value>,
 ) -> CatalystResult<Value> {
     // Security check - ensure path doesn't contain path traversal
     if file_path.contains("..") {
@@ -93,8 +93,8 @@ mod tests {
         fs::write(&test_file, r#"{"user": "{{username}}", "id": {{user_id}}}"#).unwrap();
 
         let mut vars = HashMap::new();
-        vars.insert("username".to_string(), "john_doe".to_string());
-        vars.insert("user_id".to_string(), "123".to_string());
+        vars.insert("username".to_string(), Value::from("john_doe"));
+        vars.insert("user_id".to_string(), Value::from("123"));
 
         let result = load_body_from_file("test_vars.json", test_dir, &vars).unwrap();
 
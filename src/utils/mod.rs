@@ -1,6 +1,7 @@
 pub mod debug;
 pub mod file;
 pub mod json_path;
+pub mod secrets;
 pub mod string;
 
 pub use string::*;
@@ -0,0 +1,183 @@
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{LazyLock, RwLock};
+
+/// Placeholder printed instead of a secret value
+pub const MASK: &str = "****";
+
+/// Values shorter than this are never registered, so that masking `1` or `ok`
+/// cannot garble unrelated output
+const MIN_SECRET_LEN: usize = 4;
+
+const SENSITIVE_KEY_PARTS: &[&str] = &["token", "password", "secret", "key"];
+/// Variable names equal to, or ending in `_` followed by, one of these hold a secret
+const SECRET_NAME_SUFFIXES: &[&str] = &["token", "password", "secret", "api_key"];
+const SECRET_HEADERS: &[&str] = &["authorization", "proxy-authorization", "cookie", "set-cookie"];
+
+static SECRET_NAMES: LazyLock<RwLock<HashSet<String>>> = LazyLock::new(RwLock::default);
+static SECRET_VALUES: LazyLock<RwLock<Vec<String>>> = LazyLock::new(RwLock::default);
+
+/// Whether a variable or environment key looks like it holds a secret
+#[must_use]
+pub fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SENSITIVE_KEY_PARTS.iter().any(|part| key.contains(part))
+}
+
+/// Whether a header carries credentials (Authorization, Cookie, Set-Cookie)
+#[must_use]
+pub fn is_secret_header(name: &str) -> bool {
+    SECRET_HEADERS.contains(&name.to_lowercase().as_str())
+}
+
+/// Declare variable names from `[config].secrets`
+pub fn declare_secret_names(names: &[String]) {
+    SECRET_NAMES.write().unwrap().extend(names.iter().cloned());
+}
+
+/// Whether the value of variable `name` must be masked: declared in `[config].secrets`,
+/// or named like `token`, `api_key` or `*_password`
+#[must_use]
+pub fn is_secret_variable(name: &str) -> bool {
+    if SECRET_NAMES.read().unwrap().contains(name) {
+        return true;
+    }
+    let name = name.to_lowercase();
+    SECRET_NAME_SUFFIXES.iter().any(|suffix| {
+        name.strip_suffix(suffix)
+            .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('_'))
+    })
+}
+
+/// Register a value to be masked in all output
+pub fn register_secret(value: &str) {
+    let value = value.trim();
+    if value.len() < MIN_SECRET_LEN {
+        return;
+    }
+    let mut values = SECRET_VALUES.write().unwrap();
+    if !values.iter().any(|v| v == value) {
+        values.push(value.to_string());
+        // Longest first, so a secret containing another one is masked whole
+        values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+    }
+}
+
+/// Register the values of every secret variable
+pub fn register_variables(vars: &HashMap<String, Value>) {
+    for (name, value) in vars {
+        if is_secret_variable(name) {
+            match value {
+                Value::String(s) => register_secret(s),
+                Value::Null => {}
+                other => register_secret(&other.to_string()),
+            }
+        }
+    }
+}
+
+/// Register the credentials carried by a request or response header
+pub fn register_header(name: &str, value: &str) {
+    match name.to_lowercase().as_str() {
+        "authorization" | "proxy-authorization" => {
+            register_secret(value);
+            if let Some((_, credentials)) = value.split_once(' ') {
+                register_secret(credentials);
+            }
+        }
+        "cookie" => {
+            for pair in value.split(';') {
+                if let Some((_, cookie_value)) = pair.split_once('=') {
+                    register_secret(cookie_value);
+                }
+            }
+        }
+        "set-cookie" => {
+            for line in value.lines() {
+                let pair = line.split(';').next().unwrap_or_default();
+                if let Some((_, cookie_value)) = pair.split_once('=') {
+                    register_secret(cookie_value);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace every registered secret in `text` with the mask
+#[must_use]
+pub fn redact(text: &str) -> String {
+    let values = SECRET_VALUES.read().unwrap();
+    let mut result = text.to_string();
+    for value in values.iter() {
+        if result.contains(value.as_str()) {
+            result = result.replace(value.as_str(), MASK);
+        }
+    }
+    result
+}
+
+/// Redact every string inside a JSON value
+#[must_use]
+pub fn redact_json(json: &Value) -> Value {
+    match json {
+        Value::String(s) => Value::String(redact(s)),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), redact_json(v)))
+                .collect(),
+        ),
+        Value::Array(arr) => Value::Array(arr.iter().map(redact_json).collect()),
+        _ => json.clone(),
+    }
+}
+
+/// Redact header values, masking credential headers entirely
+#[must_use]
+pub fn redact_headers(headers: &HashMap<String, String>) -> HashMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if is_secret_header(name) {
+                mask_header_value(name, value)
+            } else {
+                redact(value)
+            };
+            (name.clone(), value)
+        })
+        .collect()
+}
+
+/// Mask a credential header, keeping the scheme, cookie names and cookie attributes readable
+fn mask_header_value(name: &str, value: &str) -> String {
+    match name.to_lowercase().as_str() {
+        "cookie" => value
+            .split(';')
+            .map(|pair| match pair.split_once('=') {
+                Some((cookie_name, _)) => format!("{cookie_name}={MASK}"),
+                None => pair.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(";"),
+        "set-cookie" => value
+            .lines()
+            .map(|line| {
+                let (pair, attributes) = line.split_once(';').unwrap_or((line, ""));
+                let masked = match pair.split_once('=') {
+                    Some((cookie_name, _)) => format!("{cookie_name}={MASK}"),
+                    None => pair.to_string(),
+                };
+                if attributes.is_empty() {
+                    masked
+                } else {
+                    format!("{masked};{attributes}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => match value.split_once(' ') {
+            Some((scheme, _)) => format!("{scheme} {MASK}"),
+            None => MASK.to_string(),
+        },
+    }
+}
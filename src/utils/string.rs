@@ -1,9 +1,11 @@
 use crate::debug;
 use regex::Regex;
+use serde_json::Value;
 use std::{collections::HashMap, fs, path::Path, sync::OnceLock};
 
 static ENV_REGEX: OnceLock<Regex> = OnceLock::new();
 static VAR_REGEX: OnceLock<Regex> = OnceLock::new();
+static PLACEHOLDER_REGEX: OnceLock<Regex> = OnceLock::new();
 
 fn env_pattern() -> &'static Regex {
     ENV_REGEX.get_or_init(|| Regex::new(r"\$\{\{([^}]+)\}\}").unwrap())
@@ -13,7 +15,55 @@ fn var_pattern() -> &'static Regex {
     VAR_REGEX.get_or_init(|| Regex::new(r"\{\{([^}]+)\}\}").unwrap())
 }
 
-pub fn replace_variables(input: &str, vars: &HashMap<String, String>) -> String {
+fn placeholder_pattern() -> &'static Regex {
+    PLACEHOLDER_REGEX.get_or_init(|| Regex::new(r"(\$?)\{\{([^}]+)\}\}").unwrap())
+}
+
+/// Return every placeholder still present in `input`, e.g. `{{token}}` or `${{HOME}}`
+#[must_use]
+pub fn find_unresolved_placeholders(input: &str) -> Vec<String> {
+    placeholder_pattern()
+        .find_iter(input)
+        .map(|m| m.as_str().to_string())
+        .collect()
+}
+
+/// Return the names of the `{{name}}` variables referenced by `input`.
+///
+/// Environment references (`${{NAME}}`) and file inclusions (`{{file:path}}`) are skipped.
+#[must_use]
+pub fn find_variable_references(input: &str) -> Vec<&str> {
+    placeholder_pattern()
+        .captures_iter(input)
+        .filter(|caps| caps[1].is_empty())
+        .filter_map(|caps| caps.get(2))
+        .map(|m| m.as_str())
+        .filter(|name| !name.starts_with("file:"))
+        .collect()
+}
+
+/// Render a variable value for interpolation into a string.
+///
+/// Strings are inserted as-is, everything else as compact JSON.
+#[must_use]
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+/// Return the variable name when `input` consists of a single `{{name}}` placeholder.
+#[must_use]
+pub fn sole_variable_name(input: &str) -> Option<&str> {
+    let name = input.strip_prefix("{{")?.strip_suffix("}}")?;
+    if name.is_empty() || name.contains(['{', '}']) || name.starts_with("file:") {
+        return None;
+    }
+    Some(name)
+}
+
+pub fn replace_variables(input: &str, vars: &HashMap<String, Value>) -> String {
     let with_env = env_pattern().replace_all(input, |caps: &regex::Captures| {
         let var_name = &caps[1];
         let value = std::env::var(var_name).unwrap_or_else(|_| caps[0].to_string());
@@ -26,7 +76,7 @@ pub fn replace_variables(input: &str, vars: &HashMap<String, String>) -> String
             let var_name = &caps[1];
             let value = vars
                 .get(var_name)
-                .map_or_else(|| caps[0].to_string(), |v| v.to_string());
+                .map_or_else(|| caps[0].to_string(), value_to_string);
             debug!("VAR replace: {} => {}", var_name, value);
             value
         })
@@ -42,7 +92,7 @@ pub fn replace_variables(input: &str, vars: &HashMap<String, String>) -> String
 /// May panic if regex compilation fails
 pub fn replace_variables_with_files(
     input: &str,
-    vars: &HashMap<String, String>,
+    vars: &HashMap<String, Value>,
     test_file_dir: &Path,
 ) -> Result<String, String> {
     let file_regex = Regex::new(r"\{\{file:([^}]+)\}\}").unwrap();
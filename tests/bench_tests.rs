@@ -0,0 +1,250 @@
+use catalyst::core::bench::{
+    BenchOptions, BenchStats, Comparison, ErrorKind, Metric, Sample, Threshold,
+    parse_bench_duration, run_bench,
+};
+use catalyst::output::BenchReportFormatter;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+mod common;
+use common::{Response, start_http_server};
+
+/// `/fail` answers 500, `/login` sets a session cookie that `/me` requires, `/hang`
+/// answers after a minute; everything else 200 with a small JSON body
+async fn start_server() -> String {
+    start_http_server(|request| match request.path.as_str() {
+        "/fail" => Response::json(json!({ "ok": false })).status(500),
+        "/login" => Response::json(json!({ "ok": true })).header("Set-Cookie", "sid=s-1; Path=/"),
+        "/me" if request.header("cookie") != Some("sid=s-1") => Response::json(json!({ "ok": false })).status(401),
+        "/hang" => Response::json(json!({ "ok": true })).delay(Duration::from_secs(60)),
+        _ => Response::json(json!({ "ok": true })),
+    })
+    .await
+}
+
+fn bench_options(file: &Path, duration: Duration) -> BenchOptions {
+    BenchOptions {
+        file: Some(file.to_string_lossy().to_string()),
+        filter: None,
+        concurrency: 2,
+        duration,
+        var: None,
+        profile: None,
+    }
+}
+
+fn sample(test: &str, ms: u64, expected_status: u16, status: u16, success: bool) -> Sample {
+    Sample {
+        test: test.to_string(),
+        latency: Duration::from_millis(ms),
+        expected_status,
+        status,
+        success,
+        timed_out: false,
+    }
+}
+
+#[test]
+fn test_threshold_parsing() {
+    let p95 = Threshold::parse("p95 < 300ms").unwrap();
+    assert_eq!(p95.metric, Metric::Percentile(95.0));
+    assert_eq!(p95.comparison, Comparison::Less);
+    assert_eq!(p95.value, 300.0);
+
+    assert_eq!(Threshold::parse("p99<1.5s").unwrap().value, 1500.0);
+    assert_eq!(Threshold::parse("mean <= 200").unwrap().value, 200.0);
+
+    let errors = Threshold::parse("error_rate <= 1%").unwrap();
+    assert_eq!(errors.metric, Metric::ErrorRate);
+    assert_eq!(errors.comparison, Comparison::LessOrEqual);
+    assert_eq!(errors.value, 1.0);
+
+    let rps = Threshold::parse("rps > 100").unwrap();
+    assert_eq!(rps.metric, Metric::Throughput);
+    assert_eq!(rps.comparison, Comparison::Greater);
+
+    assert!(Threshold::parse("latency < 3ms").is_err());
+    assert!(Threshold::parse("p95 300ms").is_err());
+    assert!(Threshold::parse("p95 < fast").is_err());
+}
+
+#[test]
+fn test_bench_duration_parsing() {
+    assert_eq!(parse_bench_duration("60").unwrap(), Duration::from_secs(60));
+    assert_eq!(
+        parse_bench_duration("60s").unwrap(),
+        Duration::from_secs(60)
+    );
+    assert_eq!(
+        parse_bench_duration("2m").unwrap(),
+        Duration::from_secs(120)
+    );
+    assert_eq!(
+        parse_bench_duration("500ms").unwrap(),
+        Duration::from_millis(500)
+    );
+    assert!(parse_bench_duration("ten").is_err());
+    assert_eq!(
+        parse_bench_duration("99999999999999999999999h").unwrap_err(),
+        "Invalid duration '99999999999999999999999h': out of range"
+    );
+}
+
+#[test]
+fn test_stats_percentiles_errors_and_thresholds() {
+    let mut samples: Vec<Sample> = (1..=100)
+        .map(|ms| sample("list", ms, 200, 200, true))
+        .collect();
+    samples[0] = sample("list", 1, 200, 503, false);
+    samples[1] = sample("list", 2, 200, 200, false);
+    samples[2] = sample("list", 3, 200, 0, false);
+
+    let stats = BenchStats::from_samples(&samples, Duration::from_secs(2));
+    assert_eq!(stats.requests, 100);
+    assert_eq!(stats.errors, 3);
+    assert_eq!(stats.percentile(50.0), Duration::from_millis(50));
+    assert_eq!(stats.percentile(90.0), Duration::from_millis(90));
+    assert_eq!(stats.percentile(99.0), Duration::from_millis(99));
+    assert_eq!(stats.throughput(), 50.0);
+    assert_eq!(stats.errors_by_kind[&ErrorKind::Status(503)], 1);
+    assert_eq!(stats.errors_by_kind[&ErrorKind::Assertion], 1);
+    assert_eq!(stats.errors_by_kind[&ErrorKind::Transport], 1);
+    assert_eq!(stats.histogram().iter().map(|(_, c)| c).sum::<usize>(), 100);
+
+    assert!(Threshold::parse("p95 < 100ms").unwrap().passes(&stats));
+    assert!(!Threshold::parse("p99 < 50ms").unwrap().passes(&stats));
+    assert!(Threshold::parse("error_rate < 5%").unwrap().passes(&stats));
+    assert!(!Threshold::parse("error_rate < 1%").unwrap().passes(&stats));
+
+    let report = BenchReportFormatter::new(true)
+        .format_report(&stats, &[Threshold::parse("p99 < 50ms").unwrap()]);
+    assert!(report.contains("p50 50ms  p90 90ms  p95 95ms  p99 99ms"));
+    assert!(report.contains("status 503"));
+    assert!(report.contains("✗ p99 < 50ms"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_bench_drives_filtered_tests_concurrently() {
+    let base_url = start_server().await;
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("tests.toml");
+    fs::write(
+        &file,
+        format!(
+            r#"
+            [config]
+            base_url = "{base_url}"
+
+            [[tests]]
+            name = "checkout ok"
+            method = "GET"
+            endpoint = "/ok"
+            expected_status = 200
+
+            [[tests]]
+            name = "checkout broken"
+            method = "GET"
+            endpoint = "/fail"
+            expected_status = 200
+
+            [[tests]]
+            name = "unrelated"
+            method = "GET"
+            endpoint = "/other"
+            expected_status = 200
+            "#
+        ),
+    )
+    .unwrap();
+
+    let stats = run_bench(&BenchOptions {
+        file: Some(file.to_string_lossy().to_string()),
+        filter: Some("checkout".to_string()),
+        concurrency: 4,
+        duration: Duration::from_millis(300),
+        var: None,
+        profile: None,
+    })
+    .await
+    .unwrap();
+
+    assert!(stats.requests >= 8, "{stats:?}");
+    assert!(!stats.per_test.contains_key("unrelated"));
+    let (ok_requests, ok_errors) = stats.per_test["checkout ok"];
+    assert!(ok_requests > 0);
+    assert_eq!(ok_errors, 0);
+    assert_eq!(stats.errors, stats.per_test["checkout broken"].0);
+    assert_eq!(stats.errors_by_kind[&ErrorKind::Status(500)], stats.errors);
+    assert!(stats.elapsed >= Duration::from_millis(300));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_bench_requests_share_the_setup_session() {
+    let base_url = start_server().await;
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("tests.toml");
+    fs::write(
+        &file,
+        format!(
+            r#"
+            [config]
+            base_url = "{base_url}"
+
+            [[setup_tests]]
+            name = "login"
+            method = "POST"
+            endpoint = "/login"
+            expected_status = 200
+
+            [[tests]]
+            name = "me"
+            method = "GET"
+            endpoint = "/me"
+            expected_status = 200
+            "#
+        ),
+    )
+    .unwrap();
+
+    let stats = run_bench(&bench_options(&file, Duration::from_millis(200))).await.unwrap();
+    assert!(stats.requests > 0, "{stats:?}");
+    assert_eq!(stats.errors, 0, "{stats:?}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_bench_stops_at_the_duration_despite_hung_requests() {
+    let base_url = start_server().await;
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("tests.toml");
+    fs::write(
+        &file,
+        format!(
+            r#"
+            [config]
+            base_url = "{base_url}"
+
+            [[tests]]
+            name = "ok"
+            method = "GET"
+            endpoint = "/ok"
+            expected_status = 200
+
+            [[tests]]
+            name = "hang"
+            method = "GET"
+            endpoint = "/hang"
+            expected_status = 200
+            "#
+        ),
+    )
+    .unwrap();
+
+    let options = bench_options(&file, Duration::from_millis(300));
+    let stats = tokio::time::timeout(Duration::from_secs(10), run_bench(&options)).await.expect("the bench overran").unwrap();
+    assert_eq!(stats.per_test["ok"], (2, 0), "{stats:?}");
+    assert!(!stats.per_test.contains_key("hang"));
+    assert!(stats.elapsed < Duration::from_secs(2), "{stats:?}");
+}
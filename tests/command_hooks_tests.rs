@@ -1,4 +1,5 @@
 use catalyst::models::command::{CommandStep, CaptureConfig};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
@@ -111,7 +112,7 @@ mod command_execution_tests {
         let cmd_result = result.unwrap();
         assert!(cmd_result.success);
         assert!(variables.contains_key("result"));
-        assert!(variables.get("result").unwrap().contains("test_output"));
+        assert!(variables["result"].as_str().unwrap().contains("test_output"));
         assert!(variables.contains_key("result_stderr"));
     }
 
@@ -148,7 +149,7 @@ mod command_execution_tests {
         let cmd_result = result.unwrap();
         assert!(cmd_result.success);
         assert!(variables.contains_key("env_result"));
-        assert!(variables.get("env_result").unwrap().contains("test_value"));
+        assert!(variables["env_result"].as_str().unwrap().contains("test_value"));
     }
 
     #[tokio::test]
@@ -203,7 +204,7 @@ mod command_execution_tests {
 
         // Test when condition is false - should skip execution
         let mut variables = HashMap::new();
-        variables.insert("run_test".to_string(), "false".to_string());
+        variables.insert("run_test".to_string(), Value::from("false"));
         let work_dir = Path::new(".");
         let allowed_commands: Option<&[String]> = None;
 
@@ -214,7 +215,7 @@ mod command_execution_tests {
         assert!(cmd_result.stdout.is_empty()); // Should not have run
 
         // Test when condition is true - should execute
-        variables.insert("run_test".to_string(), "true".to_string());
+        variables.insert("run_test".to_string(), Value::from("true"));
         let result = execute_command_step(&step, &mut variables, work_dir, allowed_commands).await;
         assert!(result.is_ok());
         let cmd_result = result.unwrap();
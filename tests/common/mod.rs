@@ -0,0 +1,205 @@
+//! Helpers shared by the integration tests; each test crate uses only some of them
+#![allow(dead_code)]
+
+use catalyst::checker::parse_tests;
+use catalyst::core::mock;
+use catalyst::models::test::Test;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Serve the `[[mocks]]` of the tests file `server`, written as `server.toml` in
+/// `dir`; returns the base URL of the mock server
+pub async fn start_mock_server(dir: impl AsRef<Path>, server: &str) -> String {
+    let file = dir.as_ref().join("server.toml");
+    fs::write(&file, server).unwrap();
+    serve_mocks(&file.to_string_lossy()).await
+}
+
+/// Serve the `[[mocks]]` of the tests file at `file`; returns the base URL of the mock server
+pub async fn serve_mocks(file: &str) -> String {
+    let suite = parse_tests(Some(file)).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(mock::serve(listener, mock::routes(&suite).unwrap()));
+    format!("http://{address}")
+}
+
+/// Write `tests` as `tests.toml` in `dir`, after a `[config]` with `base_url`;
+/// returns the path of the file
+pub fn write_suite(dir: impl AsRef<Path>, base_url: &str, tests: &str) -> String {
+    write_tests_file(dir, &format!("[config]\nbase_url = \"{base_url}\"\n\n{tests}"))
+}
+
+/// Write `content` as `tests.toml` in `dir`; returns the path of the file
+pub fn write_tests_file(dir: impl AsRef<Path>, content: &str) -> String {
+    let file = dir.as_ref().join("tests.toml");
+    fs::write(&file, content).unwrap();
+    file.to_string_lossy().to_string()
+}
+
+/// Parse a single `[[tests]]` entry
+pub fn test_from(toml_src: &str) -> Test {
+    toml::from_str(toml_src).unwrap()
+}
+
+/// A request read by [`read_request`]; header names are lowercase
+pub struct Request {
+    pub method: String,
+    /// The request target: path and query
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A response written by [`write_response`], after its `delay`
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub delay: Duration,
+}
+
+impl Response {
+    /// An empty response with `status`
+    pub fn new(status: u16) -> Self {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: String::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// `200 OK` with `body` as JSON
+    pub fn json(body: Value) -> Self {
+        Response {
+            body: body.to_string(),
+            ..Response::new(200)
+        }
+        .header("Content-Type", "application/json")
+    }
+
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// Serve HTTP/1.1 on a free loopback port, answering every request with
+/// `handler`; returns the base URL of the server
+pub async fn start_http_server(handler: impl Fn(&Request) -> Response + Send + Sync + 'static) -> String {
+    let handler = Arc::new(handler);
+    let address = start_tcp_server(move |mut socket| {
+        let handler = Arc::clone(&handler);
+        async move {
+            let request = read_request(&mut socket).await;
+            write_response(&mut socket, &handler(&request)).await;
+        }
+    })
+    .await;
+    format!("http://{address}")
+}
+
+/// Accept connections on a free loopback port, each handled by `handle` on a task
+/// of its own, for servers that answer other than with a [`Response`]; returns the
+/// address of the listener
+pub async fn start_tcp_server<F, Fut>(handle: F) -> SocketAddr
+where
+    F: Fn(TcpStream) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(handle(socket));
+        }
+    });
+    address
+}
+
+/// Read one request: the head up to the blank line, then `Content-Length` bytes of body
+pub async fn read_request<S: AsyncRead + Unpin>(stream: &mut S) -> Request {
+    let mut data = Vec::new();
+    let mut chunk = [0u8; 1024];
+    let head_end = loop {
+        if let Some(end) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => break data.len(),
+            Ok(n) => data.extend_from_slice(&chunk[..n]),
+        }
+    };
+
+    let head = String::from_utf8_lossy(&data[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut start = lines.next().unwrap_or_default().split_whitespace();
+    let method = start.next().unwrap_or_default().to_string();
+    let path = start.next().unwrap_or("/").to_string();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+        .collect();
+    let mut request = Request {
+        method,
+        path,
+        headers,
+        body: data[head_end..].to_vec(),
+    };
+
+    let length = request
+        .header("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    while request.body.len() < length {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => request.body.extend_from_slice(&chunk[..n]),
+        }
+    }
+    request
+}
+
+/// Write `response` with a `Content-Length` and `Connection: close`, then close the stream
+pub async fn write_response<S: AsyncWrite + Unpin>(stream: &mut S, response: &Response) {
+    tokio::time::sleep(response.delay).await;
+    let reason = StatusCode::from_u16(response.status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or_default();
+    let mut head = format!("HTTP/1.1 {} {reason}\r\n", response.status);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n", response.body.len()));
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(response.body.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
@@ -0,0 +1,147 @@
+use catalyst::engine::execution;
+use catalyst::http::client::HttpClient;
+use catalyst::http::cookies::{parse_set_cookie, parse_set_cookies};
+use catalyst::models::config::Config;
+use catalyst::models::test::Test;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+
+mod common;
+use common::{Response, start_http_server, test_from};
+
+/// Minimal HTTP/1.1 server: `/login?user=NAME` sets cookies, `/whoami` echoes the Cookie header
+async fn start_server() -> String {
+    start_http_server(|request| match request.path.strip_prefix("/login?user=") {
+        Some(user) => Response::json(json!({ "ok": true }))
+            .header("Set-Cookie", &format!("sid={user}-token==; Path=/; HttpOnly; SameSite=Strict"))
+            .header("Set-Cookie", "theme=dark; Path=/"),
+        None => Response::json(json!({ "cookie": request.header("cookie").unwrap_or_default() })),
+    })
+    .await
+}
+
+async fn run(client: &HttpClient, test: &Test, vars: &mut HashMap<String, Value>) -> execution::ExecutionResult {
+    execution::run(client, test, Path::new("."), vars, false).await
+}
+
+#[test]
+fn test_parse_set_cookie_attributes() {
+    let cookie =
+        parse_set_cookie("sid=a=b==; Domain=.example.com; Path=/api; Secure; HttpOnly; SameSite=Lax; Max-Age=60")
+            .unwrap();
+    assert_eq!(cookie.name, "sid");
+    assert_eq!(cookie.value, "a=b==");
+    assert_eq!(cookie.domain.as_deref(), Some("example.com"));
+    assert_eq!(cookie.path.as_deref(), Some("/api"));
+    assert_eq!(cookie.same_site.as_deref(), Some("Lax"));
+    assert_eq!(cookie.max_age, Some(60));
+    assert!(cookie.secure);
+    assert!(cookie.http_only);
+
+    let cookies = parse_set_cookies("a=1; Path=/\nb=2; Expires=Wed, 21 Oct 2015 07:28:00 GMT");
+    assert_eq!(cookies.len(), 2);
+    assert_eq!(cookies[1].name, "b");
+    assert_eq!(cookies[1].expires.as_deref(), Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+}
+
+#[tokio::test]
+async fn test_sessions_have_isolated_cookie_jars() {
+    let config = Config {
+        base_url: start_server().await,
+        ..Default::default()
+    };
+    let client = HttpClient::new(&config);
+    let mut vars = HashMap::new();
+
+    for (session, user) in [("admin", "root"), ("viewer", "guest")] {
+        let login = test_from(&format!(
+            r#"
+name = "Login {user}"
+method = "GET"
+endpoint = "/login?user={user}"
+expected_status = 200
+session = "{session}"
+get_cookie = {{ "sid" = "{session}_sid", "theme" = "{session}_theme" }}
+"#
+        ));
+        let result = run(&client, &login, &mut vars).await;
+        assert!(result.success, "{:?}", result.errors);
+    }
+
+    assert_eq!(vars["admin_sid"], json!("root-token=="));
+    assert_eq!(vars["admin_theme"], json!("dark"));
+    assert_eq!(vars["viewer_sid"], json!("guest-token=="));
+
+    for (session, user) in [("admin", "root"), ("viewer", "guest")] {
+        let whoami = test_from(&format!(
+            r#"
+name = "Who am I"
+method = "GET"
+endpoint = "/whoami"
+expected_status = 200
+session = "{session}"
+"#
+        ));
+        let result = run(&client, &whoami, &mut vars).await;
+        let cookie = result.body.unwrap()["cookie"].as_str().unwrap().to_string();
+        assert!(cookie.contains(&format!("sid={user}-token==")), "{cookie}");
+        assert!(cookie.contains("theme=dark"), "{cookie}");
+    }
+
+    let anonymous = test_from(
+        r#"
+name = "Anonymous"
+method = "GET"
+endpoint = "/whoami"
+expected_status = 200
+"#,
+    );
+    let result = run(&client, &anonymous, &mut vars).await;
+    assert_eq!(result.body.unwrap()["cookie"], json!(""));
+}
+
+#[tokio::test]
+async fn test_cookie_attribute_assertions() {
+    let config = Config {
+        base_url: start_server().await,
+        ..Default::default()
+    };
+    let client = HttpClient::new(&config);
+    let mut vars = HashMap::new();
+
+    let passing = test_from(
+        r#"
+name = "Login"
+method = "GET"
+endpoint = "/login?user=alice"
+expected_status = 200
+expected_cookies = { sid = { http_only = true, same_site = "strict", path = "/" }, theme = { value = "dark" } }
+"#,
+    );
+    let result = run(&client, &passing, &mut vars).await;
+    assert!(result.success, "{:?}", result.errors);
+
+    let failing = test_from(
+        r#"
+name = "Login"
+method = "GET"
+endpoint = "/login?user=alice"
+expected_status = 200
+expected_cookies = { theme = { value = "DARK", http_only = true, secure = true }, missing = {} }
+"#,
+    );
+    let result = run(&client, &failing, &mut vars).await;
+    assert!(!result.success);
+    let mut errors = result.errors.clone();
+    errors.sort();
+    assert_eq!(
+        errors,
+        vec![
+            "Cookie 'missing' was not set".to_string(),
+            "Cookie 'theme' HttpOnly is false, expected true".to_string(),
+            "Cookie 'theme' Secure is false, expected true".to_string(),
+            "Cookie 'theme' value is dark, expected DARK".to_string(),
+        ]
+    );
+}
@@ -0,0 +1,147 @@
+use catalyst::core::runner::TestRunner;
+use catalyst::engine::dataset::{expand_tests, load_data_file};
+use catalyst::models::suite::TestSuite;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::{Response, start_http_server, write_tests_file};
+
+/// Echoes the request line's path as `{"path": ...}`
+async fn start_server() -> String {
+    start_http_server(|request| Response::json(json!({ "path": request.path }))).await
+}
+
+fn suite_from(toml_src: &str) -> TestSuite {
+    toml::from_str(toml_src).unwrap()
+}
+
+const MATRIX_SUITE: &str = r#"
+[config]
+base_url = "http://localhost"
+
+[[tests]]
+name = "create user"
+method = "POST"
+endpoint = "/users/{{role}}"
+query_params = { locale = "{{locale}}" }
+expected_status = 200
+matrix = { role = ["admin", "user"], locale = ["en", "fr"] }
+
+[[tests]]
+name = "plain"
+method = "GET"
+endpoint = "/"
+expected_status = 200
+"#;
+
+#[test]
+fn test_matrix_expands_in_declaration_order() {
+    let suite = suite_from(MATRIX_SUITE);
+    let tests = expand_tests(&suite.tests, Path::new(".")).unwrap();
+
+    let names: Vec<&str> = tests.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "create user [role=admin, locale=en]",
+            "create user [role=admin, locale=fr]",
+            "create user [role=user, locale=en]",
+            "create user [role=user, locale=fr]",
+            "plain",
+        ]
+    );
+    assert_eq!(
+        tests[1].parameters,
+        vec![
+            ("role".to_string(), json!("admin")),
+            ("locale".to_string(), json!("fr"))
+        ]
+    );
+    assert!(tests[4].parameters.is_empty());
+}
+
+#[test]
+fn test_csv_and_json_data_files() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("cases.csv"), "email, role\na@example.com, admin\nb@example.com, user\n").unwrap();
+    fs::write(
+        dir.path().join("cases.json"),
+        r#"[{"qty": 2, "sku": "A-1"}, {"qty": 0, "sku": "B-2"}]"#,
+    )
+    .unwrap();
+
+    let csv = load_data_file("cases.csv", dir.path()).unwrap();
+    assert_eq!(csv.len(), 2);
+    assert_eq!(
+        csv[0],
+        vec![
+            ("email".to_string(), json!("a@example.com")),
+            ("role".to_string(), json!("admin"))
+        ]
+    );
+
+    // JSON rows keep their types and key order
+    let rows = load_data_file("cases.json", dir.path()).unwrap();
+    assert_eq!(rows[1], vec![("qty".to_string(), json!(0)), ("sku".to_string(), json!("B-2"))]);
+
+    assert!(load_data_file("../cases.csv", dir.path()).is_err());
+    let absolute = dir.path().join("cases.csv");
+    assert!(load_data_file(&absolute.to_string_lossy(), dir.path()).is_err());
+    assert!(load_data_file("missing.csv", dir.path()).is_err());
+
+    let both = suite_from(
+        r#"
+        [config]
+        base_url = "http://localhost"
+
+        [[tests]]
+        name = "ambiguous"
+        method = "GET"
+        endpoint = "/"
+        expected_status = 200
+        matrix = { a = [1] }
+        data_file = "cases.csv"
+        "#,
+    );
+    let err = expand_tests(&both.tests, dir.path()).unwrap_err();
+    assert!(err.to_string().contains("both `matrix` and `data_file`"), "{err}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_runner_binds_rows_and_filters_instances() {
+    let base_url = start_server().await;
+    let dir = TempDir::new().unwrap();
+    let file = write_tests_file(&dir, &MATRIX_SUITE.replace("http://localhost", &base_url));
+
+    let mut runner = TestRunner::new(true);
+    runner
+        .execute_tests(
+            Some("role=admin".to_string()),
+            false,
+            Some(file),
+            None,
+            true,
+        )
+        .await;
+
+    let paths: Vec<(&str, Option<&Value>)> = runner
+        .results
+        .iter()
+        .map(|r| (r.name.as_str(), r.response_body.as_ref().and_then(|b| b.get("path"))))
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            ("create user [role=admin, locale=en]", Some(&json!("/users/admin?locale=en"))),
+            ("create user [role=admin, locale=fr]", Some(&json!("/users/admin?locale=fr"))),
+        ]
+    );
+    assert!(runner.results.iter().all(|r| r.success));
+
+    // Row values are only in scope while their instance runs
+    assert!(!runner.variables.contains_key("role"));
+    assert!(!runner.variables.contains_key("locale"));
+}
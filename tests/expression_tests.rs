@@ -0,0 +1,131 @@
+use catalyst::core::runner::TestRunner;
+use catalyst::engine::expression::{Condition, ConditionContext, LastResponse, evaluate};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::{Response, start_http_server};
+
+fn check(condition: &str, variables: &HashMap<String, Value>, response: Option<&LastResponse>) -> bool {
+    let context = ConditionContext { variables, response };
+    evaluate(condition, &context).unwrap_or_else(|e| panic!("{condition}: {e}"))
+}
+
+#[test]
+fn test_operators_variables_and_response() {
+    let vars = HashMap::from([
+        ("env".to_string(), Value::from("prod")),
+        ("count".to_string(), Value::from(12)),
+        ("name".to_string(), Value::from("John Smith")),
+        ("roles".to_string(), json!(["admin", "dev"])),
+    ]);
+    let response = LastResponse {
+        status: 503,
+        body: Some(json!({ "error": { "code": "CONFLICT" }, "items": [{ "id": 7 }] })),
+    };
+
+    let holds = |c: &str| check(c, &vars, Some(&response));
+    assert!(holds("{{env}} == prod && {{count}} > 9"));
+    assert!(holds("\"{{env}}\" == \"prod\""));
+    assert!(holds("{{count}} >= \"12\" && !({{count}} < 10)"));
+    assert!(holds("{{name}} == John Smith"));
+    assert!(holds("{{env}} == staging || {{name}} contains Smith"));
+    assert!(holds("{{roles}} contains admin && !({{roles}} contains ops)"));
+    assert!(holds("{{name}} =~ '^John\\s+S'"));
+    assert!(holds("defined(env) && !defined(token)"));
+    assert!(holds("status >= 500 && status != 504"));
+    assert!(holds("body.$.error.code == CONFLICT && body.$.items[0].id == 7"));
+    assert!(holds("body contains error"));
+    assert!(!holds("body.$.missing"));
+    assert!(!holds("{{undefined}} == prod"));
+
+    // Without a response, `status` and `body` are null
+    assert!(check("status == null && !body", &vars, None));
+}
+
+#[test]
+fn test_parse_errors_point_at_the_problem() {
+    let error = |c: &str| Condition::parse(c).unwrap_err();
+    assert_eq!(error("status == 200 &&"), "Expected a value at the end of the condition");
+    assert_eq!(error("(status == 200"), "Missing `)` for the `(` at column 1");
+    assert_eq!(error("status = 200"), "Unexpected `=` at column 8");
+    assert_eq!(error("status == 200)"), "Unexpected `)` at column 14");
+    assert_eq!(error("{{env}} == \"prod"), "Unterminated string starting at column 12");
+    assert_eq!(error("== prod"), "Expected a value but found `==` at column 1");
+    assert!(error("{{name}} =~ \"[a-\"").starts_with("Invalid regex `[a-`"));
+    assert_eq!(error(""), "Empty condition");
+}
+
+/// `/items` answers `{"items": []}` with 200, everything else 503
+async fn start_server() -> String {
+    start_http_server(|request| {
+        if request.path == "/items" {
+            Response::json(json!({ "items": [] }))
+        } else {
+            Response::json(json!({ "error": "busy" })).status(503)
+        }
+    })
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_conditions_read_the_last_response() {
+    let base_url = start_server().await;
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("tests.toml");
+    fs::write(
+        &file,
+        format!(
+            r#"
+[config]
+base_url = "{base_url}"
+
+[[tests]]
+name = "list items"
+method = "GET"
+endpoint = "/items"
+expected_status = 200
+
+[[tests.after]]
+run = "echo"
+args = ["empty"]
+on = "status == 200 && body.$.items == []"
+capture = {{ var = "seen" }}
+
+[[tests.after]]
+run = "echo"
+args = ["error"]
+on = "status >= 500"
+capture = {{ var = "alerted" }}
+
+[[tests]]
+name = "show item"
+method = "GET"
+endpoint = "/items/1"
+expected_status = 200
+skip_if = "body.$.items == [] || !defined(seen)"
+
+[[tests]]
+name = "busy"
+method = "GET"
+endpoint = "/busy"
+expected_status = 503
+skip_if = "{{{{seen}}}} =~ '^error'"
+"#
+        ),
+    )
+    .unwrap();
+
+    let mut runner = TestRunner::new(true);
+    runner
+        .execute_tests(None, false, Some(file.to_string_lossy().to_string()), None, true)
+        .await;
+
+    assert_eq!(runner.variables["seen"], "empty\n");
+    assert!(!runner.variables.contains_key("alerted"));
+    let skipped: Vec<Option<&str>> = runner.results.iter().map(|r| r.skipped.as_deref()).collect();
+    assert_eq!(skipped, vec![None, Some("skip_if body.$.items == [] || !defined(seen)"), None]);
+    assert_eq!(runner.last_response.as_ref().map(|r| r.status), Some(503));
+}
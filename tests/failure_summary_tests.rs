@@ -18,6 +18,7 @@ async fn test_failure_summary_display() {
         messages: vec![],
         method: "GET".to_string(),
         endpoint: "/api/users".to_string(),
+        ..Default::default()
     });
     
     runner.results.push(TestResult {
@@ -30,6 +31,7 @@ async fn test_failure_summary_display() {
         messages: vec!["Status mismatch".to_string(), "Body validation failed".to_string()],
         method: "POST".to_string(),
         endpoint: "/api/users/123".to_string(),
+        ..Default::default()
     });
     
     runner.results.push(TestResult {
@@ -42,6 +44,7 @@ async fn test_failure_summary_display() {
         messages: vec![],
         method: "POST".to_string(),
         endpoint: "/api/users".to_string(),
+        ..Default::default()
     });
     
     // Verify that we have the expected mix of results
@@ -83,6 +86,7 @@ async fn test_no_fail_summary_flag() {
         messages: vec!["Server error occurred".to_string()],
         method: "GET".to_string(),
         endpoint: "/api/status".to_string(),
+        ..Default::default()
     });
     
     // When no_fail_summary is true, failure details should not be displayed
@@ -125,6 +129,7 @@ async fn test_test_result_structure() {
         messages: vec![],
         method: "GET".to_string(),
         endpoint: "/api/status".to_string(),
+        ..Default::default()
     };
     
     assert_eq!(result.name, "Test API call");
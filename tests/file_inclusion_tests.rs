@@ -92,7 +92,7 @@ mod tests {
     fn test_replace_variables_with_files_combined_with_vars() {
         let temp_dir = setup_test_files();
         let mut vars = HashMap::new();
-        vars.insert("username".to_string(), "john".to_string());
+        vars.insert("username".to_string(), Value::from("john"));
 
         let input = "User: {{username}}, Content: {{file:test.txt}}";
         let result = replace_variables_with_files(input, &vars, &temp_dir).unwrap();
@@ -187,7 +187,7 @@ mod tests {
     fn test_replace_variables_in_json_with_files_nested() {
         let temp_dir = setup_test_files();
         let mut vars = HashMap::new();
-        vars.insert("key".to_string(), "value".to_string());
+        vars.insert("key".to_string(), Value::from("value"));
 
         let json = json!({
             "level1": {
@@ -220,7 +220,7 @@ mod tests {
     fn test_no_file_references() {
         let temp_dir = setup_test_files();
         let mut vars = HashMap::new();
-        vars.insert("key".to_string(), "value".to_string());
+        vars.insert("key".to_string(), Value::from("value"));
 
         let json = json!({
             "normal": "text",
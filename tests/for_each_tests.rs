@@ -0,0 +1,186 @@
+use catalyst::core::runner::TestRunner;
+use catalyst::http::client::HttpClient;
+use serde_json::json;
+use tempfile::TempDir;
+
+mod common;
+use common::{start_mock_server, write_suite};
+
+/// Three fixtures; fixture 2 cannot be deleted
+const SERVER: &str = r#"
+[config]
+base_url = "http://localhost"
+
+[[mocks]]
+method = "GET"
+path = "/fixtures"
+response_body = { items = [{ id = 1, name = "a" }, { id = 2, name = "b" }, { id = 3, name = "c" }] }
+
+[[mocks]]
+method = "DELETE"
+path = "/fixtures/2"
+status = 409
+
+[[mocks]]
+method = "DELETE"
+path = "/fixtures/{{id}}"
+status = 204
+
+[[mocks]]
+method = "PUT"
+path = "/fixtures/{{id}}"
+headers = { "x-position" = "{{position}}" }
+response_body = { saved = true }
+"#;
+
+#[tokio::test]
+async fn test_for_each_runs_once_per_stored_item() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_mock_server(&dir, SERVER).await;
+    let file = write_suite(
+        &dir,
+        &base_url,
+        r#"
+[[tests]]
+name = "list fixtures"
+method = "GET"
+endpoint = "/fixtures"
+expected_status = 200
+store = { fixtures = "$.items", fixture_ids = "$.items[*].id" }
+
+[[tests]]
+name = "save fixture"
+for_each = "$.fixtures[*]"
+method = "PUT"
+endpoint = "/fixtures/{{index}}"
+headers = { "X-Position" = "{{index}}" }
+body = { fixture = "{{item}}" }
+expected_status = 200
+
+[[tests]]
+name = "delete fixture"
+for_each = "$.fixture_ids"
+method = "DELETE"
+endpoint = "/fixtures/{{item}}"
+expected_status = 204
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner.load_suite(Some(&file), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    let mut results = Vec::new();
+    for test in &suite.tests {
+        results.extend(runner.run_each(test, &client, dir.path(), None).await);
+    }
+
+    let outcomes: Vec<_> = results.iter().map(|r| (r.name.as_str(), r.success)).collect();
+    assert_eq!(
+        outcomes,
+        [
+            ("list fixtures", true),
+            ("save fixture [index=0]", true),
+            ("save fixture [index=1]", true),
+            ("save fixture [index=2]", true),
+            ("delete fixture [index=0]", true),
+            ("delete fixture [index=1]", false),
+            ("delete fixture [index=2]", true),
+        ]
+    );
+    // `{{item}}` keeps its type when it is the whole string
+    let request = results[2].request.as_ref().unwrap();
+    assert_eq!(request.body, Some(json!({ "fixture": { "id": 2, "name": "b" } })));
+    assert_eq!(results[5].endpoint, "/fixtures/{{item}}");
+    assert_eq!(results[5].messages, ["Status 409, expected 204"]);
+
+    assert!(!runner.variables.contains_key("item"));
+    assert!(!runner.variables.contains_key("index"));
+}
+
+#[tokio::test]
+async fn test_for_each_in_scenario_steps_and_unusable_paths() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_mock_server(&dir, SERVER).await;
+    let file = write_suite(
+        &dir,
+        &base_url,
+        r#"
+[[tests]]
+name = "clean up"
+
+[[tests.steps]]
+name = "list"
+method = "GET"
+endpoint = "/fixtures"
+expected_status = 200
+store = { ids = "$.items[*].id" }
+
+[[tests.steps]]
+name = "delete"
+for_each = "$.ids[*]"
+method = "DELETE"
+endpoint = "/fixtures/{{item}}"
+expected_status = 204
+
+[[tests.steps]]
+name = "after delete"
+method = "GET"
+endpoint = "/fixtures"
+expected_status = 200
+
+[[tests]]
+name = "unset"
+for_each = "$.missing[*]"
+method = "GET"
+endpoint = "/fixtures"
+expected_status = 200
+
+[[tests]]
+name = "not an array"
+for_each = "$.count"
+method = "GET"
+endpoint = "/fixtures"
+expected_status = 200
+
+[[tests]]
+name = "empty"
+for_each = "$.none[*]"
+method = "GET"
+endpoint = "/fixtures"
+expected_status = 200
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner.load_suite(Some(&file), None).unwrap();
+    runner.variables.insert("count".to_string(), json!(3));
+    runner.variables.insert("none".to_string(), json!([]));
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+
+    let scenario = runner.run_each(&suite.tests[0], &client, dir.path(), None).await;
+    assert_eq!(scenario.len(), 1);
+    let steps: Vec<_> = scenario[0].steps.iter().map(|s| (s.name.as_str(), s.success)).collect();
+    assert_eq!(
+        steps,
+        [
+            ("list", true),
+            ("delete [index=0]", true),
+            ("delete [index=1]", false),
+            ("delete [index=2]", true),
+            ("after delete", true),
+        ]
+    );
+    assert_eq!(scenario[0].steps[4].skipped.as_deref(), Some("step 3 failed"));
+    assert_eq!(scenario[0].messages, ["Step 3 `delete [index=1]`: Status 409, expected 204"]);
+
+    let unset = runner.run_each(&suite.tests[1], &client, dir.path(), None).await;
+    assert_eq!(unset[0].messages, ["for_each $.missing[*]: `missing` is not set"]);
+    assert!(!unset[0].success);
+
+    let not_array = runner.run_each(&suite.tests[2], &client, dir.path(), None).await;
+    assert_eq!(not_array[0].messages, ["for_each $.count is 3, not an array"]);
+
+    let empty = runner.run_each(&suite.tests[3], &client, dir.path(), None).await;
+    assert_eq!(empty.len(), 1);
+    assert_eq!(empty[0].skipped.as_deref(), Some("for_each $.none[*] selected no items"));
+}
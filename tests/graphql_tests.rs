@@ -0,0 +1,133 @@
+use catalyst::core::runner::TestRunner;
+use catalyst::engine::graphql::{envelope, error_messages};
+use catalyst::http::client::HttpClient;
+use catalyst::models::test::GraphqlRequest;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::{start_mock_server, write_tests_file};
+
+const USER_QUERY: &str = "query GetUser($id: ID!) {\n  user(id: $id) { name }\n}\n";
+
+/// A GraphQL server made of mocks matching the operations the tests send
+const SERVER: &str = r#"
+[config]
+base_url = "http://localhost"
+
+[[mocks]]
+method = "POST"
+path = "/graphql"
+body = { operationName = "GetUser", variables = { id = "7" }, query = "query GetUser($id: ID!) {\n  user(id: $id) { name }\n}\n" }
+response_body = { data = { user = { name = "ada", roles = ["admin"] } } }
+
+[[mocks]]
+method = "POST"
+path = "/graphql"
+body = { operationName = "DeleteUser" }
+response_body = { data = { deleteUser = false }, errors = [{ message = "Not allowed", path = ["deleteUser"] }] }
+"#;
+
+const SUITE: &str = r#"
+[config]
+base_url = "http://localhost"
+
+[[tests]]
+name = "get user"
+endpoint = "/graphql"
+graphql = { query = "queries/user.graphql", variables = { id = "{{user_id}}" }, operation_name = "GetUser" }
+expected_status = 200
+assertions = [
+  { type = "PathContains", value = ["$.data.user", { name = "ada" }] },
+  { type = "PathContains", value = ["$.data.user.roles", ["admin"]] },
+]
+store = { name = "$.data.user.name" }
+
+[[tests]]
+name = "delete user"
+endpoint = "/graphql"
+graphql = { query = "mutation DeleteUser { deleteUser(id: 7) }", operation_name = "DeleteUser" }
+expected_status = 200
+
+[[tests]]
+name = "delete user is refused"
+endpoint = "/graphql"
+graphql = { query = "mutation DeleteUser { deleteUser(id: 7) }", operation_name = "DeleteUser", expect_errors = true }
+expected_status = 200
+assertions = [{ type = "PathContains", value = ["$.errors[0]", { message = "Not allowed" }] }]
+
+[[tests]]
+name = "get user has errors"
+endpoint = "/graphql"
+graphql = { query = "queries/user.graphql", variables = { id = "{{user_id}}" }, operation_name = "GetUser", expect_errors = true }
+expected_status = 200
+"#;
+
+#[test]
+fn test_envelope_loads_query_files_safely() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir(dir.path().join("queries")).unwrap();
+    fs::write(dir.path().join("queries/user.graphql"), USER_QUERY).unwrap();
+    let vars = HashMap::from([("user_id".to_string(), json!(7))]);
+
+    let request = GraphqlRequest {
+        query: "queries/user.graphql".to_string(),
+        variables: Some(json!({ "id": "{{user_id}}", "tag": "u{{user_id}}" })),
+        operation_name: Some("GetUser".to_string()),
+        expect_errors: None,
+    };
+    assert_eq!(
+        envelope(&request, dir.path(), &vars).unwrap(),
+        json!({ "query": USER_QUERY, "variables": { "id": 7, "tag": "u7" }, "operationName": "GetUser" })
+    );
+
+    let inline = GraphqlRequest {
+        query: "{ user(id: {{user_id}}) { name } }".to_string(),
+        variables: None,
+        operation_name: None,
+        expect_errors: None,
+    };
+    assert_eq!(inline.query_file(), None);
+    assert_eq!(envelope(&inline, dir.path(), &vars).unwrap(), json!({ "query": "{ user(id: 7) { name } }" }));
+
+    let escaping = GraphqlRequest { query: "../secret.graphql".to_string(), ..inline };
+    assert!(envelope(&escaping, dir.path(), &vars).is_err());
+
+    assert_eq!(
+        error_messages(&json!({ "errors": [{ "message": "Not allowed" }, { "code": 1 }] })),
+        vec!["Not allowed".to_string(), r#"{"code":1}"#.to_string()]
+    );
+    assert!(error_messages(&json!({ "data": {}, "errors": [] })).is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_graphql_tests_fail_on_unexpected_errors() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_mock_server(&dir, SERVER).await;
+    fs::create_dir(dir.path().join("queries")).unwrap();
+    fs::write(dir.path().join("queries/user.graphql"), USER_QUERY).unwrap();
+    let file = write_tests_file(&dir, &SUITE.replace("http://localhost", &base_url));
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner
+        .load_suite(Some(&file), Some("user_id=7".to_string()))
+        .unwrap();
+    assert!(suite.tests.iter().all(|t| t.method == "POST"));
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    let mut results = Vec::new();
+    for test in &suite.tests {
+        results.push(runner.run_test(test, &client, dir.path(), None).await);
+    }
+
+    assert!(results[0].success, "{:?}", results[0].messages);
+    assert_eq!(runner.variables["name"], json!("ada"));
+    assert_eq!(results[0].request.as_ref().unwrap().body.as_ref().unwrap()["variables"], json!({ "id": "7" }));
+
+    assert!(!results[1].success);
+    assert!(results[1].messages.iter().any(|m| m == "GraphQL errors: Not allowed"), "{:?}", results[1].messages);
+    assert!(results[2].success, "{:?}", results[2].messages);
+    assert!(!results[3].success);
+    assert!(results[3].messages.iter().any(|m| m == "Expected GraphQL errors, got none"), "{:?}", results[3].messages);
+}
@@ -0,0 +1,314 @@
+use catalyst::core::runner::TestRunner;
+use catalyst::http::client::HttpClient;
+use rcgen::{CertifiedKey, generate_simple_self_signed};
+use serde_json::json;
+use std::convert::Infallible;
+use std::fs;
+use std::path::Path;
+use std::task::{Context, Poll};
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+use tonic::codegen::{Body, BoxFuture, Service, StdError, http};
+use tonic::server::{NamedService, UnaryService};
+use tonic::transport::{Identity, Server, ServerTlsConfig};
+use tonic::transport::server::TcpIncoming;
+use tonic::{Request, Response, Status};
+
+mod common;
+use common::write_suite;
+
+const PROTO: &str = r#"
+syntax = "proto3";
+
+package users.v1;
+
+import "users/v1/types.proto";
+
+service UserService {
+  rpc GetUser(GetUserRequest) returns (User);
+  rpc WatchUser(GetUserRequest) returns (stream User);
+}
+"#;
+
+const TYPES_PROTO: &str = r#"
+syntax = "proto3";
+
+package users.v1;
+
+message GetUserRequest {
+  int64 id = 1;
+}
+
+message User {
+  int64 id = 1;
+  string name = 2;
+  bool admin = 3;
+  repeated string roles = 4;
+}
+"#;
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct GetUserRequest {
+    #[prost(int64, tag = "1")]
+    id: i64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct User {
+    #[prost(int64, tag = "1")]
+    id: i64,
+    #[prost(string, tag = "2")]
+    name: String,
+    #[prost(bool, tag = "3")]
+    admin: bool,
+    #[prost(string, repeated, tag = "4")]
+    roles: Vec<String>,
+}
+
+struct GetUser;
+
+impl UnaryService<GetUserRequest> for GetUser {
+    type Response = User;
+    type Future = BoxFuture<Response<User>, Status>;
+
+    /// User 7 exists; the `authorization` metadata is echoed back as `x-seen-auth`
+    fn call(&mut self, request: Request<GetUserRequest>) -> Self::Future {
+        Box::pin(async move {
+            let auth = request.metadata().get("authorization").cloned();
+            let id = request.into_inner().id;
+            if id != 7 {
+                return Err(Status::not_found(format!("no user {id}")));
+            }
+            let mut response = Response::new(User {
+                id,
+                name: "ada".into(),
+                admin: false,
+                roles: vec!["reader".into()],
+            });
+            if let Some(auth) = auth {
+                response.metadata_mut().insert("x-seen-auth", auth);
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[derive(Clone)]
+struct UserService;
+
+impl NamedService for UserService {
+    const NAME: &'static str = "users.v1.UserService";
+}
+
+impl<B> Service<http::Request<B>> for UserService
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<tonic::body::Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        match request.uri().path() {
+            "/users.v1.UserService/GetUser" => Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(tonic_prost::ProstCodec::default());
+                Ok(grpc.unary(GetUser, request).await)
+            }),
+            _ => Box::pin(async move { Ok(Status::unimplemented("").into_http()) }),
+        }
+    }
+}
+
+fn write_protos(dir: &Path) {
+    fs::create_dir_all(dir.join("protos/users/v1")).unwrap();
+    fs::write(dir.join("protos/users/v1/service.proto"), PROTO).unwrap();
+    fs::write(dir.join("protos/users/v1/types.proto"), TYPES_PROTO).unwrap();
+}
+
+/// The service, with server reflection built from the same `.proto` files; over
+/// TLS as `localhost` when `tls` is set
+async fn start_server(dir: &Path, tls: Option<ServerTlsConfig>) -> String {
+    let mut set = protobuf::descriptor::FileDescriptorSet::new();
+    set.file = protobuf_parse::Parser::new()
+        .pure()
+        .include(dir.join("protos"))
+        .input(dir.join("protos/users/v1/service.proto"))
+        .parse_and_typecheck()
+        .unwrap()
+        .file_descriptors;
+    let reflection = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(&protobuf::Message::write_to_bytes(&set).unwrap())
+        .build_v1()
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let mut server = Server::builder();
+    if let Some(tls) = &tls {
+        server = server.tls_config(tls.clone()).unwrap();
+    }
+    tokio::spawn(
+        server
+            .add_service(UserService)
+            .add_service(reflection)
+            .serve_with_incoming(TcpIncoming::from(listener)),
+    );
+    match tls {
+        Some(_) => format!("https://localhost:{}", address.port()),
+        None => format!("http://{address}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unary_calls_with_proto_files_and_reflection() {
+    let dir = TempDir::new().unwrap();
+    write_protos(dir.path());
+    let base_url = start_server(dir.path(), None).await;
+    let file = write_suite(
+        dir.path(),
+        &base_url,
+        r#"
+[[tests]]
+name = "get user from proto files"
+protocol = "grpc"
+service = "users.v1.UserService"
+method = "GetUser"
+proto = ["protos/users/v1/service.proto"]
+proto_path = ["protos"]
+headers = { "Authorization" = "Bearer t0k" }
+body = { id = "{{user_id}}" }
+expected_status = 0
+expected_body = { id = 7, name = "ada", admin = false, roles = ["reader"] }
+expected_headers = [["x-seen-auth", "Bearer t0k"]]
+store = { user_name = "$.name" }
+
+[[tests]]
+name = "get user by reflection"
+protocol = "grpc"
+service = "users.v1.UserService"
+method = "GetUser"
+body = { id = 7 }
+expected_status = 0
+assertions = [{ type = "PathContains", value = ["$.roles", ["reader"]] }]
+
+[[tests]]
+name = "unknown user"
+protocol = "grpc"
+service = "users.v1.UserService"
+method = "GetUser"
+body = { id = 404 }
+expected_status = 5
+expected_body = { code = 5, message = "no user 404" }
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner.load_suite(Some(&file), Some("user_id=7".to_string())).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    for test in &suite.tests {
+        let result = runner.run_test(test, &client, dir.path(), None).await;
+        assert!(result.success, "{}: {:?}", test.name, result.messages);
+    }
+    assert_eq!(runner.variables["user_name"], json!("ada"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_grpc_call_errors_are_reported() {
+    let dir = TempDir::new().unwrap();
+    write_protos(dir.path());
+    let base_url = start_server(dir.path(), None).await;
+    let file = write_suite(
+        dir.path(),
+        &base_url,
+        r#"
+[[tests]]
+name = "streaming"
+protocol = "grpc"
+service = "users.v1.UserService"
+method = "WatchUser"
+expected_status = 0
+
+[[tests]]
+name = "wrong field"
+protocol = "grpc"
+service = "users.v1.UserService"
+method = "GetUser"
+proto = ["protos/users/v1/service.proto"]
+proto_path = ["protos"]
+body = { user = 7 }
+expected_status = 0
+
+[[tests]]
+name = "unknown service"
+protocol = "grpc"
+service = "users.v1.Missing"
+method = "GetUser"
+expected_status = 0
+
+[[tests]]
+name = "undefined variables"
+protocol = "grpc"
+service = "users.v1.UserService"
+method = "GetUser"
+headers = { "x-tenant" = "{{tenant}}" }
+body = { id = "{{user_id}}" }
+expected_status = 0
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner.load_suite(Some(&file), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    let mut messages = Vec::new();
+    for test in &suite.tests {
+        let result = runner.run_test(test, &client, dir.path(), None).await;
+        assert!(!result.success, "{} passed", test.name);
+        messages.push(result.messages.join("\n"));
+    }
+    assert!(messages[0].contains("is a streaming method"), "{}", messages[0]);
+    assert!(messages[1].contains("Body does not fit `users.v1.GetUserRequest`"), "{}", messages[1]);
+    assert!(messages[2].contains("users.v1.Missing"), "{}", messages[2]);
+    assert!(
+        messages[3].contains("Unresolved variables: {{tenant}} in header 'x-tenant', {{user_id}} in body at $.id"),
+        "{}",
+        messages[3]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_grpc_over_tls_uses_the_tls_settings() {
+    let dir = TempDir::new().unwrap();
+    write_protos(dir.path());
+    let CertifiedKey { cert, signing_key } = generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let tls = ServerTlsConfig::new().identity(Identity::from_pem(cert.pem(), signing_key.serialize_pem()));
+    let base_url = start_server(dir.path(), Some(tls)).await;
+    let test = r#"
+[[tests]]
+name = "get user over tls"
+protocol = "grpc"
+service = "users.v1.UserService"
+method = "GetUser"
+body = { id = 7 }
+expected_status = 0
+expected_body = { id = 7, name = "ada" }
+"#;
+
+    let mut runner = TestRunner::new(true);
+    let insecure = write_suite(dir.path(), &base_url, &format!("insecure = true\n{test}"));
+    let suite = runner.load_suite(Some(&insecure), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    let result = runner.run_test(&suite.tests[0], &client, dir.path(), None).await;
+    assert!(result.success, "{:?}", result.messages);
+
+    let verified = write_suite(dir.path(), &base_url, test);
+    let suite = runner.load_suite(Some(&verified), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    let result = runner.run_test(&suite.tests[0], &client, dir.path(), None).await;
+    assert!(!result.success);
+    assert!(result.messages.join("\n").contains("UnknownIssuer"), "{:?}", result.messages);
+}
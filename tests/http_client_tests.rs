@@ -6,12 +6,7 @@ mod tests {
     fn create_test_config() -> Config {
         Config {
             base_url: "https://httpbin.org".to_string(),
-            auth_method: None,
-            auth_token: None,
-            default_headers: None,
-            env: None,
-            insecure: None,
-            allowed_commands: None,
+            ..Default::default()
         }
     }
 
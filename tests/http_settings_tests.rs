@@ -0,0 +1,191 @@
+use catalyst::engine::execution;
+use catalyst::http::client::HttpClient;
+use catalyst::models::config::{Config, HttpSettings};
+use catalyst::models::suite::TestSuite;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+mod common;
+use common::{Response, start_http_server, test_from};
+
+/// Minimal HTTP/1.1 server:
+/// - `/slow` answers after two seconds
+/// - `/redirect/N` redirects to `/redirect/N-1` until `/redirect/0`
+/// - anything else echoes the User-Agent header
+async fn start_server() -> String {
+    start_http_server(|request| {
+        if request.path == "/slow" {
+            Response::json(json!({"slow": true})).delay(Duration::from_secs(2))
+        } else if let Some(hops) = request.path.strip_prefix("/redirect/") {
+            match hops.parse::<u32>().unwrap_or(0) {
+                0 => Response::json(json!({"arrived": true})),
+                n => Response::new(302).header("Location", &format!("/redirect/{}", n - 1)),
+            }
+        } else {
+            Response::json(json!({"user_agent": request.header("user-agent").unwrap_or_default()}))
+        }
+    })
+    .await
+}
+
+fn config(base_url: String, http: HttpSettings) -> Config {
+    Config {
+        base_url,
+        http,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_http_settings_parse_in_config_and_tests() {
+    let suite: TestSuite = toml::from_str(
+        r#"
+        [config]
+        base_url = "http://localhost"
+        timeout_ms = 5000
+        follow_redirects = false
+        proxy = "socks5://127.0.0.1:1080"
+
+        [[tests]]
+        name = "override"
+        method = "GET"
+        endpoint = "/"
+        expected_status = 200
+        timeout_ms = 100
+        follow_redirects = true
+        max_redirects = 2
+        "#,
+    )
+    .unwrap();
+
+    let merged = suite.config.http.merged_with(&suite.tests[0].http);
+    assert_eq!(merged.timeout_ms, Some(100));
+    assert_eq!(merged.follow_redirects, Some(true));
+    assert_eq!(merged.max_redirects, Some(2));
+    assert_eq!(merged.proxy.as_deref(), Some("socks5://127.0.0.1:1080"));
+}
+
+#[tokio::test]
+async fn test_timeout_is_reported_as_timeout() {
+    let base_url = start_server().await;
+    let client = HttpClient::new(&config(base_url, HttpSettings::default()));
+    let test = test_from(
+        r#"
+        name = "slow"
+        method = "GET"
+        endpoint = "/slow"
+        expected_status = 200
+        timeout_ms = 200
+        "#,
+    );
+
+    let result = execution::run(&client, &test, Path::new("."), &mut HashMap::new(), false).await;
+    assert!(!result.success);
+    assert!(result.timed_out);
+    assert_eq!(result.status, (200, 0));
+    assert!(result.errors[0].contains("timed out"), "{:?}", result.errors);
+}
+
+#[tokio::test]
+async fn test_redirect_policy() {
+    let base_url = start_server().await;
+    let client = HttpClient::new(&config(
+        base_url,
+        HttpSettings {
+            max_redirects: Some(3),
+            ..Default::default()
+        },
+    ));
+
+    let follow = test_from(
+        r#"
+        name = "within limit"
+        method = "GET"
+        endpoint = "/redirect/3"
+        expected_status = 200
+        expected_body = { arrived = true }
+        "#,
+    );
+    let result = execution::run(&client, &follow, Path::new("."), &mut HashMap::new(), false).await;
+    assert!(result.success, "{:?}", result.errors);
+
+    let too_many = test_from(
+        r#"
+        name = "over limit"
+        method = "GET"
+        endpoint = "/redirect/4"
+        expected_status = 200
+        "#,
+    );
+    let result = execution::run(&client, &too_many, Path::new("."), &mut HashMap::new(), false).await;
+    assert!(!result.success);
+    assert!(!result.timed_out);
+
+    let no_follow = test_from(
+        r#"
+        name = "no follow"
+        method = "GET"
+        endpoint = "/redirect/1"
+        expected_status = 302
+        follow_redirects = false
+        "#,
+    );
+    let result = execution::run(&client, &no_follow, Path::new("."), &mut HashMap::new(), false).await;
+    assert!(result.success, "{:?}", result.errors);
+}
+
+#[tokio::test]
+async fn test_user_agent_from_config_and_test() {
+    let base_url = start_server().await;
+    let client = HttpClient::new(&config(
+        base_url,
+        HttpSettings {
+            user_agent: Some("catalyst-suite/1.0".to_string()),
+            ..Default::default()
+        },
+    ));
+
+    let from_config = test_from(
+        r#"
+        name = "config agent"
+        method = "GET"
+        endpoint = "/"
+        expected_status = 200
+        expected_body = { user_agent = "catalyst-suite/1.0" }
+        "#,
+    );
+    let result = execution::run(&client, &from_config, Path::new("."), &mut HashMap::new(), false).await;
+    assert!(result.success, "{:?}", result.errors);
+
+    let overridden = test_from(
+        r#"
+        name = "test agent"
+        method = "GET"
+        endpoint = "/"
+        expected_status = 200
+        user_agent = "mobile-app/2.3"
+        expected_body = { user_agent = "mobile-app/2.3" }
+        "#,
+    );
+    let result = execution::run(&client, &overridden, Path::new("."), &mut HashMap::new(), false).await;
+    assert!(result.success, "{:?}", result.errors);
+}
+
+#[tokio::test]
+async fn test_client_leaves_the_default_crypto_provider_alone() {
+    let base_url = start_server().await;
+    let client = HttpClient::new(&config(base_url, HttpSettings::default()));
+    let test = test_from(
+        r#"
+        name = "agent"
+        method = "GET"
+        endpoint = "/"
+        expected_status = 200
+        "#,
+    );
+    let result = execution::run(&client, &test, Path::new("."), &mut HashMap::new(), false).await;
+    assert!(result.success, "{:?}", result.errors);
+    assert!(tokio_rustls::rustls::crypto::CryptoProvider::get_default().is_none());
+}
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use catalyst::engine::variables::get_json_value;
+    use catalyst::engine::variables::{get_json_value, select_json_values, store_variables};
+    use std::collections::HashMap;
     use serde_json::json;
 
     #[test]
@@ -69,4 +70,28 @@ mod tests {
         // Test missing field
         assert_eq!(get_json_value(&json, "$.[0].missing"), None);
     }
+
+    #[test]
+    fn test_wildcards_select_every_element() {
+        let json = json!({
+            "items": [
+                {"id": 1, "tags": ["a", "b"]},
+                {"id": 2, "tags": []},
+                {"name": "no id", "tags": ["c"]}
+            ],
+            "counts": {"open": 2, "closed": 5}
+        });
+
+        assert_eq!(select_json_values(&json, "$.items[*].id"), [&json!(1), &json!(2)]);
+        assert_eq!(select_json_values(&json, "$.items[*].tags[*]"), [&json!("a"), &json!("b"), &json!("c")]);
+        assert_eq!(select_json_values(&json, "$.items[0].tags[1]"), [&json!("b")]);
+        assert_eq!(select_json_values(&json, "$.counts.*"), [&json!(5), &json!(2)]);
+        assert!(select_json_values(&json, "$.missing[*]").is_empty());
+
+        // A wildcard in `store` stores the array of what it selects
+        let mut vars = HashMap::new();
+        let store = HashMap::from([("ids".to_string(), "$.items[*].id".to_string())]);
+        store_variables(&json, &store, &HashMap::new(), &None, 0, &mut vars);
+        assert_eq!(vars["ids"], json!([1, 2]));
+    }
 }
@@ -0,0 +1,137 @@
+use catalyst::checker::parse_tests;
+use catalyst::core::mock::routes;
+use catalyst::core::runner::TestRunner;
+use catalyst::http::client::HttpClient;
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+mod common;
+use common::{serve_mocks, write_tests_file};
+
+const SUITE: &str = r#"
+[config]
+base_url = "http://localhost"
+
+[[mocks]]
+name = "bad login"
+method = "POST"
+path = "/login"
+body = { user = "mallory" }
+status = 401
+response_body = { error = "bad credentials" }
+
+[[mocks]]
+path = "/search"
+query = { q = "{{q}}" }
+headers = { "X-Api-Key" = "k1" }
+response_headers = { "X-Total" = "1" }
+response_body = "found"
+
+[[tests]]
+name = "login"
+method = "POST"
+endpoint = "/login"
+body = { user = "ada" }
+expected_status = 200
+assertions = [{ type = "Contains", value = { ok = true } }]
+store = { token = "$.token", user_id = "$.user.id" }
+
+[[tests]]
+name = "get user"
+method = "GET"
+endpoint = "/users/{{user_id}}?fields=all"
+headers = { "Authorization" = "Bearer {{token}}" }
+expected_status = 200
+expected_body = { name = "ada" }
+
+[[tests]]
+name = "delete user"
+method = "DELETE"
+endpoint = "/users/{{user_id}}"
+expected_status = 204
+"#;
+
+#[tokio::test]
+async fn test_mock_serves_mocks_then_tests() {
+    let dir = TempDir::new().unwrap();
+    let base_url = serve_mocks(&write_tests_file(&dir, SUITE)).await;
+    let client = reqwest::Client::new();
+
+    let bad = client
+        .post(format!("{base_url}/login"))
+        .json(&json!({ "user": "mallory", "password": "x" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(bad.status(), 401);
+    assert_eq!(bad.json::<Value>().await.unwrap(), json!({ "error": "bad credentials" }));
+
+    // Falls through to the test, with placeholders for what it stores
+    let good = client
+        .post(format!("{base_url}/login"))
+        .json(&json!({ "user": "ada" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(good.status(), 200);
+    assert_eq!(
+        good.json::<Value>().await.unwrap(),
+        json!({ "ok": true, "token": "mock-token", "user": { "id": "mock-user_id" } })
+    );
+
+    let user = client.get(format!("{base_url}/users/42")).send().await.unwrap();
+    assert_eq!(user.status(), 200);
+    assert_eq!(user.json::<Value>().await.unwrap(), json!({ "name": "ada" }));
+    let deleted = client.delete(format!("{base_url}/users/42")).send().await.unwrap();
+    assert_eq!(deleted.status(), 204);
+
+    let search = client
+        .get(format!("{base_url}/search?q=rust%20lang"))
+        .header("x-api-key", "k1")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(search.status(), 200);
+    assert_eq!(search.headers()["x-total"], "1");
+    assert_eq!(search.headers()["content-type"], "text/plain");
+    assert_eq!(search.text().await.unwrap(), "found");
+
+    for unmatched in [
+        client.get(format!("{base_url}/search?q=rust")),
+        client.get(format!("{base_url}/search")).header("X-Api-Key", "k1"),
+        client.get(format!("{base_url}/users/42/posts")),
+        client.put(format!("{base_url}/users/42")),
+    ] {
+        let response = unmatched.send().await.unwrap();
+        assert_eq!(response.status(), 404);
+        assert!(response.text().await.unwrap().contains("No mock matches"));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_suite_passes_against_its_own_mock() {
+    let dir = TempDir::new().unwrap();
+    let mock_url = serve_mocks(&write_tests_file(&dir, SUITE)).await;
+    let file = write_tests_file(&dir, &SUITE.replace("http://localhost", &mock_url));
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner.load_suite(Some(&file), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    for test in &suite.tests {
+        let result = runner.run_test(test, &client, dir.path(), None).await;
+        assert!(result.success, "{}: {:?}", test.name, result.messages);
+    }
+    assert_eq!(runner.variables["user_id"], json!("mock-user_id"));
+}
+
+#[test]
+fn test_invalid_mock_status_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    let file = write_tests_file(
+        &dir,
+        "[config]\nbase_url = \"http://localhost\"\n\n[[mocks]]\nname = \"broken\"\npath = \"/\"\nstatus = 42\n",
+    );
+    let suite = parse_tests(Some(&file)).unwrap();
+    assert!(suite.tests.is_empty());
+    assert_eq!(routes(&suite).unwrap_err(), "`broken` has an invalid status 42");
+}
@@ -0,0 +1,135 @@
+use catalyst::checker::parse_tests;
+use catalyst::core::record::{Recorder, serve, shape};
+use catalyst::core::runner::TestRunner;
+use catalyst::core::record;
+use catalyst::http::client::HttpClient;
+use catalyst::models::test::JsonAssertion;
+use serde_json::json;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+
+mod common;
+use common::start_mock_server;
+
+const UPSTREAM: &str = r#"
+[config]
+base_url = "http://localhost"
+
+[[mocks]]
+method = "GET"
+path = "/users"
+response_body = { page = 2, users = [{ id = 1 }], next = "/users?page=3" }
+response_headers = { "X-Total" = "1" }
+
+[[mocks]]
+method = "POST"
+path = "/users"
+status = 201
+response_body = { id = 7, name = "ada", profile = { admin = false } }
+
+[[mocks]]
+method = "POST"
+path = "/import"
+status = 202
+"#;
+
+async fn start_recorder(target: &str, output: &std::path::Path) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let recorder = Arc::new(Mutex::new(Recorder::new(target, output)));
+    tokio::spawn(serve(listener, target.to_string(), recorder));
+    format!("http://{address}")
+}
+
+#[test]
+fn test_shape_keeps_values_and_empties_arrays() {
+    let body = json!({ "id": 7, "tags": ["a"], "deleted_at": null, "owner": { "name": "ada", "roles": [] } });
+    assert_eq!(
+        shape(&body),
+        Some(json!({ "id": 7, "tags": [], "owner": { "name": "ada", "roles": [] } }))
+    );
+    assert_eq!(shape(&json!([1, 2])), None);
+    assert_eq!(shape(&json!({ "gone": null })), None);
+    assert_eq!(shape(&json!("ok")), Some(json!("ok")));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_proxied_traffic_becomes_a_passing_suite() {
+    let dir = TempDir::new().unwrap();
+    let upstream = start_mock_server(&dir, UPSTREAM).await;
+    let output = dir.path().join("recorded.toml");
+    let proxy = start_recorder(&upstream, &output).await;
+    let client = reqwest::Client::new();
+
+    let listed = client
+        .get(format!("{proxy}/users?page=2"))
+        .header("X-Tenant", "acme")
+        .header("Authorization", "Bearer s3cret")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(listed.status(), 200);
+    assert_eq!(listed.headers()["x-total"], "1");
+    assert_eq!(listed.json::<serde_json::Value>().await.unwrap()["page"], 2);
+
+    let created = client.post(format!("{proxy}/users")).json(&json!({ "name": "ada" })).send().await.unwrap();
+    assert_eq!(created.status(), 201);
+    let rows: Vec<_> = (0..100).map(|i| json!({ "id": i, "name": format!("user {i}") })).collect();
+    let imported = client.post(format!("{proxy}/import")).json(&rows).send().await.unwrap();
+    assert_eq!(imported.status(), 202);
+    client.get(format!("{proxy}/users")).send().await.unwrap();
+
+    let content = fs::read_to_string(&output).unwrap();
+    assert!(!content.contains("s3cret"), "{content}");
+    let suite = parse_tests(Some(&output.to_string_lossy())).unwrap();
+    assert_eq!(suite.config.base_url, upstream);
+    let names: Vec<_> = suite.tests.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["GET /users", "POST /users", "POST /import", "GET /users (2)"]);
+
+    let list = &suite.tests[0];
+    assert_eq!(list.query_params.as_ref().unwrap()["page"], "2");
+    assert_eq!(list.headers.as_ref().unwrap()["x-tenant"], "acme");
+    assert!(!list.headers.as_ref().unwrap().contains_key("authorization"));
+    assert_eq!(list.expected_status, 200);
+    let Some([JsonAssertion::Contains(listed_shape)]) = list.assertions.as_deref() else {
+        panic!("expected one Contains assertion: {:?}", list.assertions);
+    };
+    assert_eq!(*listed_shape, json!({ "page": 2, "users": [], "next": "/users?page=3" }));
+
+    let create = &suite.tests[1];
+    assert_eq!(create.body, Some(json!({ "name": "ada" })));
+    assert_eq!(create.expected_status, 201);
+
+    // Large bodies go to a file next to the tests file
+    let import = &suite.tests[2];
+    assert!(import.body.is_none());
+    assert_eq!(import.body_file.as_deref(), Some("recorded_bodies/003-post-import.json"));
+    let saved: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("recorded_bodies/003-post-import.json")).unwrap()).unwrap();
+    assert_eq!(saved.as_array().unwrap().len(), 100);
+    assert!(import.assertions.is_none());
+
+    // Replaying the recording against the server passes
+    let mut runner = TestRunner::new(true);
+    let file = output.to_string_lossy().to_string();
+    let suite = runner.load_suite(Some(&file), None).unwrap();
+    let http = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    for test in &suite.tests {
+        let result = runner.run_test(test, &http, dir.path(), None).await;
+        assert!(result.success, "{}: {:?}", test.name, result.messages);
+    }
+}
+
+#[tokio::test]
+async fn test_record_refuses_to_overwrite() {
+    let dir = TempDir::new().unwrap();
+    let output = dir.path().join("existing.toml");
+    fs::write(&output, "# keep me").unwrap();
+    let error = record::run_record("http://localhost:1", "127.0.0.1", 0, &output.to_string_lossy())
+        .await
+        .unwrap_err();
+    assert!(error.contains("already exists"), "{error}");
+    assert_eq!(fs::read_to_string(&output).unwrap(), "# keep me");
+}
@@ -0,0 +1,161 @@
+use catalyst::checker::parse_tests;
+use catalyst::core::runner::TestRunner;
+use catalyst::http::client::HttpClient;
+use catalyst::output::TestSummaryFormatter;
+use serde_json::json;
+use tempfile::TempDir;
+
+mod common;
+use common::{start_mock_server, write_suite};
+
+/// Login hands out a token; orders can only be created with it, and only order 42 exists
+const SERVER: &str = r#"
+[config]
+base_url = "http://localhost"
+
+[[mocks]]
+method = "POST"
+path = "/login"
+response_body = { token = "t0k" }
+
+[[mocks]]
+method = "POST"
+path = "/orders"
+headers = { "authorization" = "Bearer t0k" }
+status = 201
+response_body = { id = 42 }
+
+[[mocks]]
+method = "GET"
+path = "/orders/42"
+response_body = { id = 42, status = "open" }
+
+[[mocks]]
+path = "/orders/{{id}}"
+status = 404
+response_body = { error = "not found" }
+"#;
+
+#[tokio::test]
+async fn test_scenario_runs_steps_with_their_own_variables() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_mock_server(&dir, SERVER).await;
+    let file = write_suite(
+        &dir,
+        &base_url,
+        r#"
+[templates.authorized]
+headers = { "Authorization" = "Bearer {{token}}" }
+
+[[tests]]
+name = "checkout"
+
+[[tests.steps]]
+name = "login"
+method = "POST"
+endpoint = "/login"
+body = { user = "{{user}}" }
+expected_status = 200
+store = { token = "$.token" }
+
+[[tests.steps]]
+name = "create order"
+extends = "authorized"
+method = "POST"
+endpoint = "/orders"
+expected_status = 201
+store = { order_id = "$.id" }
+
+[[tests.steps]]
+name = "get order"
+method = "GET"
+endpoint = "/orders/{{order_id}}"
+expected_status = 200
+expected_body = { id = 42, status = "open" }
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner.load_suite(Some(&file), Some("user=ada".to_string())).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    let result = runner.run_test(&suite.tests[0], &client, dir.path(), None).await;
+
+    assert!(result.success, "{:?}", result.messages);
+    let steps: Vec<_> = result.steps.iter().map(|s| (s.name.as_str(), s.success)).collect();
+    assert_eq!(steps, [("login", true), ("create order", true), ("get order", true)]);
+    assert_eq!(result.steps[2].response_body, Some(json!({ "id": 42, "status": "open" })));
+
+    // Stored by the steps, then dropped with the scenario
+    assert!(!runner.variables.contains_key("token"));
+    assert!(!runner.variables.contains_key("order_id"));
+    assert_eq!(runner.variables["user"], json!("ada"));
+}
+
+#[tokio::test]
+async fn test_scenario_stops_and_reports_the_failing_step() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_mock_server(&dir, SERVER).await;
+    let file = write_suite(
+        &dir,
+        &base_url,
+        r#"
+[[tests]]
+name = "order lookup"
+
+[[tests.steps]]
+name = "login"
+method = "POST"
+endpoint = "/login"
+expected_status = 200
+
+[[tests.steps]]
+name = "get order"
+method = "GET"
+endpoint = "/orders/7"
+expected_status = 200
+
+[[tests.steps]]
+name = "cancel order"
+method = "DELETE"
+endpoint = "/orders/7"
+expected_status = 204
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner.load_suite(Some(&file), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    let result = runner.run_test(&suite.tests[0], &client, dir.path(), None).await;
+
+    assert!(!result.success);
+    assert_eq!((result.expected_status, result.actual_status), (200, 404));
+    assert_eq!(result.messages, ["Step 2 `get order`: Status 404, expected 200"]);
+    assert_eq!(result.steps[2].skipped.as_deref(), Some("step 2 failed"));
+
+    let summary = TestSummaryFormatter::new(true).format_compact_results(&[result], 0, 1);
+    assert!(summary.contains("  ✗ order lookup (expected 200, got 404)\n"), "{summary}");
+    assert!(summary.contains("      ✓ 1. login"), "{summary}");
+    assert!(summary.contains("      ✗ 2. get order (expected 200, got 404)\n"), "{summary}");
+    assert!(summary.contains("      - 3. cancel order (skipped: step 2 failed)\n"), "{summary}");
+    assert!(summary.contains("1 tests failed"), "{summary}");
+}
+
+#[test]
+fn test_scenario_steps_need_an_expected_status() {
+    let dir = TempDir::new().unwrap();
+    let file = write_suite(
+        &dir,
+        "http://localhost",
+        r#"
+[[tests]]
+name = "checkout"
+
+[[tests.steps]]
+name = "login"
+method = "POST"
+endpoint = "/login"
+"#,
+    );
+    let error = parse_tests(Some(&file)).unwrap_err();
+    assert_eq!(error, "Test `login` is missing `expected_status`");
+}
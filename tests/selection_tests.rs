@@ -0,0 +1,141 @@
+use catalyst::core::runner::TestRunner;
+use catalyst::core::selection::{LastRun, TestSelection};
+use catalyst::models::suite::TestSuite;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::{Response, start_http_server};
+
+/// `/fail` answers 500, everything else 200
+async fn start_server() -> String {
+    start_http_server(|request| {
+        let status = if request.path == "/fail" { 500 } else { 200 };
+        Response::json(json!({})).status(status)
+    })
+    .await
+}
+
+const SUITE: &str = r#"
+[config]
+base_url = "http://localhost"
+
+[[tests]]
+name = "Get user"
+method = "GET"
+endpoint = "/users/1"
+expected_status = 200
+tags = ["smoke", "users"]
+
+[[tests]]
+name = "Get order"
+method = "GET"
+endpoint = "/fail"
+expected_status = 200
+tags = ["smoke", "payments"]
+
+[[tests]]
+name = "Export orders"
+method = "GET"
+endpoint = "/export"
+expected_status = 200
+tags = ["payments", "slow"]
+
+[[tests]]
+name = "Health [v2]"
+method = "GET"
+endpoint = "/health"
+expected_status = 200
+"#;
+
+fn selected(selection: &TestSelection, last_run: Option<&LastRun>) -> Vec<String> {
+    let suite: TestSuite = toml::from_str(SUITE).unwrap();
+    let selector = selection.selector(last_run);
+    suite
+        .tests
+        .iter()
+        .filter(|t| selector.matches(t))
+        .map(|t| t.name.clone())
+        .collect()
+}
+
+#[test]
+fn test_filter_is_substring_or_regex() {
+    let by = |filter: &str| TestSelection {
+        filter: Some(filter.to_string()),
+        ..Default::default()
+    };
+    assert_eq!(selected(&by("order"), None), vec!["Get order", "Export orders"]);
+    assert_eq!(selected(&by("^Get (user|order)$"), None), vec!["Get user", "Get order"]);
+    // Not a valid pattern, but still a plain substring of the name
+    assert_eq!(selected(&by("Health [v2"), None), vec!["Health [v2]"]);
+}
+
+#[test]
+fn test_tags_exact_names_and_previous_failures() {
+    let smoke = TestSelection {
+        tags: vec!["smoke".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(selected(&smoke, None), vec!["Get user", "Get order"]);
+
+    let nightly_fast = TestSelection {
+        tags: vec!["payments".to_string(), "users".to_string()],
+        exclude_tags: vec!["slow".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(selected(&nightly_fast, None), vec!["Get user", "Get order"]);
+
+    let exact = TestSelection {
+        names: vec!["Get user".to_string(), "Health".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(selected(&exact, None), vec!["Get user"]);
+
+    let last_run = LastRun {
+        passed: vec!["Get user".to_string()],
+        failed: vec!["Get order".to_string(), "Export orders".to_string()],
+    };
+    let only_failed = TestSelection {
+        only_failed: true,
+        exclude_tags: vec!["slow".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(selected(&only_failed, Some(&last_run)), vec!["Get order"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_only_failed_reruns_the_previous_failures() {
+    let base_url = start_server().await;
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("api.toml");
+    fs::write(&file, SUITE.replace("http://localhost", &base_url)).unwrap();
+    let file = file.to_string_lossy().to_string();
+
+    let mut first = TestRunner::new(true);
+    first.execute_tests(None, false, Some(file.clone()), None, true).await;
+    assert_eq!(first.results.len(), 4);
+
+    let results_path = LastRun::path_for(Path::new(&file));
+    assert_eq!(results_path, dir.path().join("api.last-run.json"));
+    let last_run = LastRun::load(&results_path).unwrap();
+    assert_eq!(last_run.failed, vec!["Get order"]);
+    assert_eq!(last_run.passed.len(), 3);
+
+    // A narrower run only updates the tests it ran
+    let mut smoke = TestRunner::new(true);
+    smoke.selection.tags = vec!["users".to_string()];
+    smoke.execute_tests(None, false, Some(file.clone()), None, true).await;
+    assert_eq!(smoke.results.len(), 1);
+    let last_run = LastRun::load(&results_path).unwrap();
+    assert_eq!(last_run.failed, vec!["Get order"]);
+    assert_eq!(last_run.passed.len(), 3);
+
+    let mut rerun = TestRunner::new(true);
+    rerun.selection.only_failed = true;
+    rerun.execute_tests(None, false, Some(file), None, true).await;
+    let names: Vec<&str> = rerun.results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["Get order"]);
+}
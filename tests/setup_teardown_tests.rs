@@ -0,0 +1,212 @@
+use catalyst::checker::parse_tests;
+use catalyst::core::runner::{TestResult, TestRunner};
+use catalyst::core::selection::LastRun;
+use catalyst::output::TestSummaryFormatter;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::sync::Notify;
+
+mod common;
+use common::{start_mock_server, write_suite};
+
+/// Users can be created, read and deleted; only user 7 exists once created. `/slow` hangs
+const SERVER: &str = r#"
+[config]
+base_url = "http://localhost"
+
+[[mocks]]
+method = "POST"
+path = "/users"
+status = 201
+response_body = { id = 7 }
+
+[[mocks]]
+method = "GET"
+path = "/users/7"
+response_body = { id = 7, name = "ada" }
+
+[[mocks]]
+method = "DELETE"
+path = "/users/7"
+status = 204
+
+[[mocks]]
+method = "GET"
+path = "/slow"
+delay_ms = 60000
+"#;
+
+fn outcomes(results: &[TestResult]) -> Vec<(&str, bool)> {
+    results.iter().map(|r| (r.name.as_str(), r.success)).collect()
+}
+
+#[tokio::test]
+async fn test_setup_and_teardown_tests_seed_and_clean_through_the_api() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_mock_server(&dir, SERVER).await;
+    let file = write_suite(
+        &dir,
+        &base_url,
+        r#"
+[[setup_tests]]
+name = "create user"
+method = "POST"
+endpoint = "/users"
+expected_status = 201
+store = { user_id = "$.id" }
+
+[[tests]]
+name = "get user"
+method = "GET"
+endpoint = "/users/{{user_id}}"
+expected_status = 200
+
+[[tests]]
+name = "get missing user"
+method = "GET"
+endpoint = "/users/8"
+expected_status = 200
+
+[[teardown_tests]]
+name = "delete user"
+method = "DELETE"
+endpoint = "/users/{{user_id}}"
+expected_status = 204
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    runner.execute_tests(None, false, Some(file), None, true).await;
+
+    assert_eq!(outcomes(&runner.setup_results), [("create user", true)]);
+    assert_eq!(outcomes(&runner.results), [("get user", true), ("get missing user", false)]);
+    assert_eq!(outcomes(&runner.teardown_results), [("delete user", true)]);
+    assert_eq!(runner.teardown_results[0].request.as_ref().unwrap().method, "DELETE");
+    assert!(runner.teardown_results[0].request.as_ref().unwrap().url.ends_with("/users/7"));
+}
+
+#[tokio::test]
+async fn test_teardown_runs_every_test_when_setup_fails() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_mock_server(&dir, SERVER).await;
+    let file = write_suite(
+        &dir,
+        &base_url,
+        r#"
+[[setup_tests]]
+name = "create org"
+method = "POST"
+endpoint = "/orgs"
+expected_status = 201
+
+[[setup_tests]]
+name = "create user"
+method = "POST"
+endpoint = "/users"
+expected_status = 201
+
+[[tests]]
+name = "get user"
+method = "GET"
+endpoint = "/users/7"
+expected_status = 200
+
+[[teardown_tests]]
+name = "delete org"
+method = "DELETE"
+endpoint = "/orgs/1"
+expected_status = 204
+
+[[teardown_tests]]
+name = "delete user"
+method = "DELETE"
+endpoint = "/users/7"
+expected_status = 204
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    runner.execute_tests(None, false, Some(file), None, true).await;
+
+    assert_eq!(outcomes(&runner.setup_results), [("create org", false)]);
+    assert!(runner.results.is_empty());
+    assert_eq!(outcomes(&runner.teardown_results), [("delete org", false), ("delete user", true)]);
+
+    let report = TestSummaryFormatter::new(true).format_suite_tests("Teardown", &runner.teardown_results);
+    assert!(report.starts_with("\nTeardown:\n  ✗ delete org (expected 204, got 404)\n"), "{report}");
+    assert!(report.contains("    - Status 404, expected 204\n"), "{report}");
+    assert!(report.contains("  ✓ delete user"), "{report}");
+}
+
+/// Interrupt the run after `delay`, then again after another `delay`
+fn interrupt_twice(interrupt: Arc<Notify>, delay: Duration) {
+    tokio::spawn(async move {
+        for _ in 0..2 {
+            tokio::time::sleep(delay).await;
+            interrupt.notify_one();
+        }
+    });
+}
+
+#[tokio::test]
+async fn test_interrupts_stop_the_tests_then_a_hanging_teardown() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_mock_server(&dir, SERVER).await;
+    let file = write_suite(
+        &dir,
+        &base_url,
+        r#"
+[[setup_tests]]
+name = "create user"
+method = "POST"
+endpoint = "/users"
+expected_status = 201
+
+[[tests]]
+name = "slow test"
+method = "GET"
+endpoint = "/slow"
+expected_status = 200
+
+[[teardown_tests]]
+name = "delete user"
+method = "DELETE"
+endpoint = "/users/7"
+expected_status = 204
+
+[[teardown_tests]]
+name = "slow teardown"
+method = "GET"
+endpoint = "/slow"
+expected_status = 200
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    interrupt_twice(Arc::clone(&runner.interrupt), Duration::from_millis(300));
+    let run = runner.execute_tests(None, false, Some(file), None, true);
+    tokio::time::timeout(Duration::from_secs(10), run).await.expect("the run was not interrupted");
+
+    assert_eq!(outcomes(&runner.setup_results), [("create user", true)]);
+    assert!(runner.results.is_empty());
+    assert_eq!(outcomes(&runner.teardown_results), [("delete user", true)]);
+    assert!(!LastRun::path_for(&dir.path().join("tests.toml")).exists());
+}
+
+#[test]
+fn test_teardown_tests_need_an_expected_status() {
+    let dir = TempDir::new().unwrap();
+    let file = write_suite(
+        &dir,
+        "http://localhost",
+        r#"
+[[teardown_tests]]
+name = "delete user"
+method = "DELETE"
+endpoint = "/users/7"
+"#,
+    );
+    let error = parse_tests(Some(&file)).unwrap_err();
+    assert_eq!(error, "Test `delete user` is missing `expected_status`");
+}
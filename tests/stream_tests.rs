@@ -0,0 +1,179 @@
+use catalyst::core::runner::TestRunner;
+use catalyst::engine::stream::{Decoder, Format};
+use catalyst::http::client::HttpClient;
+use serde_json::json;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+
+mod common;
+use common::{read_request, start_tcp_server, write_suite};
+
+const TICK: Duration = Duration::from_millis(30);
+
+/// Streams each piece as its own chunk, `TICK` apart
+async fn stream_chunks(socket: &mut TcpStream, content_type: &str, pieces: impl IntoIterator<Item = String>) {
+    let head = format!("HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n");
+    if socket.write_all(head.as_bytes()).await.is_err() {
+        return;
+    }
+    for piece in pieces {
+        let chunk = format!("{:x}\r\n{piece}\r\n", piece.len());
+        if socket.write_all(chunk.as_bytes()).await.is_err() {
+            return;
+        }
+        tokio::time::sleep(TICK).await;
+    }
+    let _ = socket.write_all(b"0\r\n\r\n").await;
+}
+
+/// `/jobs/1/progress`: SSE job progress; `/completions`: NDJSON; `/ticks`: an SSE tick that never ends
+async fn start_server() -> String {
+    let address = start_tcp_server(|mut socket| async move {
+        let request = read_request(&mut socket).await;
+        match request.path.as_str() {
+            "/jobs/1/progress" => {
+                let mut events = vec![": connected\n\n".to_string()];
+                events.extend((1..=3).map(|p| format!("event: progress\nid: {p}\ndata: {{\"percent\": {}}}\n\n", p * 25)));
+                // One event split across two chunks
+                events.push("event: done\ndata: {\"status\":".to_string());
+                events.push(" \"complete\", \"job\": 1}\n\n".to_string());
+                stream_chunks(&mut socket, "text/event-stream", events).await;
+            }
+            "/completions" => {
+                let lines = ["{\"token\":\"Hel\"}\n{\"token\":\"lo\"}\n", "{\"token\":\"!\",\"done\":true}"];
+                stream_chunks(&mut socket, "application/x-ndjson", lines.map(String::from)).await;
+            }
+            _ => {
+                let ticks = (1..).map(|n| format!("event: tick\ndata: {n}\n\n"));
+                stream_chunks(&mut socket, "text/event-stream", ticks).await;
+            }
+        }
+    })
+    .await;
+    format!("http://{address}")
+}
+
+#[test]
+fn test_decoder_splits_events_across_chunks() {
+    let mut sse = Decoder::new(Format::Sse);
+    assert!(sse.feed(b": comment\n\nevent: update\r\ndata: {\"a\":").is_empty());
+    assert_eq!(sse.feed(b"1}\r\n\r\ndata: line one\ndata: line two\n"), vec![json!({ "event": "update", "data": { "a": 1 } })]);
+    assert_eq!(sse.finish(), vec![json!({ "event": "message", "data": "line one\nline two" })]);
+
+    let mut ndjson = Decoder::new(Format::Ndjson);
+    assert_eq!(ndjson.feed(b"{\"n\":1}\n\n{\"n\""), vec![json!({ "n": 1 })]);
+    assert_eq!(ndjson.feed(b":2}\nnot json\n"), vec![json!({ "n": 2 }), json!("not json")]);
+    assert!(ndjson.finish().is_empty());
+
+    assert_eq!(Format::from_content_type("text/event-stream; charset=utf-8"), Format::Sse);
+    assert_eq!(Format::from_content_type("application/x-ndjson"), Format::Ndjson);
+    assert_eq!(Format::from_content_type("text/plain"), Format::Chunks);
+}
+
+#[tokio::test]
+async fn test_stream_events_counts_and_order() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_server().await;
+    let file = write_suite(
+        &dir,
+        &base_url,
+        r#"
+[[tests]]
+name = "job progress"
+method = "GET"
+endpoint = "/jobs/1/progress"
+expected_status = 200
+stream = { min_events = 4, max_events = 4, events = [
+  { event = "progress", data = { percent = 25 } },
+  { event = "progress", id = "3" },
+  { event = "done", data = { status = "complete" } },
+] }
+store = { job = "$[3].data.job" }
+
+[[tests]]
+name = "completion"
+method = "GET"
+endpoint = "/completions"
+expected_status = 200
+stream = { until = "body.$.done == true", events = [{ token = "Hel" }, { token = "lo" }] }
+assertions = [{ type = "PathContains", value = ["$[2]", { token = "!" }] }]
+
+[[tests]]
+name = "out of order"
+method = "GET"
+endpoint = "/jobs/1/progress"
+expected_status = 200
+stream = { events = [{ event = "done" }, { event = "progress" }] }
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner.load_suite(Some(&file), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+
+    let progress = runner.run_test(&suite.tests[0], &client, dir.path(), None).await;
+    assert!(progress.success, "{:?}", progress.messages);
+    assert_eq!(runner.variables["job"], json!(1));
+
+    let completion = runner.run_test(&suite.tests[1], &client, dir.path(), None).await;
+    assert!(completion.success, "{:?}", completion.messages);
+
+    let out_of_order = runner.run_test(&suite.tests[2], &client, dir.path(), None).await;
+    assert!(!out_of_order.success);
+    let messages = out_of_order.messages.join("\n");
+    assert!(messages.contains("Event 2 {\"event\":\"progress\"} not received in order"), "{messages}");
+}
+
+#[tokio::test]
+async fn test_endless_stream_stops_at_until_or_window() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_server().await;
+    let file = write_suite(
+        &dir,
+        &base_url,
+        r#"
+[[tests]]
+name = "until"
+method = "GET"
+endpoint = "/ticks"
+expected_status = 200
+stream = { until = "body.$.data >= 3" }
+assertions = [{ type = "Exact", value = [
+  { event = "tick", data = 1 },
+  { event = "tick", data = 2 },
+  { event = "tick", data = 3 },
+] }]
+
+[[tests]]
+name = "window"
+method = "GET"
+endpoint = "/ticks"
+expected_status = 200
+stream = { window_ms = 200, min_events = 2, max_events = 20 }
+
+[[tests]]
+name = "until never holds"
+method = "GET"
+endpoint = "/ticks"
+expected_status = 200
+stream = { until = "body.$.event == done", window_ms = 100 }
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner.load_suite(Some(&file), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+
+    let until = runner.run_test(&suite.tests[0], &client, dir.path(), None).await;
+    assert!(until.success, "{:?}", until.messages);
+
+    let window = runner.run_test(&suite.tests[1], &client, dir.path(), None).await;
+    assert!(window.success, "{:?}", window.messages);
+
+    let never = runner.run_test(&suite.tests[2], &client, dir.path(), None).await;
+    assert!(!never.success);
+    let messages = never.messages.join("\n");
+    assert!(messages.contains("`until` did not hold within 100ms"), "{messages}");
+}
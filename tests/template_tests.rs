@@ -0,0 +1,188 @@
+use catalyst::checker::parse_tests;
+use catalyst::engine::execution;
+use catalyst::http::client::HttpClient;
+use catalyst::models::test::JsonAssertion;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::{Response, start_http_server, write_tests_file};
+
+/// Echoes the request headers as `{"headers": {name: [values]}}`
+async fn start_server() -> String {
+    start_http_server(|request| {
+        let mut headers: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (name, value) in &request.headers {
+            headers.entry(name).or_default().push(value);
+        }
+        Response::json(json!({ "headers": headers }))
+    })
+    .await
+}
+
+const TEMPLATED_SUITE: &str = r#"
+[config]
+base_url = "http://localhost"
+default_headers = { "Accept" = "text/plain", "X-Client" = "catalyst" }
+
+[templates.json]
+method = "GET"
+expected_status = 200
+headers = { "Accept" = "application/json" }
+assertions = [{ type = "Contains", value = { ok = true } }]
+
+[templates.authed_json]
+extends = "json"
+auth_method = "Bearer"
+auth_token = "{{token}}"
+query_params = { "version" = "2" }
+
+[[tests]]
+name = "list users"
+extends = "authed_json"
+endpoint = "/users"
+headers = { "X-Trace" = "abc" }
+query_params = { "page" = "1" }
+assertions = [{ type = "Regex", value = "users" }]
+
+[[tests]]
+name = "create user"
+extends = "authed_json"
+method = "POST"
+endpoint = "/users"
+expected_status = 201
+"#;
+
+#[test]
+fn test_templates_merge_field_by_field() {
+    let dir = TempDir::new().unwrap();
+    let suite = parse_tests(Some(&write_tests_file(&dir, TEMPLATED_SUITE))).unwrap();
+
+    let list = &suite.tests[0];
+    assert_eq!(list.extends.as_deref(), Some("authed_json"));
+    assert_eq!(list.method, "GET");
+    assert_eq!(list.expected_status, 200);
+    assert_eq!(list.auth_method.as_deref(), Some("Bearer"));
+
+    let headers = list.headers.as_ref().unwrap();
+    assert_eq!(headers["Accept"], "application/json");
+    assert_eq!(headers["X-Trace"], "abc");
+    let params = list.query_params.as_ref().unwrap();
+    assert_eq!(params["version"], "2");
+    assert_eq!(params["page"], "1");
+
+    // Inherited assertions come first, the test's own are appended
+    let assertions = list.assertions.as_ref().unwrap();
+    assert_eq!(assertions.len(), 2);
+    assert!(matches!(assertions[0], JsonAssertion::Contains(_)));
+    assert!(matches!(assertions[1], JsonAssertion::Regex(_)));
+
+    // Scalars set by the test replace the template's
+    let create = &suite.tests[1];
+    assert_eq!(create.method, "POST");
+    assert_eq!(create.expected_status, 201);
+    assert_eq!(create.assertions.as_ref().unwrap().len(), 1);
+}
+
+#[test]
+fn test_unknown_templates_and_cycles_are_reported() {
+    let dir = TempDir::new().unwrap();
+    let file = write_tests_file(
+        &dir,
+        r#"
+        [config]
+        base_url = "http://localhost"
+
+        [templates.a]
+        extends = "b"
+
+        [templates.b]
+        extends = "a"
+
+        [templates.c]
+        extends = "missing"
+
+        [[tests]]
+        name = "orphan"
+        extends = "nowhere"
+        method = "GET"
+        endpoint = "/"
+        expected_status = 200
+        "#,
+    );
+
+    let err = parse_tests(Some(&file)).unwrap_err();
+    assert!(err.contains("Template inheritance cycle: a -> b -> a"), "{err}");
+    assert_eq!(err.matches("cycle").count(), 1, "{err}");
+    assert!(err.contains("Template `c` extends unknown template `missing`"), "{err}");
+    assert!(err.contains("Test `orphan` extends unknown template `nowhere`"), "{err}");
+}
+
+#[tokio::test]
+async fn test_template_headers_and_auth_are_sent() {
+    let base_url = start_server().await;
+    let dir = TempDir::new().unwrap();
+    let file = write_tests_file(&dir, &TEMPLATED_SUITE.replace("http://localhost", &base_url));
+    let suite = parse_tests(Some(&file)).unwrap();
+
+    let client = HttpClient::new(&suite.config);
+    let mut vars = HashMap::from([("token".to_string(), Value::from("s3cret"))]);
+    let result = execution::run(&client, &suite.tests[0], Path::new("."), &mut vars, false).await;
+
+    let headers = &result.body.unwrap()["headers"];
+    // The template's Accept replaces the default instead of being sent twice
+    assert_eq!(headers["accept"], json!(["application/json"]));
+    assert_eq!(headers["x-client"], json!(["catalyst"]));
+    assert_eq!(headers["x-trace"], json!(["abc"]));
+    assert_eq!(headers["authorization"], json!(["Bearer s3cret"]));
+}
+
+#[tokio::test]
+async fn test_config_auth_is_sent_unless_overridden() {
+    let base_url = start_server().await;
+    let dir = TempDir::new().unwrap();
+    let file = write_tests_file(
+        &dir,
+        &format!(
+            r#"
+[config]
+base_url = "{base_url}"
+auth_method = "Basic"
+auth_token = "{{{{token}}}}"
+
+[[tests]]
+name = "config auth"
+method = "GET"
+endpoint = "/"
+expected_status = 200
+
+[[tests]]
+name = "test auth"
+method = "GET"
+endpoint = "/"
+expected_status = 200
+auth_method = "Bearer"
+auth_token = "other"
+
+[[tests]]
+name = "explicit header"
+method = "GET"
+endpoint = "/"
+expected_status = 200
+headers = {{ "Authorization" = "Token abc" }}
+"#
+        ),
+    );
+    let suite = parse_tests(Some(&file)).unwrap();
+
+    let client = HttpClient::new(&suite.config);
+    let mut vars = HashMap::from([("token".to_string(), Value::from("s3cret"))]);
+    let mut sent = Vec::new();
+    for test in &suite.tests {
+        let result = execution::run(&client, test, Path::new("."), &mut vars, false).await;
+        sent.push(result.body.unwrap()["headers"]["authorization"].clone());
+    }
+    assert_eq!(sent, [json!(["Basic s3cret"]), json!(["Bearer other"]), json!(["Token abc"])]);
+}
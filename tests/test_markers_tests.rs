@@ -0,0 +1,197 @@
+use catalyst::core::runner::{TestResult, TestRunner};
+use catalyst::output::{ResultCounts, TestSummaryFormatter};
+use serde_json::json;
+use tempfile::TempDir;
+
+mod common;
+use common::{Response, start_http_server, write_suite};
+
+/// `/fail` answers 500, everything else 200
+async fn start_server() -> String {
+    start_http_server(|request| {
+        let status = if request.path == "/fail" { 500 } else { 200 };
+        Response::json(json!({})).status(status)
+    })
+    .await
+}
+
+async fn run_suite(tests: &str, var: Option<&str>) -> TestRunner {
+    let base_url = start_server().await;
+    let dir = TempDir::new().unwrap();
+    let file = write_suite(&dir, &base_url, tests);
+
+    let mut runner = TestRunner::new(true);
+    runner
+        .execute_tests(
+            None,
+            false,
+            Some(file),
+            var.map(str::to_string),
+            true,
+        )
+        .await;
+    runner
+}
+
+fn result<'a>(runner: &'a TestRunner, name: &str) -> &'a TestResult {
+    runner.results.iter().find(|r| r.name == name).unwrap()
+}
+
+const MARKED_SUITE: &str = r#"
+[[tests]]
+name = "works"
+method = "GET"
+endpoint = "/ok"
+expected_status = 200
+
+[[tests]]
+name = "not ready"
+method = "GET"
+endpoint = "/ok"
+expected_status = 200
+skip = "endpoint not deployed yet"
+
+[[tests]]
+name = "prod only skip"
+method = "GET"
+endpoint = "/fail"
+expected_status = 200
+skip_if = "{{env}} == prod"
+
+[[tests]]
+name = "staging only skip"
+method = "GET"
+endpoint = "/ok"
+expected_status = 200
+skip_if = "{{env}} == staging"
+
+[[tests]]
+name = "broken skip"
+method = "GET"
+endpoint = "/ok"
+expected_status = 200
+skip_if = "{{env}} =~ '('"
+
+[[tests]]
+name = "known bug"
+method = "GET"
+endpoint = "/fail"
+expected_status = 200
+xfail = "JIRA-123"
+
+[[tests]]
+name = "fixed bug"
+method = "GET"
+endpoint = "/ok"
+expected_status = 200
+xfail = "JIRA-456"
+"#;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_skip_skip_if_and_xfail() {
+    let runner = run_suite(MARKED_SUITE, Some("env=prod")).await;
+    assert_eq!(runner.results.len(), 7);
+
+    let skipped = result(&runner, "not ready");
+    assert!(skipped.success);
+    assert_eq!(skipped.skipped.as_deref(), Some("endpoint not deployed yet"));
+    assert_eq!(skipped.actual_status, 0);
+
+    let conditional = result(&runner, "prod only skip");
+    assert_eq!(conditional.skipped.as_deref(), Some("skip_if {{env}} == prod"));
+    assert!(result(&runner, "staging only skip").skipped.is_none());
+
+    // A condition that cannot be evaluated fails the test rather than skipping it
+    let broken = result(&runner, "broken skip");
+    assert!(!broken.success);
+    assert!(broken.skipped.is_none());
+    assert!(broken.messages[0].starts_with("Invalid `skip_if` condition '{{env}} =~ '('':"), "{:?}", broken.messages);
+
+    let known = result(&runner, "known bug");
+    assert!(known.success);
+    assert_eq!(known.xfailed.as_deref(), Some("JIRA-123"));
+
+    // A test marked xfail that passes needs its marker removed
+    let fixed = result(&runner, "fixed bug");
+    assert!(!fixed.success);
+    assert!(fixed.xfailed.is_none());
+    assert!(fixed.messages.iter().any(|m| m == "Expected to fail (JIRA-456) but passed"));
+
+    assert_eq!(
+        ResultCounts::of(&runner.results),
+        ResultCounts {
+            passed: 2,
+            failed: 2,
+            timed_out: 0,
+            skipped: 2,
+            xfailed: 1,
+        }
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_only_runs_marked_tests() {
+    let suite = r#"
+[[tests]]
+name = "first"
+method = "GET"
+endpoint = "/ok"
+expected_status = 200
+
+[[tests]]
+name = "focused"
+method = "GET"
+endpoint = "/ok"
+expected_status = 200
+only = true
+"#;
+    let runner = run_suite(suite, None).await;
+    let names: Vec<&str> = runner.results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["focused"]);
+}
+
+#[test]
+fn test_summary_counts_each_category() {
+    let results = vec![
+        TestResult {
+            name: "ok".to_string(),
+            success: true,
+            ..Default::default()
+        },
+        TestResult {
+            name: "broken".to_string(),
+            success: false,
+            expected_status: 200,
+            actual_status: 500,
+            ..Default::default()
+        },
+        TestResult {
+            name: "known bug".to_string(),
+            success: true,
+            xfailed: Some("JIRA-123".to_string()),
+            ..Default::default()
+        },
+        TestResult {
+            name: "later".to_string(),
+            success: true,
+            skipped: Some("not deployed".to_string()),
+            ..Default::default()
+        },
+    ];
+
+    let output = TestSummaryFormatter::new(true).format_compact_results(&results, 3, 7);
+    assert!(output.contains("  x known bug (xfail: JIRA-123)"), "{output}");
+    assert!(output.contains("  - later (skipped: not deployed)"), "{output}");
+    assert!(
+        output.contains(
+            "1 tests passed, 1 tests failed, 1 tests xfailed, 1 tests skipped, 3 tests not selected (total: 7)"
+        ),
+        "{output}"
+    );
+
+    // Neither skipped nor expected failures are reported as failures
+    let details = TestSummaryFormatter::new(true).format_failure_details(&results);
+    assert!(details.contains("Test: broken"));
+    assert!(!details.contains("known bug"));
+    assert!(!details.contains("later"));
+}
@@ -0,0 +1,197 @@
+use catalyst::engine::execution::{self, ExecutionResult};
+use catalyst::http::client::HttpClient;
+use catalyst::http::timing::{Phase, Timings};
+use catalyst::models::config::Config;
+use catalyst::models::test::{ResponseTimeLimit, Test};
+use rcgen::{CertifiedKey, generate_simple_self_signed};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer};
+
+mod common;
+use common::{read_request, start_tcp_server, test_from};
+
+const HEADER_DELAY: Duration = Duration::from_millis(150);
+const BODY_DELAY: Duration = Duration::from_millis(100);
+
+/// Answers every request after `HEADER_DELAY`, then sends the second half of
+/// the body `BODY_DELAY` after the first
+async fn respond_slowly<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S) {
+    read_request(&mut stream).await;
+    tokio::time::sleep(HEADER_DELAY).await;
+
+    let body = r#"{"status":"ok","padding":"....."}"#;
+    let (first, second) = body.split_at(body.len() / 2);
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{first}",
+        body.len()
+    );
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.flush().await;
+    tokio::time::sleep(BODY_DELAY).await;
+    let _ = stream.write_all(second.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+async fn start_server() -> u16 {
+    start_tcp_server(respond_slowly).await.port()
+}
+
+async fn start_tls_server() -> u16 {
+    let CertifiedKey { cert, signing_key } =
+        generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![cert.der().clone()],
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(signing_key.serialize_der())),
+        )
+        .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    start_tcp_server(move |socket| {
+        let acceptor = acceptor.clone();
+        async move {
+            if let Ok(stream) = acceptor.accept(socket).await {
+                respond_slowly(stream).await;
+            }
+        }
+    })
+    .await
+    .port()
+}
+
+async fn run(base_url: String, insecure: bool, test: &Test) -> ExecutionResult {
+    let config = Config {
+        base_url,
+        insecure: Some(insecure),
+        ..Default::default()
+    };
+    let client = HttpClient::new(&config);
+    execution::run(&client, test, Path::new("."), &mut HashMap::new(), false).await
+}
+
+const GET_TEST: &str = r#"
+name = "slow"
+method = "GET"
+endpoint = "/"
+expected_status = 200
+"#;
+
+#[tokio::test]
+async fn test_phases_are_measured_over_plain_http() {
+    let port = start_server().await;
+    let result = run(format!("http://localhost:{port}"), false, &test_from(GET_TEST)).await;
+    assert!(result.success, "{:?}", result.errors);
+
+    let timings = result.timings.unwrap();
+    assert!(timings.dns > Duration::ZERO, "{timings:?}");
+    assert!(timings.connect > Duration::ZERO, "{timings:?}");
+    assert_eq!(timings.tls, Duration::ZERO);
+    assert!(timings.ttfb >= HEADER_DELAY, "{timings:?}");
+    assert!(timings.download >= BODY_DELAY - Duration::from_millis(20), "{timings:?}");
+    assert!(timings.total >= timings.dns + timings.connect + timings.ttfb + timings.download);
+    assert_eq!(result.time_ms, timings.total_ms());
+}
+
+#[tokio::test]
+async fn test_ip_literal_skips_dns() {
+    let port = start_server().await;
+    let result = run(format!("http://127.0.0.1:{port}"), false, &test_from(GET_TEST)).await;
+
+    let timings = result.timings.unwrap();
+    assert_eq!(timings.dns, Duration::ZERO);
+    assert!(timings.connect > Duration::ZERO, "{timings:?}");
+}
+
+#[tokio::test]
+async fn test_tls_handshake_is_measured_separately() {
+    let port = start_tls_server().await;
+    let result = run(format!("https://localhost:{port}"), true, &test_from(GET_TEST)).await;
+    assert!(result.success, "{:?}", result.errors);
+
+    let timings = result.timings.unwrap();
+    assert!(timings.connect > Duration::ZERO, "{timings:?}");
+    assert!(timings.tls > Duration::ZERO, "{timings:?}");
+    assert!(timings.ttfb >= HEADER_DELAY, "{timings:?}");
+}
+
+#[tokio::test]
+async fn test_max_response_time_per_phase() {
+    let port = start_server().await;
+    let base_url = format!("http://127.0.0.1:{port}");
+
+    let within = test_from(
+        r#"
+        name = "generous"
+        method = "GET"
+        endpoint = "/"
+        expected_status = 200
+        max_response_time = { connect = 1000, download = 5000 }
+        "#,
+    );
+    let result = run(base_url.clone(), false, &within).await;
+    assert!(result.success, "{:?}", result.errors);
+
+    let over = test_from(
+        r#"
+        name = "ttfb budget"
+        method = "GET"
+        endpoint = "/"
+        expected_status = 200
+        max_response_time = { ttfb = 50 }
+        "#,
+    );
+    let result = run(base_url.clone(), false, &over).await;
+    assert!(!result.success);
+    assert!(result.errors[0].starts_with("TTFB time "), "{:?}", result.errors);
+
+    let total = test_from(
+        r#"
+        name = "total budget"
+        method = "GET"
+        endpoint = "/"
+        expected_status = 200
+        max_response_time = 50
+        "#,
+    );
+    let result = run(base_url, false, &total).await;
+    assert!(!result.success);
+    assert!(result.errors[0].starts_with("Time "), "{:?}", result.errors);
+}
+
+#[test]
+fn test_response_time_limit_forms() {
+    let total = test_from(&format!("{GET_TEST}max_response_time = 200"));
+    assert!(matches!(total.max_response_time, Some(ResponseTimeLimit::Total(200))));
+
+    let phases = test_from(&format!("{GET_TEST}max_response_time = {{ tls = 30, total = 500 }}"));
+    assert_eq!(
+        phases.max_response_time.unwrap().limits(),
+        vec![(Phase::Tls, 30), (Phase::Total, 500)]
+    );
+
+    let typo: Result<Test, _> = toml::from_str(&format!("{GET_TEST}max_response_time = {{ tffb = 30 }}"));
+    assert!(typo.is_err());
+}
+
+#[test]
+fn test_timings_display() {
+    let timings = Timings {
+        dns: Duration::from_micros(1500),
+        connect: Duration::from_millis(3),
+        tls: Duration::ZERO,
+        ttfb: Duration::from_millis(120),
+        download: Duration::from_millis(15),
+        total: Duration::from_millis(140),
+    };
+    assert_eq!(
+        timings.to_string(),
+        "140ms (dns 1.5ms, connect 3.0ms, tls 0.0ms, ttfb 120ms, download 15ms)"
+    );
+}
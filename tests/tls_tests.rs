@@ -0,0 +1,250 @@
+use catalyst::engine::execution::{self, ExecutionResult};
+use catalyst::http::client::HttpClient;
+use catalyst::http::tls::{fingerprint, parse_tls_version};
+use catalyst::models::config::{Config, TlsSettings};
+use catalyst::models::test::Test;
+use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, Issuer, KeyPair};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{RootCertStore, ServerConfig};
+
+mod common;
+use common::{Response, read_request, start_tcp_server, test_from, write_response};
+
+/// A private CA with a server certificate for `localhost` and a client certificate,
+/// written as PEM files (plus a PKCS#12 archive of the client identity) into a temp dir
+struct Pki {
+    dir: TempDir,
+    ca_der: CertificateDer<'static>,
+    server_der: CertificateDer<'static>,
+    server_key: Vec<u8>,
+}
+
+fn create_pki() -> Pki {
+    let ca_key = KeyPair::generate().unwrap();
+    let mut ca_params = CertificateParams::new(Vec::new()).unwrap();
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    ca_params.distinguished_name.push(DnType::CommonName, "Catalyst Test CA");
+    let ca_cert = ca_params.self_signed(&ca_key).unwrap();
+    let issuer = Issuer::new(ca_params, ca_key);
+
+    let server_key = KeyPair::generate().unwrap();
+    let mut server_params = CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+    server_params.distinguished_name.push(DnType::CommonName, "localhost");
+    server_params.distinguished_name.push(DnType::OrganizationName, "Catalyst");
+    let server_cert = server_params.signed_by(&server_key, &issuer).unwrap();
+
+    let client_key = KeyPair::generate().unwrap();
+    let mut client_params = CertificateParams::new(Vec::new()).unwrap();
+    client_params.distinguished_name.push(DnType::CommonName, "catalyst-client");
+    let client_cert = client_params.signed_by(&client_key, &issuer).unwrap();
+
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("ca.pem"), ca_cert.pem()).unwrap();
+    fs::write(dir.path().join("client.pem"), client_cert.pem()).unwrap();
+    fs::write(dir.path().join("client.key"), client_key.serialize_pem()).unwrap();
+
+    let mut keystore = p12_keystore::KeyStore::new();
+    let chain = p12_keystore::PrivateKeyChain::new(
+        client_key.serialize_der(),
+        [1u8; 20],
+        vec![p12_keystore::Certificate::from_der(client_cert.der()).unwrap()],
+    );
+    keystore.add_entry("client", p12_keystore::KeyStoreEntry::PrivateKeyChain(chain));
+    fs::write(
+        dir.path().join("client.p12"),
+        keystore.writer("changeit").write().unwrap(),
+    )
+    .unwrap();
+
+    Pki {
+        dir,
+        ca_der: ca_cert.der().clone(),
+        server_der: server_cert.der().clone(),
+        server_key: server_key.serialize_der(),
+    }
+}
+
+/// HTTPS server that requires a client certificate signed by the test CA
+async fn start_mtls_server(pki: &Pki) -> String {
+    let mut roots = RootCertStore::empty();
+    roots.add(pki.ca_der.clone()).unwrap();
+    let verifier = WebPkiClientVerifier::builder(Arc::new(roots)).build().unwrap();
+    let config = ServerConfig::builder()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(
+            vec![pki.server_der.clone()],
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(pki.server_key.clone())),
+        )
+        .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let address = start_tcp_server(move |socket| {
+        let acceptor = acceptor.clone();
+        async move {
+            let Ok(mut stream) = acceptor.accept(socket).await else {
+                return;
+            };
+            read_request(&mut stream).await;
+            write_response(&mut stream, &Response::json(json!({ "secure": true }))).await;
+        }
+    })
+    .await;
+    format!("https://localhost:{}", address.port())
+}
+
+async fn run(base_url: String, tls: TlsSettings, dir: &Path, test: &Test) -> ExecutionResult {
+    let config = Config {
+        base_url,
+        tls,
+        ..Default::default()
+    };
+    let client = HttpClient::new(&config).with_base_dir(dir);
+    execution::run(&client, test, dir, &mut HashMap::new(), false).await
+}
+
+fn pem_identity() -> TlsSettings {
+    TlsSettings {
+        ca_cert: Some("ca.pem".to_string()),
+        client_cert: Some("client.pem".to_string()),
+        client_key: Some("client.key".to_string()),
+        ..Default::default()
+    }
+}
+
+const SECURE_TEST: &str = r#"
+name = "secure"
+method = "GET"
+endpoint = "/"
+expected_status = 200
+expected_body = { secure = true }
+"#;
+
+#[tokio::test]
+async fn test_mtls_with_pem_identity_and_private_ca() {
+    let pki = create_pki();
+    let base_url = start_mtls_server(&pki).await;
+    let mut tls = pem_identity();
+    tls.min_tls_version = Some("1.2".to_string());
+
+    let result = run(base_url, tls, pki.dir.path(), &test_from(SECURE_TEST)).await;
+    assert!(result.success, "{:?}", result.errors);
+}
+
+#[tokio::test]
+async fn test_mtls_with_pkcs12_identity() {
+    let pki = create_pki();
+    let base_url = start_mtls_server(&pki).await;
+    let tls = TlsSettings {
+        ca_cert: Some("ca.pem".to_string()),
+        client_cert: Some("client.p12".to_string()),
+        client_cert_password: Some("changeit".to_string()),
+        ..Default::default()
+    };
+
+    let result = run(base_url, tls, pki.dir.path(), &test_from(SECURE_TEST)).await;
+    assert!(result.success, "{:?}", result.errors);
+}
+
+#[tokio::test]
+async fn test_handshake_fails_without_ca_or_client_cert() {
+    let pki = create_pki();
+    let base_url = start_mtls_server(&pki).await;
+
+    let no_ca = TlsSettings {
+        ca_cert: None,
+        ..pem_identity()
+    };
+    let result = run(base_url.clone(), no_ca, pki.dir.path(), &test_from(SECURE_TEST)).await;
+    assert!(!result.success);
+    assert_eq!(result.status.1, 0);
+
+    let no_identity = TlsSettings {
+        ca_cert: Some("ca.pem".to_string()),
+        ..Default::default()
+    };
+    let result = run(base_url, no_identity, pki.dir.path(), &test_from(SECURE_TEST)).await;
+    assert!(!result.success);
+    assert_eq!(result.status.1, 0);
+}
+
+#[tokio::test]
+async fn test_insecure_accepts_an_unknown_server_certificate() {
+    let pki = create_pki();
+    let base_url = start_mtls_server(&pki).await;
+    let config = Config {
+        base_url,
+        tls: TlsSettings {
+            ca_cert: None,
+            ..pem_identity()
+        },
+        insecure: Some(true),
+        ..Default::default()
+    };
+    let client = HttpClient::new(&config).with_base_dir(pki.dir.path());
+
+    let result = execution::run(&client, &test_from(SECURE_TEST), pki.dir.path(), &mut HashMap::new(), false).await;
+    assert!(result.success, "{:?}", result.errors);
+}
+
+#[tokio::test]
+async fn test_missing_certificate_file_is_reported() {
+    let pki = create_pki();
+    let tls = TlsSettings {
+        ca_cert: Some("missing.pem".to_string()),
+        ..Default::default()
+    };
+
+    let result = run("https://localhost:9".to_string(), tls, pki.dir.path(), &test_from(SECURE_TEST)).await;
+    assert!(!result.success);
+    assert!(result.errors[0].contains("Cannot read ca_cert"), "{:?}", result.errors);
+}
+
+#[tokio::test]
+async fn test_peer_certificate_assertions() {
+    let pki = create_pki();
+    let base_url = start_mtls_server(&pki).await;
+    let pin = fingerprint(&pki.server_der);
+
+    let passing = test_from(&format!(
+        r#"
+        name = "certificate"
+        method = "GET"
+        endpoint = "/"
+        expected_status = 200
+        expected_certificate = {{ subject = "cn=localhost", issuer = "Catalyst Test CA", min_days_valid = 30, sha256_fingerprint = "{}" }}
+        "#,
+        pin.replace(':', "").to_lowercase()
+    ));
+    let result = run(base_url.clone(), pem_identity(), pki.dir.path(), &passing).await;
+    assert!(result.success, "{:?}", result.errors);
+
+    let failing = test_from(
+        r#"
+        name = "wrong certificate"
+        method = "GET"
+        endpoint = "/"
+        expected_status = 200
+        expected_certificate = { subject = "CN=api.example.com", sha256_fingerprint = "00:11" }
+        "#,
+    );
+    let result = run(base_url, pem_identity(), pki.dir.path(), &failing).await;
+    assert!(!result.success);
+    assert_eq!(result.errors.len(), 2, "{:?}", result.errors);
+    assert!(result.errors[0].contains("Certificate subject is 'CN=localhost, O=Catalyst'"));
+    assert!(result.errors[1].contains(&pin));
+}
+
+#[test]
+fn test_parse_tls_version() {
+    assert!(parse_tls_version("1.2").is_ok());
+    assert!(parse_tls_version("TLS1.3").is_ok());
+    assert!(parse_tls_version("1.0").is_err());
+}
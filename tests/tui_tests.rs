@@ -0,0 +1,131 @@
+use catalyst::core::runner::TestRunner;
+use catalyst::http::client::HttpClient;
+use catalyst::tui::app::{Action, App, Pane, RunEvent, Status, TestRecord};
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use serde_json::json;
+use tempfile::TempDir;
+
+mod common;
+use common::{Response, start_http_server, write_tests_file};
+
+/// Answers every request with `{"id": 7, "name": "ada"}`
+async fn start_server() -> String {
+    start_http_server(|_| Response::json(json!({"id": 7, "name": "ada"})).header("X-Request-Id", "r-1")).await
+}
+
+const SUITE: &str = r#"
+[config]
+base_url = "http://localhost"
+default_headers = { "Accept" = "application/json" }
+
+[[tests]]
+name = "Create user"
+method = "POST"
+endpoint = "/users"
+query_params = { "notify" = "{{notify}}" }
+headers = { "Authorization" = "Bearer {{token}}" }
+body = { name = "ada" }
+expected_status = 200
+assertions = [{ type = "Contains", value = { name = "ada" } }, { type = "Contains", value = { name = "bob" } }]
+store = { user_id = "$.id" }
+
+[[tests]]
+name = "Health"
+method = "GET"
+endpoint = "/health"
+expected_status = 200
+
+[[tests]]
+name = "Delete user"
+method = "DELETE"
+endpoint = "/users/{{user_id}}"
+expected_status = 200
+"#;
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::from(code)
+}
+
+fn load(dir: &TempDir, base_url: &str) -> (TestRunner, catalyst::models::suite::TestSuite, String) {
+    let file = write_tests_file(dir, &SUITE.replace("http://localhost", base_url));
+    let mut runner = TestRunner::new(true);
+    let suite = runner
+        .load_suite(Some(&file), Some("token=s3cret-token,notify=true".to_string()))
+        .unwrap();
+    (runner, suite, file)
+}
+
+#[test]
+fn test_keys_filter_select_and_queue_runs() {
+    let dir = TempDir::new().unwrap();
+    let (_, suite, _) = load(&dir, "http://localhost");
+    let mut app = App::new(suite.tests, suite.config.base_url);
+
+    for c in "/user".chars() {
+        assert_eq!(app.handle_key(key(KeyCode::Char(c))), Action::None);
+    }
+    assert_eq!(app.handle_key(key(KeyCode::Enter)), Action::None);
+    assert_eq!(app.visible(), vec![0, 2]);
+
+    app.handle_key(key(KeyCode::Down));
+    assert_eq!(app.current().unwrap().test.name, "Delete user");
+    assert_eq!(app.handle_key(key(KeyCode::Enter)), Action::Run(vec![2]));
+    // One batch at a time
+    assert_eq!(app.handle_key(key(KeyCode::Char('a'))), Action::None);
+    app.apply(RunEvent::Idle);
+    assert_eq!(app.entries[2].status, Status::NotRun);
+
+    assert_eq!(app.handle_key(key(KeyCode::Char('a'))), Action::Run(vec![0, 2]));
+    assert_eq!(app.entries[0].status, Status::Queued);
+    app.apply(RunEvent::Started(0));
+    assert_eq!(app.entries[0].status, Status::Running);
+
+    app.handle_key(key(KeyCode::Tab));
+    assert_eq!(app.pane, Pane::Response);
+    app.handle_key(key(KeyCode::BackTab));
+    app.handle_key(key(KeyCode::BackTab));
+    assert_eq!(app.pane, Pane::Variables);
+    assert_eq!(app.handle_key(key(KeyCode::Char('q'))), Action::Quit);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_detail_panes_show_the_exchange() {
+    let base_url = start_server().await;
+    let dir = TempDir::new().unwrap();
+    let (mut runner, suite, _) = load(&dir, &base_url);
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+
+    let before = runner.variables.clone();
+    let result = runner.run_test(&suite.tests[0], &client, dir.path(), None).await;
+    let record = TestRecord::new(result, &before, &runner.variables);
+
+    let mut app = App::new(suite.tests.clone(), suite.config.base_url.clone());
+    app.apply(RunEvent::Finished(0, Box::new(record)));
+    app.apply(RunEvent::Idle);
+    assert_eq!(app.entries[0].status, Status::Failed);
+
+    let request = app.detail_lines();
+    assert_eq!(request[0], format!("POST {base_url}/users?notify=true"));
+    assert!(request.contains(&"  Accept: application/json".to_string()), "{request:?}");
+    assert!(request.contains(&"  Authorization: Bearer ****".to_string()), "{request:?}");
+    assert!(request.contains(&"    \"name\": \"ada\"".to_string()), "{request:?}");
+
+    app.pane = Pane::Response;
+    let response = app.detail_lines();
+    assert_eq!(response[0], "Status: 200 (expected 200)");
+    assert!(response.contains(&"  x-request-id: r-1".to_string()), "{response:?}");
+    assert!(response.contains(&"    \"id\": 7,".to_string()), "{response:?}");
+
+    app.pane = Pane::Assertions;
+    let assertions = app.detail_lines();
+    assert_eq!(assertions[0], "✓ Status 200");
+    assert!(assertions[1].starts_with("✓ Contains"), "{assertions:?}");
+    assert!(assertions[2].starts_with("✗ Contains"), "{assertions:?}");
+
+    app.pane = Pane::Variables;
+    let variables = app.detail_lines();
+    assert!(variables.contains(&"  notify = true".to_string()), "{variables:?}");
+    assert!(variables.contains(&"  token = ****".to_string()), "{variables:?}");
+    // Stored only when the assertions hold
+    assert!(!variables.iter().any(|v| v.contains("user_id")), "{variables:?}");
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use catalyst::engine::variables::{replace_variables_in_json, store_variables};
+    use catalyst::utils::string::replace_variables;
+    use serde_json::{Value, json};
+    use std::collections::HashMap;
+
+    fn store(body: &Value, paths: &[(&str, &str)]) -> HashMap<String, Value> {
+        let store_map = paths
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let mut vars = HashMap::new();
+        store_variables(body, &store_map, &HashMap::new(), &None, 12, &mut vars);
+        vars
+    }
+
+    #[test]
+    fn test_store_keeps_json_types() {
+        let body = json!({
+            "count": 5,
+            "active": true,
+            "user": {"id": 1, "name": "alice"},
+            "tags": ["a", "b"]
+        });
+        let vars = store(
+            &body,
+            &[
+                ("count", "$.count"),
+                ("active", "$.active"),
+                ("user", "$.user"),
+                ("tags", "$.tags"),
+                ("name", "$.user.name"),
+            ],
+        );
+
+        assert_eq!(vars["count"], json!(5));
+        assert_eq!(vars["active"], json!(true));
+        assert_eq!(vars["user"], json!({"id": 1, "name": "alice"}));
+        assert_eq!(vars["tags"], json!(["a", "b"]));
+        assert_eq!(vars["name"], json!("alice"));
+        assert_eq!(vars["response_time_ms"], json!(12));
+    }
+
+    #[test]
+    fn test_whole_placeholder_inserts_typed_value() {
+        let mut vars = HashMap::new();
+        vars.insert("count".to_string(), json!(5));
+        vars.insert("user".to_string(), json!({"id": 1, "roles": ["admin"]}));
+        vars.insert("ids".to_string(), json!([1, 2, 3]));
+
+        let body = json!({
+            "count": "{{count}}",
+            "owner": "{{user}}",
+            "items": ["{{ids}}"]
+        });
+
+        assert_eq!(
+            replace_variables_in_json(&body, &vars),
+            json!({
+                "count": 5,
+                "owner": {"id": 1, "roles": ["admin"]},
+                "items": [[1, 2, 3]]
+            })
+        );
+    }
+
+    #[test]
+    fn test_embedded_placeholder_stays_a_string() {
+        let mut vars = HashMap::new();
+        vars.insert("count".to_string(), json!(5));
+        vars.insert("user".to_string(), json!({"id": 1}));
+
+        let body = json!({"label": "total: {{count}}", "raw": "user={{user}}"});
+
+        assert_eq!(
+            replace_variables_in_json(&body, &vars),
+            json!({"label": "total: 5", "raw": "user={\"id\":1}"})
+        );
+    }
+
+    #[test]
+    fn test_undefined_whole_placeholder_is_left_untouched() {
+        let vars = HashMap::new();
+        let body = json!({"id": "{{missing}}"});
+
+        assert_eq!(replace_variables_in_json(&body, &vars), body);
+    }
+
+    #[test]
+    fn test_string_interpolation_of_typed_values() {
+        let mut vars = HashMap::new();
+        vars.insert("id".to_string(), json!(42));
+        vars.insert("name".to_string(), json!("bob"));
+        vars.insert("flag".to_string(), json!(false));
+
+        assert_eq!(
+            replace_variables("/users/{{id}}/{{name}}?active={{flag}}", &vars),
+            "/users/42/bob?active=false"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use catalyst::checker::references::find_undefined_variables;
+    use catalyst::engine::execution;
+    use catalyst::http::client::HttpClient;
+    use catalyst::models::suite::TestSuite;
+    use catalyst::models::test::Test;
+    use serde_json::{Value, json};
+    use std::collections::{HashMap, HashSet};
+    use std::path::Path;
+
+    fn suite(content: &str) -> TestSuite {
+        toml::from_str(content).unwrap()
+    }
+
+    const SUITE: &str = r#"
+[config]
+base_url = "http://127.0.0.1:9"
+
+[config.env]
+store = { "tenant" = "acme" }
+
+[[setup]]
+run = "echo"
+args = ["{{seed}}"]
+capture = { var = "setup_out" }
+
+[[tests]]
+name = "Login"
+method = "POST"
+endpoint = "/{{tenant}}/login"
+body = { "user" = "{{username}}", "note" = "${{HOME}} {{file:data.txt}}" }
+expected_status = 200
+store = { "token" = "$.token" }
+
+[[tests]]
+name = "Profile"
+method = "GET"
+endpoint = "/users/{{user_id}}"
+headers = { "Authorization" = "Bearer {{token}}", "X-Trace" = "{{header_x-request-id}}" }
+query_params = { "from" = "{{setup_out}}" }
+expected_status = 200
+"#;
+
+    #[test]
+    fn test_static_pass_reports_undefined_variables() {
+        let undefined = find_undefined_variables(&suite(SUITE), &HashSet::new());
+        let mut names: Vec<_> = undefined.iter().map(|r| r.name.as_str()).collect();
+        names.sort_unstable();
+
+        assert_eq!(names, vec!["seed", "user_id", "username"]);
+        assert!(
+            undefined
+                .iter()
+                .any(|r| r.name == "user_id" && r.location == "test `Profile` endpoint")
+        );
+        assert!(
+            undefined
+                .iter()
+                .any(|r| r.name == "seed" && r.location == "setup step 1")
+        );
+    }
+
+    #[test]
+    fn test_static_pass_accepts_provided_variables() {
+        let provided: HashSet<String> = ["seed", "user_id", "username"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert!(find_undefined_variables(&suite(SUITE), &provided).is_empty());
+    }
+
+    fn profile_test() -> Test {
+        toml::from_str(
+            r#"
+name = "Profile"
+method = "GET"
+endpoint = "/users/{{user_id}}"
+headers = { "Authorization" = "Bearer {{token}}" }
+query_params = { "page" = "{{page}}" }
+body = { "filter" = { "owner" = "{{owner}}" } }
+expected_status = 200
+"#,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_strict_mode_fails_before_sending() {
+        let config = suite(SUITE).config;
+        let client = HttpClient::new(&config);
+        let mut vars: HashMap<String, Value> = HashMap::new();
+        vars.insert("token".to_string(), json!("abc"));
+
+        let result =
+            execution::run(&client, &profile_test(), Path::new("."), &mut vars, false).await;
+
+        assert!(!result.success);
+        assert_eq!(result.status, (200, 0));
+        assert_eq!(result.errors.len(), 1);
+        let message = &result.errors[0];
+        assert!(message.contains("{{user_id}} in endpoint"));
+        assert!(message.contains("{{page}} in query param 'page'"));
+        assert!(message.contains("{{owner}} in body at $.filter.owner"));
+        assert!(!message.contains("{{token}}"));
+    }
+
+    #[tokio::test]
+    async fn test_allow_undefined_sends_the_request() {
+        let config = suite(SUITE).config;
+        let client = HttpClient::new(&config);
+        let mut vars = HashMap::new();
+
+        let result =
+            execution::run(&client, &profile_test(), Path::new("."), &mut vars, true).await;
+
+        // Nothing listens on port 9, so the request itself fails
+        assert!(!result.success);
+        assert!(!result.errors[0].contains("Unresolved variables"));
+    }
+}
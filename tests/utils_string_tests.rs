@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use catalyst::utils::string::replace_variables;
+    use serde_json::Value;
     use std::{collections::HashMap, env};
 
     fn with_env_var<F>(key: &str, value: &str, test: F)
@@ -17,7 +18,7 @@ mod tests {
     #[test]
     fn test_replace_variables_single() {
         let mut variables = HashMap::new();
-        variables.insert("name".to_string(), "John".to_string());
+        variables.insert("name".to_string(), Value::from("John"));
         let result = replace_variables("Hello {{name}}!", &variables);
         assert_eq!(result, "Hello John!");
     }
@@ -25,8 +26,8 @@ mod tests {
     #[test]
     fn test_replace_variables_multiple() {
         let mut variables = HashMap::new();
-        variables.insert("name".to_string(), "John".to_string());
-        variables.insert("age".to_string(), "30".to_string());
+        variables.insert("name".to_string(), Value::from("John"));
+        variables.insert("age".to_string(), Value::from("30"));
         let result = replace_variables("{{name}} is {{age}} years old.", &variables);
         assert_eq!(result, "John is 30 years old.");
     }
@@ -34,7 +35,7 @@ mod tests {
     #[test]
     fn test_replace_variables_no_match() {
         let mut variables = HashMap::new();
-        variables.insert("name".to_string(), "John".to_string());
+        variables.insert("name".to_string(), Value::from("John"));
         let result = replace_variables("Hello world!", &variables);
         assert_eq!(result, "Hello world!");
     }
@@ -42,7 +43,7 @@ mod tests {
     #[test]
     fn test_replace_variables_partial_match() {
         let mut variables = HashMap::new();
-        variables.insert("name".to_string(), "John".to_string());
+        variables.insert("name".to_string(), Value::from("John"));
         let result = replace_variables("Hello {{name}}, welcome to {{city}}!", &variables);
         assert_eq!(result, "Hello John, welcome to {{city}}!");
     }
@@ -60,7 +61,7 @@ mod tests {
     fn test_replace_env_and_test_vars() {
         with_env_var("API_KEY", "secret123", || {
             let mut variables = HashMap::new();
-            variables.insert("username".to_string(), "john_doe".to_string());
+            variables.insert("username".to_string(), Value::from("john_doe"));
 
             let result =
                 replace_variables("Auth: Bearer ${{API_KEY}}, User: {{username}}", &variables);
@@ -72,7 +73,7 @@ mod tests {
     fn test_replace_variables_json() {
         with_env_var("TOKEN", "xyz789", || {
             let mut variables = HashMap::new();
-            variables.insert("user_id".to_string(), "42".to_string());
+            variables.insert("user_id".to_string(), Value::from("42"));
 
             let result = replace_variables(
                 r#"{
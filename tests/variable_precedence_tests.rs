@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use catalyst::engine::variables::{ResolvedVariable, VariableSource, resolve_variables};
+    use catalyst::models::suite::TestSuite;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::env;
+
+    const SUITE: &str = r#"
+[config]
+base_url = "http://localhost"
+
+[config.env]
+store = { "tenant" = "acme", "region" = "eu", "api_url" = "${{CATALYST_PRECEDENCE_HOST}}/api" }
+
+[config.profiles.staging]
+region = "us"
+tenant = "staging-{{tenant}}"
+
+[[tests]]
+name = "Uses everything"
+method = "GET"
+endpoint = "/{{tenant}}/{{region}}/{{CATALYST_PRECEDENCE_USER}}/{{CATALYST_PRECEDENCE_SHADOWED}}"
+expected_status = 200
+"#;
+
+    fn suite() -> TestSuite {
+        toml::from_str(SUITE).unwrap()
+    }
+
+    fn find<'a>(vars: &'a [ResolvedVariable], name: &str) -> &'a ResolvedVariable {
+        vars.iter().find(|v| v.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_precedence_chain() {
+        unsafe {
+            env::set_var("CATALYST_PRECEDENCE_HOST", "https://example.test");
+            env::set_var("CATALYST_PRECEDENCE_USER", "from-env");
+            env::set_var("CATALYST_PRECEDENCE_SHADOWED", "from-env");
+            env::set_var("CATALYST_PRECEDENCE_UNUSED", "never-listed");
+        }
+
+        let mut cli = HashMap::new();
+        cli.insert("CATALYST_PRECEDENCE_SHADOWED".to_string(), "from-cli".to_string());
+        cli.insert("region".to_string(), "ap".to_string());
+
+        let vars = resolve_variables(&suite(), Some("staging"), &cli).unwrap();
+
+        assert_eq!(find(&vars, "region").value, json!("ap"));
+        assert_eq!(find(&vars, "region").source, VariableSource::Cli);
+        assert_eq!(find(&vars, "tenant").value, json!("staging-acme"));
+        assert_eq!(
+            find(&vars, "tenant").source,
+            VariableSource::Profile("staging".to_string())
+        );
+        assert_eq!(find(&vars, "api_url").value, json!("https://example.test/api"));
+        assert_eq!(find(&vars, "api_url").source, VariableSource::EnvStore);
+        assert_eq!(find(&vars, "CATALYST_PRECEDENCE_USER").value, json!("from-env"));
+        assert_eq!(
+            find(&vars, "CATALYST_PRECEDENCE_USER").source,
+            VariableSource::ProcessEnv
+        );
+        assert_eq!(
+            find(&vars, "CATALYST_PRECEDENCE_SHADOWED").value,
+            json!("from-cli")
+        );
+        assert!(!vars.iter().any(|v| v.name == "CATALYST_PRECEDENCE_UNUSED"));
+
+        unsafe {
+            env::remove_var("CATALYST_PRECEDENCE_HOST");
+            env::remove_var("CATALYST_PRECEDENCE_USER");
+            env::remove_var("CATALYST_PRECEDENCE_SHADOWED");
+            env::remove_var("CATALYST_PRECEDENCE_UNUSED");
+        }
+    }
+
+    #[test]
+    fn test_env_store_without_profile() {
+        let vars = resolve_variables(&suite(), None, &HashMap::new()).unwrap();
+
+        assert_eq!(find(&vars, "tenant").value, json!("acme"));
+        assert_eq!(find(&vars, "region").value, json!("eu"));
+        assert_eq!(find(&vars, "region").source, VariableSource::EnvStore);
+    }
+
+    #[test]
+    fn test_unknown_profile() {
+        let result = resolve_variables(&suite(), Some("prod"), &HashMap::new());
+        assert_eq!(result.unwrap_err(), "Unknown profile 'prod'");
+    }
+}
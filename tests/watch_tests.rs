@@ -0,0 +1,120 @@
+use catalyst::core::watch::{Rerun, SuiteSnapshot};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+mod common;
+use common::write_tests_file;
+
+const SUITE: &str = r#"
+[config]
+base_url = "http://localhost"
+
+[[tests]]
+name = "login"
+method = "POST"
+endpoint = "/login"
+expected_status = 200
+store = { token = "$.token" }
+
+[[tests]]
+name = "list users"
+method = "GET"
+endpoint = "/users"
+expected_status = 200
+headers = { "Authorization" = "Bearer {{token}}" }
+body_file = "bodies/filter.json"
+
+[[tests]]
+name = "create user"
+method = "POST"
+endpoint = "/users"
+expected_status = 201
+headers = { "Authorization" = "Bearer {{token}}" }
+body = { note = "{{file:notes/user.txt}}" }
+store = { user_id = "$.id" }
+
+[[tests]]
+name = "delete user"
+method = "DELETE"
+endpoint = "/users/{{user_id}}"
+expected_status = 204
+
+[[tests]]
+name = "health"
+method = "GET"
+endpoint = "/health"
+expected_status = 200
+data_file = "regions.csv"
+"#;
+
+fn names(rerun: Rerun) -> Vec<String> {
+    match rerun {
+        Rerun::Tests(names) => names,
+        Rerun::Everything => panic!("expected a partial rerun"),
+    }
+}
+
+#[test]
+fn test_watched_files_include_sidecars() {
+    let dir = TempDir::new().unwrap();
+    let file = write_tests_file(&dir, SUITE);
+    let snapshot = SuiteSnapshot::load(Path::new(&file)).unwrap();
+
+    let root = std::path::absolute(dir.path()).unwrap();
+    let files: Vec<PathBuf> = snapshot.files().into_iter().collect();
+    assert_eq!(
+        files,
+        vec![
+            root.join("bodies/filter.json"),
+            root.join("notes/user.txt"),
+            root.join("regions.csv"),
+            root.join("tests.toml"),
+        ]
+    );
+}
+
+#[test]
+fn test_changed_tests_and_their_dependents_rerun() {
+    let dir = TempDir::new().unwrap();
+    let file = write_tests_file(&dir, SUITE);
+    let original = SuiteSnapshot::load(Path::new(&file)).unwrap();
+    let root = std::path::absolute(dir.path()).unwrap();
+
+    // A sidecar only affects the test reading it
+    let body_changed = HashSet::from([root.join("bodies/filter.json")]);
+    assert_eq!(names(original.affected(&original, &body_changed)), vec!["list users"]);
+    let note_changed = HashSet::from([root.join("notes/user.txt")]);
+    assert_eq!(names(original.affected(&original, &note_changed)), vec!["create user", "delete user"]);
+
+    // Editing login reruns everything using its token, and what uses their variables in turn
+    write_tests_file(&dir, &SUITE.replace("/login", "/v2/login"));
+    let edited = SuiteSnapshot::load(Path::new(&file)).unwrap();
+    let changed = HashSet::from([root.join("tests.toml")]);
+    assert_eq!(
+        names(edited.affected(&original, &changed)),
+        vec!["login", "list users", "create user", "delete user"]
+    );
+
+    // Comments and formatting change nothing
+    write_tests_file(&dir, &format!("# users API\n{SUITE}"));
+    let commented = SuiteSnapshot::load(Path::new(&file)).unwrap();
+    assert!(names(commented.affected(&original, &changed)).is_empty());
+
+    // The config applies to every test
+    write_tests_file(&dir, &SUITE.replace("http://localhost", "http://localhost:8080"));
+    let reconfigured = SuiteSnapshot::load(Path::new(&file)).unwrap();
+    assert_eq!(reconfigured.affected(&original, &changed), Rerun::Everything);
+}
+
+#[test]
+fn test_dependencies_are_added_without_kept_variables() {
+    let dir = TempDir::new().unwrap();
+    let snapshot = SuiteSnapshot::load(Path::new(&write_tests_file(&dir, SUITE))).unwrap();
+
+    assert_eq!(
+        snapshot.with_dependencies(&["delete user".to_string()]),
+        vec!["login", "create user", "delete user"]
+    );
+    assert_eq!(snapshot.with_dependencies(&["health".to_string()]), vec!["health"]);
+}
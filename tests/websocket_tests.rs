@@ -0,0 +1,224 @@
+use catalyst::core::runner::TestRunner;
+use catalyst::http::client::HttpClient;
+use futures_util::{SinkExt, StreamExt};
+use rcgen::{CertifiedKey, generate_simple_self_signed};
+use serde_json::json;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+
+mod common;
+use common::{start_tcp_server, write_suite};
+
+/// Greets with the `Authorization` header it got, then echoes; `/private` refuses the upgrade
+async fn start_echo_server() -> String {
+    let address = start_tcp_server(|stream| async move {
+        let mut auth = String::new();
+        let callback = |request: &Request, response: Response| -> Result<Response, ErrorResponse> {
+            if request.uri().path() == "/private" {
+                let mut refused = ErrorResponse::new(Some("forbidden".to_string()));
+                *refused.status_mut() = tokio_tungstenite::tungstenite::http::StatusCode::FORBIDDEN;
+                return Err(refused);
+            }
+            auth = request
+                .headers()
+                .get("authorization")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            Ok(response)
+        };
+        let Ok(mut socket) = tokio_tungstenite::accept_hdr_async(stream, callback).await else {
+            return;
+        };
+        let welcome = json!({ "type": "welcome", "auth": auth, "session": "s-42" });
+        let _ = socket.send(Message::text(welcome.to_string())).await;
+        while let Some(Ok(message)) = socket.next().await {
+            match message {
+                Message::Text(text) if text.as_str() == "silence" => {}
+                Message::Text(_) | Message::Binary(_) => {
+                    let _ = socket.send(message).await;
+                }
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+    })
+    .await;
+    format!("http://{address}")
+}
+
+/// wss server with a self-signed certificate for localhost; greets with "hello"
+async fn start_wss_server() -> String {
+    let CertifiedKey { cert, signing_key } = generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![cert.der().clone()],
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(signing_key.serialize_der())),
+        )
+        .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let address = start_tcp_server(move |stream| {
+        let acceptor = acceptor.clone();
+        async move {
+            let Ok(stream) = acceptor.accept(stream).await else {
+                return;
+            };
+            let Ok(mut socket) = tokio_tungstenite::accept_async(stream).await else {
+                return;
+            };
+            let _ = socket.send(Message::text("hello")).await;
+        }
+    })
+    .await;
+    format!("https://localhost:{}", address.port())
+}
+
+#[tokio::test]
+async fn test_websocket_script_sends_expects_and_stores() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_echo_server().await;
+    let file = write_suite(
+        &dir,
+        &base_url,
+        r#"
+[[tests]]
+name = "chat"
+protocol = "ws"
+endpoint = "/chat"
+headers = { "Authorization" = "Bearer t0k" }
+expected_status = 101
+
+[[tests.script]]
+expect = { type = "welcome", auth = "Bearer t0k" }
+store = { session = "$.session" }
+
+[[tests.script]]
+send = { action = "join", session = "{{session}}" }
+expect = { action = "join" }
+assertions = [{ type = "PathRegex", value = ["$.session", "^s-\\d+$"] }]
+
+[[tests.script]]
+send = "ping {{session}}"
+expect = "ping s-42"
+store = { echoed = "$" }
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner.load_suite(Some(&file), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    let result = runner.run_test(&suite.tests[0], &client, dir.path(), None).await;
+    assert!(result.success, "{:?}", result.messages);
+    assert_eq!(runner.variables["session"], json!("s-42"));
+    assert_eq!(runner.variables["echoed"], json!("ping s-42"));
+}
+
+#[tokio::test]
+async fn test_websocket_failures_are_reported() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_echo_server().await;
+    let file = write_suite(
+        &dir,
+        &base_url,
+        r#"
+[[tests]]
+name = "mismatch"
+protocol = "ws"
+endpoint = "/chat"
+expected_status = 101
+script = [{ expect = { type = "goodbye" } }, { send = "never sent", expect = "never sent" }]
+
+[[tests]]
+name = "timeout"
+protocol = "ws"
+endpoint = "/chat"
+expected_status = 101
+script = [{ expect = { type = "welcome" } }, { send = "silence", expect = "anything", timeout_ms = 200 }]
+
+[[tests]]
+name = "refused"
+protocol = "ws"
+endpoint = "/private"
+expected_status = 403
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner.load_suite(Some(&file), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+
+    let mismatch = runner.run_test(&suite.tests[0], &client, dir.path(), None).await;
+    assert!(!mismatch.success);
+    let messages = mismatch.messages.join("\n");
+    assert!(messages.contains("Step 1: received"), "{messages}");
+    assert!(!messages.contains("Step 2"), "{messages}");
+
+    let timeout = runner.run_test(&suite.tests[1], &client, dir.path(), None).await;
+    assert!(!timeout.success);
+    let messages = timeout.messages.join("\n");
+    assert!(messages.contains("Step 2: no message within 200ms"), "{messages}");
+
+    let refused = runner.run_test(&suite.tests[2], &client, dir.path(), None).await;
+    assert!(refused.success, "{:?}", refused.messages);
+}
+
+#[tokio::test]
+async fn test_websocket_messages_with_undefined_variables_are_not_sent() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_echo_server().await;
+    let file = write_suite(
+        &dir,
+        &base_url,
+        r#"
+[[tests]]
+name = "undefined"
+protocol = "ws"
+endpoint = "/chat"
+expected_status = 101
+script = [{ expect = { type = "welcome" } }, { send = { room = "{{room_id}}" }, expect = { room = "{{room_id}}" } }]
+"#,
+    );
+
+    let mut runner = TestRunner::new(true);
+    let suite = runner.load_suite(Some(&file), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    let result = runner.run_test(&suite.tests[0], &client, dir.path(), None).await;
+    assert!(!result.success);
+    let messages = result.messages.join("\n");
+    assert!(messages.contains("Unresolved variables: {{room_id}} in script step 2"), "{messages}");
+}
+
+#[tokio::test]
+async fn test_wss_uses_the_tls_settings() {
+    let dir = TempDir::new().unwrap();
+    let base_url = start_wss_server().await;
+    let test = r#"
+[[tests]]
+name = "greeting"
+protocol = "ws"
+endpoint = "/"
+expected_status = 101
+script = [{ expect = "hello" }]
+"#;
+
+    let mut runner = TestRunner::new(true);
+    let insecure = write_suite(&dir, &base_url, &format!("insecure = true\n{test}"));
+    let suite = runner.load_suite(Some(&insecure), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    let result = runner.run_test(&suite.tests[0], &client, dir.path(), None).await;
+    assert!(result.success, "{:?}", result.messages);
+
+    let verified = write_suite(&dir, &base_url, test);
+    let suite = runner.load_suite(Some(&verified), None).unwrap();
+    let client = HttpClient::new(&suite.config).with_base_dir(dir.path());
+    let result = runner.run_test(&suite.tests[0], &client, dir.path(), None).await;
+    assert!(!result.success);
+    assert!(result.messages.join("\n").contains("UnknownIssuer"), "{:?}", result.messages);
+}